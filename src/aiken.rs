@@ -0,0 +1,190 @@
+use std::collections::BTreeSet;
+
+use amaru_kernel::PlutusData;
+use anyhow::{Result, anyhow, bail};
+
+use crate::{
+    PlutusVersion, RedeemerContext, export::test_name, formatter::indent_lines, script_data,
+};
+
+/// The validator an Aiken test runs a script context against.
+pub struct TestTarget<'a> {
+    /// Module path and validator name, e.g. `dex/pool.pool`.
+    pub validator: &'a str,
+    /// Types the datum and redeemer are cast to, when the handler doesn't take them as Data.
+    pub datum_type: Option<&'a str>,
+    pub redeemer_type: Option<&'a str>,
+    /// Whether the validator is expected to reject the transaction.
+    pub expect_failure: bool,
+}
+
+/// An Aiken module with one test per script context, each running the validator's handler for
+/// the context's purpose on the context's own arguments.
+pub fn test_module(
+    target: &TestTarget<'_>,
+    transaction: &str,
+    contexts: &[RedeemerContext],
+) -> Result<String> {
+    let (module, validator) = target.validator.rsplit_once('.').ok_or_else(|| {
+        anyhow!(
+            "Invalid validator {}, expected <module>.<validator>",
+            target.validator
+        )
+    })?;
+    let alias = module.rsplit('/').next().unwrap_or(module);
+
+    let mut constructors = BTreeSet::from(["ScriptContext"]);
+    let mut tests = Vec::new();
+
+    for context in contexts {
+        if context.version != PlutusVersion::PlutusV3 {
+            bail!(
+                "Aiken validators take PlutusV3 contexts, but redeemer {} has a {:?} one",
+                context.redeemer,
+                context.version
+            );
+        }
+
+        let (constructor, pattern, handler, arguments) = match context.purpose.as_str() {
+            "Spend" => (
+                "Spending",
+                "Spending { output, datum }",
+                "spend",
+                "datum, redeemer, output",
+            ),
+            "Mint" => (
+                "Minting",
+                "Minting(policy_id)",
+                "mint",
+                "redeemer, policy_id",
+            ),
+            "Cert" => (
+                "Publishing",
+                "Publishing { certificate, .. }",
+                "publish",
+                "redeemer, certificate",
+            ),
+            "Reward" => (
+                "Withdrawing",
+                "Withdrawing(credential)",
+                "withdraw",
+                "redeemer, credential",
+            ),
+            "Vote" => ("Voting", "Voting(voter)", "vote", "redeemer, voter"),
+            "Propose" => (
+                "Proposing",
+                "Proposing { proposal_procedure, .. }",
+                "propose",
+                "redeemer, proposal_procedure",
+            ),
+            purpose => bail!("No Aiken handler for redeemer purpose {}", purpose),
+        };
+        constructors.insert(constructor);
+
+        let mut body = vec![
+            format!(
+                "expect context: ScriptContext =\n{}",
+                indent_lines(&data_literal(&context.plutus_data), 2)
+            ),
+            format!("expect {} = context.info", pattern),
+        ];
+        match target.redeemer_type {
+            Some(redeemer_type) => body.push(format!(
+                "expect redeemer: {} = context.redeemer",
+                redeemer_type
+            )),
+            None => body.push("let redeemer = context.redeemer".to_string()),
+        }
+        let arguments = match (handler, target.datum_type) {
+            ("spend", Some(datum_type)) => {
+                body.push("expect Some(datum) = datum".to_string());
+                body.push(format!("expect datum: {} = datum", datum_type));
+                arguments.replacen("datum", "Some(datum)", 1)
+            }
+            _ => arguments.to_string(),
+        };
+        body.push(format!(
+            "{}.{}.{}({}, context.transaction)",
+            alias, validator, handler, arguments
+        ));
+
+        tests.push(format!(
+            "test {}_{}_{}(){} {{\n{}\n}}",
+            test_name(transaction),
+            handler,
+            context.index,
+            if target.expect_failure { " fail" } else { "" },
+            indent_lines(&body.join("\n"), 2)
+        ));
+    }
+
+    Ok(format!(
+        "use aiken/builtin\nuse cardano/script_context.{{{}}}\nuse {}\n\n// Generated by nawi from transaction {}.\n\n{}\n",
+        constructors.into_iter().collect::<Vec<_>>().join(", "),
+        module,
+        transaction,
+        tests.join("\n\n")
+    ))
+}
+
+/// Render Data as an Aiken expression, built with the `aiken/builtin` constructors, so that it
+/// can be pasted into a test as is.
+pub fn data_literal(data: &PlutusData) -> String {
+    literal(data, 0)
+}
+
+fn literal(data: &PlutusData, indent: usize) -> String {
+    match data {
+        PlutusData::Constr(constr) => {
+            let fields: Vec<_> = constr
+                .fields
+                .iter()
+                .map(|field| literal(field, indent + 1))
+                .collect();
+            format!(
+                "builtin.constr_data({}, {})",
+                script_data::constructor(constr.tag, constr.any_constructor),
+                list(&fields, indent)
+            )
+        }
+        PlutusData::Map(pairs) => {
+            let pairs: Vec<_> = pairs
+                .iter()
+                .map(|(key, value)| {
+                    format!(
+                        "Pair({}, {})",
+                        literal(key, indent + 1),
+                        literal(value, indent + 1)
+                    )
+                })
+                .collect();
+            format!("builtin.map_data({})", list(&pairs, indent))
+        }
+        PlutusData::Array(elements) => {
+            let elements: Vec<_> = elements
+                .iter()
+                .map(|element| literal(element, indent + 1))
+                .collect();
+            format!("builtin.list_data({})", list(&elements, indent))
+        }
+        PlutusData::BigInt(int) => format!("builtin.i_data({})", script_data::integer(int)),
+        PlutusData::BoundedBytes(bytes) => {
+            format!("builtin.b_data(#\"{}\")", hex::encode(bytes.to_vec()))
+        }
+    }
+}
+
+/// An Aiken list, on one line when short, or else one element per line.
+fn list(elements: &[String], indent: usize) -> String {
+    let inline = elements.join(", ");
+    if elements.is_empty() || (inline.len() <= 60 && !inline.contains('\n')) {
+        return format!("[{}]", inline);
+    }
+
+    let element_indent = "  ".repeat(indent + 1);
+    let elements: Vec<_> = elements
+        .iter()
+        .map(|element| format!("{}{},", element_indent, element))
+        .collect();
+    format!("[\n{}\n{}]", elements.join("\n"), "  ".repeat(indent))
+}
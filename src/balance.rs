@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use amaru_kernel::{AssetName, Hash, MemoizedTransactionOutput, MintedTx};
+use amaru_plutus::script_context::{CurrencySymbol, TransactionOutput};
+
+use crate::{
+    deposits::Deposits,
+    formatter::{ReadableFormatter, indent_lines},
+    resolver::ResolvedUtxos,
+};
+
+/// An asset of the balance: lovelace, or a native asset by policy and name.
+pub type Asset = Option<(Hash<28>, Vec<u8>)>;
+
+/// What the transaction consumes and produces of one asset.
+#[derive(Default)]
+struct Sides {
+    consumed: i128,
+    produced: i128,
+}
+
+/// The value the transaction consumes (inputs, withdrawals, refunds, minted assets) against the
+/// value it produces (outputs, fee, deposits, donation, burnt assets), asset by asset, as the
+/// ledger checks before raising ValueNotConserved. Returns the report and the number of assets
+/// out of balance.
+pub fn describe(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    deposits: &Deposits,
+) -> (String, usize) {
+    let body = &transaction.transaction_body;
+    let mut balance: BTreeMap<Asset, Sides> = BTreeMap::new();
+    balance.entry(None).or_default();
+
+    let mut unresolved = 0;
+    let mut inputs = 0;
+    for input in body.inputs.iter() {
+        match utxos.get(input) {
+            Some(output) => inputs += add(&mut balance, output, |sides| &mut sides.consumed),
+            None => unresolved += 1,
+        }
+    }
+
+    let mut outputs = 0;
+    let mut undecodable = 0;
+    for output in body.outputs.iter() {
+        match MemoizedTransactionOutput::try_from(output.clone()) {
+            Ok(output) => outputs += add(&mut balance, &output, |sides| &mut sides.produced),
+            Err(_) => undecodable += 1,
+        }
+    }
+
+    if let Some(mint) = &body.mint {
+        for (policy, assets) in mint.iter() {
+            for (name, amount) in assets.iter() {
+                let amount = i64::from(*amount) as i128;
+                let sides = balance.entry(Some((*policy, name.to_vec()))).or_default();
+                if amount > 0 {
+                    sides.consumed += amount;
+                } else {
+                    sides.produced -= amount;
+                }
+            }
+        }
+    }
+
+    let withdrawals: u64 = body
+        .withdrawals
+        .iter()
+        .flat_map(|withdrawals| withdrawals.iter())
+        .map(|(_, amount)| *amount)
+        .sum();
+    let fee = body.fee;
+    let donation = body.donation.map(u64::from).unwrap_or_default();
+
+    let lovelace = balance.entry(None).or_default();
+    lovelace.consumed += (withdrawals + deposits.refunds()) as i128;
+    lovelace.produced += (fee + deposits.deposits() + donation) as i128;
+
+    let mut section = String::from("Value preservation:");
+    section.push_str("\n  Consumed:");
+    section.push_str(&format!("\n    {:<13} {} lovelace", "Inputs:", inputs));
+    section.push_str(&format!(
+        "\n    {:<13} {} lovelace",
+        "Withdrawals:", withdrawals
+    ));
+    section.push_str(&format!(
+        "\n    {:<13} {} lovelace",
+        "Refunds:",
+        deposits.refunds()
+    ));
+    section.push_str("\n  Produced:");
+    section.push_str(&format!("\n    {:<13} {} lovelace", "Outputs:", outputs));
+    section.push_str(&format!("\n    {:<13} {} lovelace", "Fee:", fee));
+    section.push_str(&format!(
+        "\n    {:<13} {} lovelace",
+        "Deposits:",
+        deposits.deposits()
+    ));
+    section.push_str(&format!("\n    {:<13} {} lovelace", "Donation:", donation));
+
+    section.push_str(&format!(
+        "\n\n  {:<40} {:>20} {:>20} {:>16}",
+        "Asset", "Consumed", "Produced", "Difference"
+    ));
+    let mut discrepancies = 0;
+    for (asset, sides) in &balance {
+        let difference = sides.consumed - sides.produced;
+        let flag = if difference == 0 {
+            ""
+        } else {
+            discrepancies += 1;
+            "  NOT CONSERVED"
+        };
+        section.push_str(&format!(
+            "\n  {:<40} {:>20} {:>20} {:>16}{}",
+            asset_name(asset),
+            sides.consumed,
+            sides.produced,
+            format!("{:+}", difference),
+            flag
+        ));
+    }
+
+    if let Some(list) = deposits.describe() {
+        section.push_str(&format!("\n\n{}", indent_lines(&list, 2)));
+    }
+    if deposits.unknown() > 0 {
+        section.push_str(&format!(
+            "\n\n  {} deposit(s) or refund(s) of unknown amount are not counted. Use --protocol-params",
+            deposits.unknown()
+        ));
+    }
+    if unresolved > 0 {
+        section.push_str(&format!(
+            "\n  {} input(s) unresolved, their value is missing from the balance",
+            unresolved
+        ));
+    }
+    if undecodable > 0 {
+        section.push_str(&format!(
+            "\n  {} output(s) undecodable, their value is missing from the balance",
+            undecodable
+        ));
+    }
+
+    (section, discrepancies)
+}
+
+/// Add the value of an output to one side of the balance, returning its lovelace.
+fn add(
+    balance: &mut BTreeMap<Asset, Sides>,
+    output: &MemoizedTransactionOutput,
+    side: impl Fn(&mut Sides) -> &mut i128,
+) -> u64 {
+    let value = TransactionOutput::from(output).value;
+    let lovelace = value.ada().unwrap_or_default();
+    *side(balance.entry(None).or_default()) += lovelace as i128;
+
+    for (policy, assets) in value.0.iter() {
+        let CurrencySymbol::Native(policy) = policy else {
+            continue;
+        };
+        for (name, amount) in assets.iter() {
+            let asset = Some((*policy, name.to_vec()));
+            *side(balance.entry(asset).or_default()) += i128::from(*amount);
+        }
+    }
+
+    lovelace
+}
+
+pub fn asset_name(asset: &Asset) -> String {
+    match asset {
+        None => "lovelace".to_string(),
+        Some((policy, name)) => format!(
+            "{}.{}",
+            hex::encode(policy),
+            AssetName::from(name.clone()).format_readable()
+        ),
+    }
+}
@@ -0,0 +1,313 @@
+use std::path::{Path, PathBuf};
+
+use amaru_kernel::{Redeemer, to_cbor};
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{ArgGroup, Args as ClapArgs};
+use serde_json::json;
+
+use crate::{
+    Args, ContextInputs, OutputFormat, RedeemerContext, RedeemerSelector, aiken,
+    build_script_contexts, decode_transaction, fetch_transaction, get_redeemers, parse_hash,
+    protocol::ProtocolVersion,
+    read_cbor_file, repair_if_lenient, resolve_protocol_version, resolve_slot, resolve_transaction,
+    resolver::UtxoResolver,
+    script_data, summary_lines,
+    transaction::{self, WitnessFields},
+    warn_about_transaction,
+};
+
+#[derive(ClapArgs, Debug)]
+#[command(group(
+    ArgGroup::new("source")
+        .required(true)
+        .args(&["dir", "tx_hashes_file"])
+))]
+pub struct BatchArgs {
+    /// Directory of transaction files (`*.cbor`)
+    #[arg(value_name = "DIR")]
+    dir: Option<PathBuf>,
+
+    /// File with one transaction hash per line, each fetched from the provider
+    #[arg(long, value_name = "FILE")]
+    tx_hashes_file: Option<PathBuf>,
+
+    /// Where to write one `<name>.context` file per transaction [default: DIR, or the current
+    /// directory with --tx-hashes-file]
+    #[arg(long, value_name = "DIR", conflicts_with = "ndjson")]
+    out_dir: Option<PathBuf>,
+
+    /// Print one JSON object per script context to stdout instead of writing files
+    #[arg(long)]
+    ndjson: bool,
+}
+
+/// Where a transaction of the batch comes from.
+pub enum Source {
+    File(PathBuf),
+    Hash(String),
+}
+
+impl Source {
+    pub fn name(&self) -> String {
+        match self {
+            Source::File(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            Source::Hash(hash) => hash.clone(),
+        }
+    }
+
+    pub async fn load(&self, resolver: &dyn UtxoResolver) -> Result<Vec<u8>> {
+        match self {
+            Source::File(path) => read_cbor_file(path, "transaction"),
+            Source::Hash(hash) => {
+                fetch_transaction(resolver, &parse_hash(hash, "transaction")?).await
+            }
+        }
+    }
+}
+
+/// Build the script context of every redeemer of every transaction in the batch. A failing
+/// transaction is reported and skipped so that one bad entry doesn't hide the rest of the corpus.
+pub async fn run(args: &Args, batch: &BatchArgs, resolver: &dyn UtxoResolver) -> Result<()> {
+    let sources = sources(batch)?;
+
+    let out_dir = match (&batch.out_dir, &batch.dir) {
+        (Some(out_dir), _) | (None, Some(out_dir)) => out_dir.clone(),
+        (None, None) => PathBuf::from("."),
+    };
+    if !batch.ndjson {
+        std::fs::create_dir_all(&out_dir)
+            .with_context(|| format!("Failed to create directory: {}", out_dir.display()))?;
+    }
+
+    let protocol_version = resolve_protocol_version(args)?;
+
+    if args.summary {
+        return summarize(args, resolver, &sources, protocol_version).await;
+    }
+
+    let mut failed = 0;
+    for source in &sources {
+        let name = source.name();
+        let result = process(
+            args,
+            resolver,
+            source,
+            protocol_version,
+            RedeemerSelector::All,
+        )
+        .await;
+
+        if batch.ndjson {
+            match result {
+                Ok(contexts) => {
+                    for context in contexts {
+                        println!("{}", to_json(args.output, &name, &context));
+                    }
+                }
+                Err(e) => {
+                    failed += 1;
+                    println!(
+                        "{}",
+                        json!({ "transaction": name, "error": format!("{:#}", e) })
+                    );
+                }
+            }
+            continue;
+        }
+
+        match result {
+            Ok(contexts) => {
+                let out_path = out_dir.join(format!("{}.context", name));
+                let contents = if args.output == OutputFormat::Json {
+                    let documents: Vec<_> = contexts.iter().map(RedeemerContext::to_json).collect();
+                    serde_json::to_string_pretty(&documents).expect("JSON values always serialize")
+                } else {
+                    let contents: Vec<_> = contexts
+                        .iter()
+                        .map(|context| context.format_labelled(args.output))
+                        .collect();
+                    let separator = if args.output == OutputFormat::ScriptData {
+                        "\n"
+                    } else {
+                        "\n\n"
+                    };
+                    contents.join(separator)
+                };
+
+                std::fs::write(&out_path, contents)
+                    .with_context(|| format!("Failed to write {}", out_path.display()))?;
+                eprintln!("{} -> {}", name, out_path.display());
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}: {:#}", name, e);
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} transaction(s) failed", failed, sources.len());
+    }
+
+    Ok(())
+}
+
+/// Print the summary line of every redeemer of every transaction, prefixed with the
+/// transaction's name.
+async fn summarize(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    sources: &[Source],
+    protocol_version: Option<ProtocolVersion>,
+) -> Result<()> {
+    let mut failed = 0;
+    for source in sources {
+        let name = source.name();
+        let result = with_inputs(
+            args,
+            resolver,
+            source,
+            protocol_version,
+            RedeemerSelector::All,
+            |inputs, selected| Ok(summary_lines(args, inputs, selected)),
+        )
+        .await;
+
+        match result {
+            Ok(lines) => {
+                for (line, ok) in lines {
+                    failed += usize::from(!ok);
+                    println!("{}  {}", name, line);
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "{}  FAILED  {}",
+                    name,
+                    format!("{:#}", e).replace('\n', " ")
+                );
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} redeemer(s) or transaction(s) failed", failed);
+    }
+
+    Ok(())
+}
+
+fn sources(batch: &BatchArgs) -> Result<Vec<Source>> {
+    if let Some(path) = &batch.tx_hashes_file {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read hashes file: {}", path.display()))?;
+
+        return Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| Source::Hash(line.to_string()))
+            .collect());
+    }
+
+    let dir = batch
+        .dir
+        .as_ref()
+        .ok_or_else(|| anyhow!("Either DIR or --tx-hashes-file is required"))?;
+
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    files.retain(|path| has_cbor_extension(path));
+    files.sort();
+
+    Ok(files.into_iter().map(Source::File).collect())
+}
+
+fn has_cbor_extension(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "cbor")
+}
+
+/// Build the script contexts of the selected redeemers of one transaction.
+pub async fn process(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    source: &Source,
+    protocol_version: Option<ProtocolVersion>,
+    selector: RedeemerSelector,
+) -> Result<Vec<RedeemerContext>> {
+    with_inputs(
+        args,
+        resolver,
+        source,
+        protocol_version,
+        selector,
+        |inputs, selected| build_script_contexts(args, inputs, selected),
+    )
+    .await
+}
+
+/// Load and resolve one transaction, then hand what its script contexts are built from to `f`.
+async fn with_inputs<T>(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    source: &Source,
+    protocol_version: Option<ProtocolVersion>,
+    selector: RedeemerSelector,
+    f: impl FnOnce(&ContextInputs<'_>, &[(usize, &Redeemer)]) -> Result<T>,
+) -> Result<T> {
+    let tx_bytes = transaction::complete(source.load(resolver).await?, &WitnessFields::new())?;
+    let tx_bytes = repair_if_lenient(args, tx_bytes)?;
+    let transaction = decode_transaction(&tx_bytes)?;
+
+    let (utxos, datums, _) = resolve_transaction(args, resolver, &transaction).await?;
+    warn_about_transaction(args, &transaction, &utxos);
+    let slot = resolve_slot(args, resolver, &transaction).await?;
+
+    let redeemers = get_redeemers(&transaction)?;
+    let selected = selector.select(&redeemers)?;
+
+    let inputs = ContextInputs {
+        transaction: &transaction,
+        utxos: &utxos,
+        datums: &datums,
+        datum: None,
+        network: args.network,
+        slot,
+        protocol_version,
+    };
+
+    f(&inputs, &selected)
+}
+
+fn to_json(format: OutputFormat, transaction: &str, context: &RedeemerContext) -> String {
+    let mut line = json!({
+        "transaction": transaction,
+        "redeemer": context.redeemer,
+        "purpose": context.purpose,
+        "index": context.index,
+    });
+
+    match format {
+        OutputFormat::Cbor => {}
+        OutputFormat::Json => line["context"] = context.json_context.clone(),
+        OutputFormat::ScriptData => {
+            line["context"] = json!(script_data::detailed_schema(&context.plutus_data))
+        }
+        OutputFormat::Aiken => line["context"] = json!(aiken::data_literal(&context.plutus_data)),
+        OutputFormat::Pretty | OutputFormat::Both => {
+            line["context"] = json!(context.pretty_context)
+        }
+    }
+    if !matches!(format, OutputFormat::Pretty) {
+        line["cbor"] = json!(hex::encode(to_cbor(&context.plutus_data)));
+    }
+
+    line.to_string()
+}
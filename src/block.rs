@@ -0,0 +1,79 @@
+use amaru_kernel::cbor::{Decoder, data::Type};
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::transaction::{RawTransaction, more, parse_witness_set, raw_entries, raw_items};
+
+/// Era tag of Alonzo blocks in the `[era, block]` form: Byron's two eras, then Shelley, Allegra
+/// and Mary come before it.
+const ALONZO_ERA_TAG: u8 = 5;
+
+/// Re-assemble the transaction at `index` from a post-Alonzo block, keeping the original bytes of
+/// its body so that its hash still matches.
+///
+/// Accepts both a bare block and the `[era, block]` form written by the node and most explorers.
+pub fn extract_transaction(block: &[u8], index: usize) -> Result<Vec<u8>> {
+    let mut decoder = Decoder::new(block);
+    let mut fields = decoder.array().context("Block is not a CBOR array")?;
+
+    if fields == Some(2) && decoder.datatype()? == Type::U8 {
+        let era = decoder.u8()?;
+        if era < ALONZO_ERA_TAG {
+            bail!(
+                "Unsupported block era ({}). Only Alonzo and later blocks can be read",
+                era
+            );
+        }
+        fields = decoder
+            .array()
+            .context("Wrapped block is not a CBOR array")?;
+    }
+
+    // Alonzo added the invalid transactions as a fifth field to the header, bodies, witness sets
+    // and auxiliary data of earlier blocks.
+    if fields.is_some_and(|fields| fields < 5) {
+        bail!("Unsupported block format. Only Alonzo and later blocks can be read");
+    }
+
+    decoder.skip().context("Failed to decode block header")?;
+
+    let bodies = raw_items(&mut decoder, block).context("Failed to decode transaction bodies")?;
+    let witnesses =
+        raw_items(&mut decoder, block).context("Failed to decode transaction witness sets")?;
+
+    let body = bodies.get(index).ok_or_else(|| {
+        anyhow!(
+            "Invalid transaction index {}. Block has {} transaction(s)",
+            index,
+            bodies.len()
+        )
+    })?;
+    let witness_set = witnesses
+        .get(index)
+        .context("Block has fewer witness sets than transaction bodies")?;
+
+    let auxiliary_data = raw_entries(&mut decoder, block)
+        .context("Failed to decode auxiliary data")?
+        .remove(&(index as u64));
+    let is_valid = !invalid_transactions(&mut decoder)
+        .context("Failed to decode invalid transactions")?
+        .contains(&(index as u64));
+
+    Ok(RawTransaction {
+        body,
+        witness_set: parse_witness_set(witness_set).context("Failed to decode witness set")?,
+        is_valid,
+        auxiliary_data,
+    }
+    .to_bytes())
+}
+
+fn invalid_transactions(decoder: &mut Decoder<'_>) -> Result<Vec<u64>> {
+    let len = decoder.array()?;
+    let mut indices = Vec::new();
+
+    while more(decoder, len, indices.len() as u64)? {
+        indices.push(decoder.u64()?);
+    }
+
+    Ok(indices)
+}
@@ -1,33 +1,395 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    num::NonZeroUsize,
+    time::Duration,
+};
 
-use amaru_kernel::{MemoizedTransactionOutput, TransactionInput, cbor};
-use anyhow::{Context, Result, anyhow};
-use blockfrost::BlockfrostAPI;
+use amaru_kernel::{Hash, MemoizedTransactionOutput, PlutusData, TransactionInput, cbor};
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use blockfrost::{BlockFrostSettings, BlockfrostAPI, BlockfrostError, Pagination};
+use blockfrost_openapi::models::{
+    script::Type as ScriptType, tx_content_utxo_outputs_inner::TxContentUtxoOutputsInner,
+};
+use chrono::Utc;
 use futures::future::try_join_all;
 use serde::Deserialize;
+use tokio::{
+    sync::{Mutex, Semaphore},
+    time::Instant,
+};
+
+use crate::{
+    evaluate::{Evaluation, parse_evaluation},
+    http::{HttpConfig, client_builder},
+    output::{OutputDatum, OutputParts, ReferenceScript, parse_address},
+    protocol::CostModels,
+    resolver::{ResolvedUtxos, UtxoResolver},
+    retry::RetryConfig,
+    stats::{ProviderStats, RequestStats},
+};
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BlockfrostConfig {
     pub key: String,
+    /// Overrides the public endpoint, e.g. for a self-hosted blockfrost-ryo instance.
+    #[serde(alias = "base_url")]
+    pub base_url: Option<String>,
+    /// The daily request allowance of the project's plan, shown against its usage by `--stats`.
+    #[serde(alias = "daily_requests")]
+    pub daily_requests: Option<u64>,
 }
 
+/// How many times a rate-limited request is retried before giving up.
+const RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Blockfrost refills its request bucket at 10 requests per second, so a one second pause per
+/// attempt is enough for a burst of queued requests to drain.
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(1);
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
 pub struct Blockfrost {
     api: BlockfrostAPI,
+    retry: RetryConfig,
+    max_concurrency: NonZeroUsize,
+    /// Shared by all in-flight requests so that a single 429 pauses every one of them.
+    paused_until: Mutex<Option<Instant>>,
+    /// Reference scripts already fetched, keyed by hash. `None` marks a native script.
+    scripts: Mutex<HashMap<String, Option<ReferenceScript>>>,
+    daily_requests: Option<u64>,
+    stats: RequestStats,
 }
 
 impl Blockfrost {
-    pub fn new(config: &BlockfrostConfig) -> Self {
-        Self {
-            api: BlockfrostAPI::new(&config.key, Default::default()),
+    pub fn new(
+        config: &BlockfrostConfig,
+        http: &HttpConfig,
+        retry: &RetryConfig,
+        max_concurrency: NonZeroUsize,
+    ) -> Result<Self> {
+        let mut settings = BlockFrostSettings::new();
+        settings.base_url = config.base_url.clone();
+
+        let api = BlockfrostAPI::new_with_client(&config.key, settings, client_builder(http)?)
+            .context("Failed to create the Blockfrost client")?;
+
+        Ok(Self {
+            api,
+            retry: retry.clone(),
+            max_concurrency,
+            paused_until: Mutex::new(None),
+            scripts: Mutex::new(HashMap::new()),
+            daily_requests: config.daily_requests,
+            stats: RequestStats::default(),
+        })
+    }
+
+    /// Run a request, backing off and retrying while Blockfrost answers with HTTP 429 or fails
+    /// with a transient error. The API client drops the headers of a 429, so its `Retry-After`
+    /// can't be read and the pause grows by a second on each attempt instead.
+    async fn call<T, F, Fut>(&self, request: F) -> Result<T, BlockfrostError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, BlockfrostError>>,
+    {
+        let mut rate_limited = 0;
+        let mut retries = 0;
+
+        loop {
+            let paused_until = *self.paused_until.lock().await;
+            if let Some(until) = paused_until {
+                tokio::time::sleep_until(until).await;
+            }
+
+            self.stats.record_request();
+            match request().await {
+                Err(e) if is_rate_limited(&e) && rate_limited < RATE_LIMIT_RETRIES => {
+                    rate_limited += 1;
+                    self.stats.record_retry();
+                    let until = Instant::now() + RATE_LIMIT_BACKOFF * rate_limited;
+                    let mut paused_until = self.paused_until.lock().await;
+                    *paused_until = Some(paused_until.map_or(until, |current| current.max(until)));
+                }
+                Err(e) if is_transient(&e) && retries + 1 < self.retry.max_attempts => {
+                    retries += 1;
+                    self.stats.record_retry();
+                    tokio::time::sleep(self.retry.delay(retries)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Query the outputs of a source transaction once and extract every output spent from it.
+    async fn fetch_utxos(
+        &self,
+        inputs: &[&TransactionInput],
+    ) -> Result<Vec<(TransactionInput, MemoizedTransactionOutput)>> {
+        let Some(first) = inputs.first() else {
+            return Ok(Vec::new());
+        };
+        let tx_hash = hex::encode(first.transaction_id);
+
+        let response = match self.call(|| self.api.transactions_utxos(&tx_hash)).await {
+            Ok(response) => response,
+            Err(e) if is_not_found(&e) => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(describe(e)).context(format!(
+                    "Failed to fetch outputs of transaction {}",
+                    tx_hash
+                ));
+            }
+        };
+
+        let mut resolved = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            let output = response
+                .outputs
+                .iter()
+                .find(|output| output.output_index as u64 == input.index)
+                .context(format!(
+                    "Invalid output index {} for transaction {}. Transaction has {} output(s)",
+                    input.index,
+                    tx_hash,
+                    response.outputs.len()
+                ))?;
+
+            match self.output_parts(output).await? {
+                Some(parts) => resolved.push(((*input).clone(), parts.into_memoized()?)),
+                None => return self.fetch_from_cbor(&tx_hash, inputs).await,
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Rebuild an output from the utxos endpoint, following up on its datum and reference
+    /// script. Returns `None` for native reference scripts, which Blockfrost only serves as JSON.
+    async fn output_parts(
+        &self,
+        output: &TxContentUtxoOutputsInner,
+    ) -> Result<Option<OutputParts>> {
+        let mut lovelace = 0;
+        let mut assets: BTreeMap<Hash<28>, BTreeMap<Vec<u8>, u64>> = BTreeMap::new();
+        for amount in &output.amount {
+            let quantity: u64 = amount
+                .quantity
+                .parse()
+                .context(format!("Invalid quantity for {}", amount.unit))?;
+
+            if amount.unit == "lovelace" {
+                lovelace = quantity;
+                continue;
+            }
+
+            let unit =
+                hex::decode(&amount.unit).context(format!("Invalid unit {}", amount.unit))?;
+            if unit.len() < 28 {
+                bail!("Invalid unit {}", amount.unit);
+            }
+            let (policy, name) = unit.split_at(28);
+            assets
+                .entry(Hash::from(policy))
+                .or_default()
+                .insert(name.to_vec(), quantity);
+        }
+
+        let datum = match (&output.inline_datum, &output.data_hash) {
+            (Some(datum), _) => {
+                OutputDatum::Inline(hex::decode(datum).context("Invalid inline datum hex")?)
+            }
+            (None, Some(hash)) => OutputDatum::Hash(
+                hash.parse()
+                    .map_err(|_| anyhow!("Invalid datum hash {}", hash))?,
+            ),
+            (None, None) => OutputDatum::None,
+        };
+
+        let script = match &output.reference_script_hash {
+            Some(hash) => match self.reference_script(hash).await? {
+                Some(script) => Some(script),
+                None => return Ok(None),
+            },
+            None => None,
+        };
+
+        Ok(Some(OutputParts {
+            address: parse_address(&output.address)?,
+            lovelace,
+            assets,
+            datum,
+            script,
+        }))
+    }
+
+    async fn reference_script(&self, hash: &str) -> Result<Option<ReferenceScript>> {
+        if let Some(script) = self.scripts.lock().await.get(hash) {
+            self.stats.record_cache_hit();
+            return Ok(script.clone());
+        }
+
+        let script = self.fetch_reference_script(hash).await?;
+        self.scripts
+            .lock()
+            .await
+            .insert(hash.to_string(), script.clone());
+
+        Ok(script)
+    }
+
+    async fn fetch_reference_script(&self, hash: &str) -> Result<Option<ReferenceScript>> {
+        let script = self
+            .call(|| self.api.scripts_by_id(hash))
+            .await
+            .map_err(describe)
+            .context(format!("Failed to fetch script {}", hash))?;
+
+        if matches!(script.r#type, ScriptType::Timelock) {
+            return Ok(None);
+        }
+
+        let bytes = self
+            .call(|| self.api.scripts_cbor(hash))
+            .await
+            .map_err(describe)
+            .context(format!("Failed to fetch CBOR of script {}", hash))?
+            .cbor
+            .map(hex::decode)
+            .transpose()
+            .context(format!("Invalid CBOR hex for script {}", hash))?
+            .ok_or_else(|| anyhow!("Blockfrost has no CBOR for script {}", hash))?;
+
+        Ok(Some(match script.r#type {
+            ScriptType::PlutusV1 => ReferenceScript::PlutusV1(bytes),
+            ScriptType::PlutusV2 => ReferenceScript::PlutusV2(bytes),
+            _ => ReferenceScript::PlutusV3(bytes),
+        }))
+    }
+
+    async fn transaction_cbor(&self, tx_hash: &str) -> Result<Option<Vec<u8>>> {
+        let response = match self.call(|| self.api.transactions_cbor(tx_hash)).await {
+            Ok(response) => response,
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => {
+                return Err(describe(e))
+                    .context(format!("Failed to fetch transaction {}", tx_hash));
+            }
+        };
+
+        hex::decode(&response.cbor).map(Some).context(format!(
+            "Invalid CBOR hex from Blockfrost for tranasction {}",
+            tx_hash
+        ))
+    }
+
+    /// Download the full source transaction and extract the outputs from its body. Only used
+    /// when an output can't be rebuilt from the utxos endpoint.
+    async fn fetch_from_cbor(
+        &self,
+        tx_hash: &str,
+        inputs: &[&TransactionInput],
+    ) -> Result<Vec<(TransactionInput, MemoizedTransactionOutput)>> {
+        let Some(cbor_bytes) = self.transaction_cbor(tx_hash).await? else {
+            return Ok(Vec::new());
+        };
+
+        let transaction: amaru_kernel::MintedTx<'_> = cbor::decode(&cbor_bytes)
+            .context(format!("Failed to decode transaction CBOR for {}", tx_hash))?;
+
+        inputs
+            .iter()
+            .map(|input| {
+                let output = transaction
+                    .transaction_body
+                    .outputs
+                    .get(input.index as usize)
+                    .context(format!(
+                        "Invalid output index {} for transaction {}. Transaction has {} output(s)",
+                        input.index,
+                        tx_hash,
+                        transaction.transaction_body.outputs.len()
+                    ))?
+                    .clone();
+
+                let memoized_output = MemoizedTransactionOutput::try_from(output)
+                    .map_err(|e| anyhow!("Failed to convert output to memoized format: {}", e))?;
+
+                Ok(((*input).clone(), memoized_output))
+            })
+            .collect()
+    }
+
+    /// The protocol parameters of the current epoch, as JSON.
+    async fn parameters(&self) -> Result<serde_json::Value> {
+        let response = self
+            .call(|| self.api.epochs_latest_parameters())
+            .await
+            .map_err(describe)
+            .context("Failed to fetch protocol parameters")?;
+
+        serde_json::to_value(response).context("Failed to read protocol parameters")
+    }
+}
+
+fn is_not_found(error: &BlockfrostError) -> bool {
+    matches!(error, BlockfrostError::Response { reason, .. } if reason.status_code == 404)
+}
+
+fn is_rate_limited(error: &BlockfrostError) -> bool {
+    matches!(error, BlockfrostError::Response { reason, .. } if reason.status_code == 429)
+}
+
+fn is_transient(error: &BlockfrostError) -> bool {
+    match error {
+        BlockfrostError::Reqwest(_) => true,
+        BlockfrostError::Response { reason, .. } => reason.status_code >= 500,
+        _ => false,
+    }
+}
+
+fn describe(error: BlockfrostError) -> anyhow::Error {
+    if is_rate_limited(&error) {
+        anyhow!(
+            "Rate limited by Blockfrost (HTTP 429) after {} retries. Your project may have exhausted its request quota; wait and try again",
+            RATE_LIMIT_RETRIES
+        )
+    } else {
+        error.into()
+    }
+}
+
+#[async_trait]
+impl UtxoResolver for Blockfrost {
+    async fn resolve(&self, inputs: &[TransactionInput]) -> Result<ResolvedUtxos> {
+        let mut by_transaction: BTreeMap<_, Vec<&TransactionInput>> = BTreeMap::new();
+        for input in inputs {
+            by_transaction
+                .entry(input.transaction_id)
+                .or_default()
+                .push(input);
         }
+
+        let semaphore = Semaphore::new(self.max_concurrency.get());
+        let futures = by_transaction.values().map(|inputs| async {
+            let _permit = semaphore.acquire().await?;
+            self.fetch_utxos(inputs).await
+        });
+
+        let results = try_join_all(futures)
+            .await
+            .context("Failed to fetch UTxOs from Blockfrost")?;
+
+        Ok(results.into_iter().flatten().collect())
     }
 
-    pub async fn get_tip(&self) -> Result<u64> {
+    async fn tip(&self) -> Result<u64> {
         let response = self
-            .api
-            .blocks_latest()
+            .call(|| self.api.blocks_latest())
             .await
+            .map_err(describe)
             .context("failed to get tip")?;
 
         response
@@ -36,54 +398,88 @@ impl Blockfrost {
             .ok_or(anyhow!("no tip found for latest block"))
     }
 
-    pub async fn get_utxos(
-        &self,
-        inputs: &[TransactionInput],
-    ) -> Result<BTreeMap<TransactionInput, MemoizedTransactionOutput>> {
-        let futures = inputs.iter().map(|input| self.fetch_utxo(input));
+    async fn cost_models(&self) -> Result<CostModels> {
+        let parameters = self.parameters().await?;
+        CostModels::from_json(&parameters).context("Invalid cost models from Blockfrost")
+    }
 
-        let results = try_join_all(futures)
-            .await
-            .context("Failed to fetch UTxOs from Blockfrost")?;
+    async fn protocol_parameters(&self) -> Result<serde_json::Value> {
+        self.parameters().await
+    }
+
+    async fn resolve_datum(&self, hash: &Hash<32>) -> Result<Option<PlutusData>> {
+        let hash = hex::encode(hash);
 
-        Ok(results.into_iter().collect())
+        let response = match self.call(|| self.api.scripts_datum_hash_cbor(&hash)).await {
+            Ok(response) => response,
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => return Err(describe(e)).context(format!("Failed to fetch datum {}", hash)),
+        };
+
+        let bytes =
+            hex::decode(&response.cbor).context(format!("Invalid CBOR hex for datum {}", hash))?;
+
+        cbor::decode(&bytes)
+            .map(Some)
+            .context(format!("Failed to decode datum {}", hash))
     }
 
-    async fn fetch_utxo(
-        &self,
-        input: &TransactionInput,
-    ) -> Result<(TransactionInput, MemoizedTransactionOutput)> {
-        let tx_hash = hex::encode(input.transaction_id);
+    async fn fetch_transaction(&self, hash: &Hash<32>) -> Result<Option<Vec<u8>>> {
+        self.transaction_cbor(&hex::encode(hash)).await
+    }
+
+    async fn block_transactions(&self, hash: &Hash<32>) -> Result<Vec<Hash<32>>> {
+        let hash = hex::encode(hash);
+
+        self.call(|| self.api.blocks_txs(&hash, Pagination::all()))
+            .await
+            .map_err(describe)
+            .context(format!("Failed to fetch transactions of block {}", hash))?
+            .iter()
+            .map(|tx_hash| {
+                tx_hash
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid transaction hash {}", tx_hash))
+            })
+            .collect()
+    }
 
+    async fn evaluate_transaction(&self, transaction: &[u8]) -> Result<Vec<Evaluation>> {
         let response = self
-            .api
-            .transactions_cbor(&tx_hash)
+            .call(|| self.api.utils_txs_evaluate(transaction.to_vec()))
             .await
-            .context(format!("Failed to fetch transaction {}", tx_hash))?;
+            .map_err(describe)
+            .context("Failed to evaluate the transaction")?;
 
-        let cbor_bytes = hex::decode(&response.cbor).context(format!(
-            "Invalid CBOR hex from Blockfrost for tranasction {}",
-            tx_hash
-        ))?;
+        parse_evaluation(&response)
+    }
 
-        let transaction: amaru_kernel::MintedTx<'_> = cbor::decode(&cbor_bytes)
-            .context(format!("Failed to decode transaction CBOR for {}", tx_hash))?;
+    async fn stats(&self) -> Vec<ProviderStats> {
+        // The client doesn't surface response headers, so usage comes from the metrics
+        // endpoint instead, at the cost of one more request. Its entries cover a UTC day each.
+        self.stats.record_request();
+        let today = Utc::now().timestamp().div_euclid(SECONDS_PER_DAY);
+        let quota = match self.api.metrics().await {
+            Ok(metrics) => {
+                let calls = metrics
+                    .iter()
+                    .find(|entry| i64::from(entry.time).div_euclid(SECONDS_PER_DAY) == today)
+                    .map_or(0, |entry| entry.calls);
+                Some(match self.daily_requests {
+                    Some(allowed) => format!(
+                        "{} request(s) used today, of the {} allowed per day",
+                        calls, allowed
+                    ),
+                    None => format!("{} request(s) used today", calls),
+                })
+            }
+            Err(e) => Some(format!("Could not fetch usage: {}", describe(e))),
+        };
+
+        vec![self.stats.report(self.name(), quota)]
+    }
 
-        let output = transaction
-            .transaction_body
-            .outputs
-            .get(input.index as usize)
-            .context(format!(
-                "Invalid output index {} for transaction {}. Transaction has {} output(s)",
-                input.index,
-                tx_hash,
-                transaction.transaction_body.outputs.len()
-            ))?
-            .clone();
-
-        let memoized_output = MemoizedTransactionOutput::try_from(output)
-            .map_err(|e| anyhow!("Failed to convert output to memoized format: {}", e))?;
-
-        Ok((input.clone(), memoized_output))
+    fn name(&self) -> &'static str {
+        "Blockfrost"
     }
 }
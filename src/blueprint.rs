@@ -0,0 +1,232 @@
+use std::path::Path;
+
+use amaru_kernel::{Hash, PlutusData, ScriptPurpose};
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+
+use crate::{
+    formatter::{ReadableFormatter, indent_lines},
+    script_data::constructor,
+};
+
+/// A CIP-57 Plutus blueprint (`plutus.json`), whose schemas name the fields of the datums and
+/// redeemers of its validators.
+pub struct Blueprint {
+    validators: Vec<Value>,
+    definitions: Value,
+}
+
+impl Blueprint {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read blueprint: {}", path.display()))?;
+        let json: Value = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid JSON in blueprint: {}", path.display()))?;
+
+        let validators = json
+            .get("validators")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("No validators in blueprint: {}", path.display()))?
+            .clone();
+        let definitions = json.get("definitions").cloned().unwrap_or(Value::Null);
+
+        Ok(Self {
+            validators,
+            definitions,
+        })
+    }
+
+    /// Label the datum and redeemer of a validator of the blueprint with the names of its
+    /// schemas. None when the blueprint has no validator with this hash.
+    pub fn describe(
+        &self,
+        hash: &Hash<28>,
+        purpose: ScriptPurpose,
+        datum: Option<&PlutusData>,
+        redeemer: &PlutusData,
+    ) -> Option<String> {
+        let validator = self.validator(hash, purpose)?;
+        let title = validator
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("untitled");
+
+        let mut result = format!("Blueprint ({}):", title);
+        if let Some(datum) = datum {
+            let schema = validator.pointer("/datum/schema").unwrap_or(&Value::Null);
+            result.push_str(&format!(
+                "\n  Datum: {}",
+                indent_lines(&self.render(schema, datum), 2).trim_start()
+            ));
+        }
+        let schema = validator
+            .pointer("/redeemer/schema")
+            .unwrap_or(&Value::Null);
+        result.push_str(&format!(
+            "\n  Redeemer: {}",
+            indent_lines(&self.render(schema, redeemer), 2).trim_start()
+        ));
+
+        Some(result)
+    }
+
+    /// The validator with this hash. Multi-purpose validators are listed once per purpose, as
+    /// `<module>.<name>.<purpose>`, so the one for the redeemer's purpose is preferred.
+    fn validator(&self, hash: &Hash<28>, purpose: ScriptPurpose) -> Option<&Value> {
+        let hash = hex::encode(hash);
+        let suffix = match purpose {
+            ScriptPurpose::Spend => ".spend",
+            ScriptPurpose::Mint => ".mint",
+            ScriptPurpose::Cert => ".publish",
+            ScriptPurpose::Reward => ".withdraw",
+            ScriptPurpose::Vote => ".vote",
+            ScriptPurpose::Propose => ".propose",
+        };
+
+        let mut candidates = self
+            .validators
+            .iter()
+            .filter(|validator| validator.get("hash").and_then(Value::as_str) == Some(&hash))
+            .peekable();
+        let first = *candidates.peek()?;
+
+        candidates
+            .find(|validator| {
+                validator
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .is_some_and(|title| title.ends_with(suffix))
+            })
+            .or(Some(first))
+    }
+
+    /// Follow `$ref`s into the definitions, as JSON pointers.
+    fn resolve<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        let mut schema = schema;
+        while let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let Some(pointer) = reference.strip_prefix("#/definitions") else {
+                break;
+            };
+            match self.definitions.pointer(pointer) {
+                Some(definition) => schema = definition,
+                None => break,
+            }
+        }
+        schema
+    }
+
+    /// Name of the type a schema refers to, the last segment of its reference
+    /// (`aiken/crypto/VerificationKeyHash` gives `VerificationKeyHash`).
+    fn type_name(schema: &Value) -> Option<String> {
+        let reference = schema.get("$ref").and_then(Value::as_str)?;
+        let name = reference
+            .rsplit('/')
+            .next()?
+            .replace("~1", "/")
+            .replace("~0", "~");
+        name.rsplit('/').next().map(str::to_string)
+    }
+
+    /// Format data along a schema: constructors by name with labelled fields, lists and maps
+    /// element by element. Data that doesn't match its schema is printed as is, flagged.
+    fn render(&self, schema: &Value, data: &PlutusData) -> String {
+        let resolved = self.resolve(schema);
+
+        if let Some(alternatives) = resolved.get("anyOf").and_then(Value::as_array) {
+            let index = match data {
+                PlutusData::Constr(constr) => Some(constructor(constr.tag, constr.any_constructor)),
+                _ => None,
+            };
+            return match alternatives
+                .iter()
+                .map(|alternative| self.resolve(alternative))
+                .find(|alternative| {
+                    alternative.get("index").and_then(Value::as_u64) == index
+                        || alternatives.len() == 1
+                }) {
+                Some(alternative) => self.render(alternative, data),
+                None => mismatch(data),
+            };
+        }
+
+        match (resolved.get("dataType").and_then(Value::as_str), data) {
+            (Some("constructor"), PlutusData::Constr(constr)) => {
+                let index = constructor(constr.tag, constr.any_constructor);
+                if resolved.get("index").and_then(Value::as_u64) != Some(index) {
+                    return mismatch(data);
+                }
+                let fields = resolved
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                if fields.len() != constr.fields.len() {
+                    return mismatch(data);
+                }
+
+                let mut result = resolved
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("Constr({})", index));
+                for (position, (field, value)) in fields.iter().zip(&constr.fields).enumerate() {
+                    let name = field
+                        .get("title")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| position.to_string());
+                    result.push_str(&format!("\n  {}", self.labelled(&name, field, value)));
+                }
+                result
+            }
+            (Some("integer"), PlutusData::BigInt(_))
+            | (Some("bytes"), PlutusData::BoundedBytes(_)) => data.format_readable(),
+            (Some("list"), PlutusData::Array(elements)) => {
+                let items = resolved.get("items").unwrap_or(&Value::Null);
+                let mut result = format!("{} element(s)", elements.len());
+                for (position, element) in elements.iter().enumerate() {
+                    // Tuples give one schema per element.
+                    let schema = match items {
+                        Value::Array(schemas) => schemas.get(position).unwrap_or(&Value::Null),
+                        schema => schema,
+                    };
+                    result.push_str(&format!(
+                        "\n  {}",
+                        self.labelled(&format!("[{}]", position), schema, element)
+                    ));
+                }
+                result
+            }
+            (Some("map"), PlutusData::Map(entries)) => {
+                let keys = resolved.get("keys").unwrap_or(&Value::Null);
+                let values = resolved.get("values").unwrap_or(&Value::Null);
+                let mut result = format!("{} entry(ies)", entries.len());
+                for (key, value) in entries.iter() {
+                    result.push_str(&format!(
+                        "\n  {} =>\n    {}",
+                        indent_lines(&self.render(keys, key), 2).trim_start(),
+                        indent_lines(&self.render(values, value), 4).trim_start()
+                    ));
+                }
+                result
+            }
+            // Opaque data (`{}`) and data types without a Plutus data encoding.
+            (None, _) => data.format_readable(),
+            _ => mismatch(data),
+        }
+    }
+
+    /// A field or element: its name and type, then its value.
+    fn labelled(&self, name: &str, schema: &Value, data: &PlutusData) -> String {
+        let rendered = self.render(schema, data);
+        let rendered = indent_lines(&rendered, 2);
+        match Self::type_name(schema) {
+            Some(type_name) => format!("{}: {} = {}", name, type_name, rendered.trim_start()),
+            None => format!("{} = {}", name, rendered.trim_start()),
+        }
+    }
+}
+
+fn mismatch(data: &PlutusData) -> String {
+    format!("{} (doesn't match the schema)", data.format_readable())
+}
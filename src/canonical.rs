@@ -0,0 +1,390 @@
+use std::cmp::Ordering;
+
+use amaru_kernel::{BigInt, PlutusData};
+
+use crate::{PlutusVersion, script_data};
+
+/// How the keys of a map (or the elements of a set) of the context are ordered by the ledger.
+#[derive(Clone, Copy)]
+enum Order {
+    /// The structural order of the Plutus types, e.g. bytes for policies and datum hashes, or
+    /// transaction id then index for output references.
+    Data,
+    /// Ledger credentials: script credentials before key credentials, then by hash.
+    Credential,
+    /// Staking credentials of PlutusV1 and PlutusV2, ordered by the credential they wrap.
+    StakingCredential,
+    /// Governance voters: by kind, then by credential.
+    Voter,
+    /// Script purposes: spending, minting, certifying, rewarding, voting, proposing, in the
+    /// order of the ledger's redeemer tags.
+    Purpose(PlutusVersion),
+}
+
+/// Compare the ordering of the maps and sets of a script context with the ordering the ledger
+/// produces, returning one line per divergence.
+pub fn check(version: PlutusVersion, context: &PlutusData) -> Vec<String> {
+    let mut divergences = Vec::new();
+
+    let Some(tx_info) = field(context, 0) else {
+        divergences.push("context: not a constructor holding the TxInfo".to_string());
+        return divergences;
+    };
+
+    let (sets, maps, pairs): (&[_], &[_], &[_]) = match version {
+        PlutusVersion::PlutusV1 => (
+            &[(0, "inputs"), (7, "signatories")],
+            &[],
+            &[
+                (5, "withdrawals", Order::StakingCredential),
+                (8, "datums", Order::Data),
+            ],
+        ),
+        PlutusVersion::PlutusV2 => (
+            &[(0, "inputs"), (1, "reference inputs"), (8, "signatories")],
+            &[
+                (6, "withdrawals", Order::StakingCredential),
+                (9, "redeemers", Order::Purpose(version)),
+                (10, "datums", Order::Data),
+            ],
+            &[],
+        ),
+        PlutusVersion::PlutusV3 => (
+            &[(0, "inputs"), (1, "reference inputs"), (8, "signatories")],
+            &[
+                (6, "withdrawals", Order::Credential),
+                (9, "redeemers", Order::Purpose(version)),
+                (10, "datums", Order::Data),
+                (12, "votes", Order::Voter),
+            ],
+            &[],
+        ),
+    };
+
+    for (index, name) in sets {
+        if let Some(PlutusData::Array(elements)) = field(tx_info, *index) {
+            let elements: Vec<_> = elements.iter().collect();
+            check_order(
+                &format!("txInfo.{}", name),
+                &elements,
+                Order::Data,
+                &mut divergences,
+            );
+        }
+    }
+
+    for (index, name, order) in maps {
+        if let Some(PlutusData::Map(entries)) = field(tx_info, *index) {
+            let keys: Vec<_> = entries.iter().map(|(key, _)| key).collect();
+            check_order(&format!("txInfo.{}", name), &keys, *order, &mut divergences);
+        }
+    }
+
+    // PlutusV1 has no maps: it lists (key, value) tuples instead.
+    for (index, name, order) in pairs {
+        if let Some(PlutusData::Array(tuples)) = field(tx_info, *index) {
+            let keys: Vec<_> = tuples.iter().filter_map(|tuple| field(tuple, 0)).collect();
+            check_order(&format!("txInfo.{}", name), &keys, *order, &mut divergences);
+        }
+    }
+
+    // The ledger builds the values of the inputs, outputs, fee and mint, ordered bytewise by
+    // policy then asset name. Datums, redeemers and any other user data are kept as given.
+    let (inputs, outputs, values): (&[_], &[_], &[_]) = match version {
+        PlutusVersion::PlutusV1 => (
+            &[(0, "inputs")],
+            &[(1, "outputs")],
+            &[(2, "fee"), (3, "mint")],
+        ),
+        PlutusVersion::PlutusV2 => (
+            &[(0, "inputs"), (1, "reference inputs")],
+            &[(2, "outputs")],
+            &[(3, "fee"), (4, "mint")],
+        ),
+        PlutusVersion::PlutusV3 => (
+            &[(0, "inputs"), (1, "reference inputs")],
+            &[(2, "outputs")],
+            &[(4, "mint")],
+        ),
+    };
+    for (index, name) in inputs {
+        if let Some(PlutusData::Array(inputs)) = field(tx_info, *index) {
+            for (position, input) in inputs.iter().enumerate() {
+                if let Some(value) = field(input, 1).and_then(|output| field(output, 1)) {
+                    let path = format!("txInfo.{}[{}].value", name, position);
+                    check_value(value, &path, &mut divergences);
+                }
+            }
+        }
+    }
+    for (index, name) in outputs {
+        if let Some(PlutusData::Array(outputs)) = field(tx_info, *index) {
+            for (position, output) in outputs.iter().enumerate() {
+                if let Some(value) = field(output, 1) {
+                    let path = format!("txInfo.{}[{}].value", name, position);
+                    check_value(value, &path, &mut divergences);
+                }
+            }
+        }
+    }
+    for (index, name) in values {
+        if let Some(value) = field(tx_info, *index) {
+            check_value(value, &format!("txInfo.{}", name), &mut divergences);
+        }
+    }
+
+    // Votes of each voter are keyed by governance action id.
+    if matches!(version, PlutusVersion::PlutusV3)
+        && let Some(PlutusData::Map(votes)) = field(tx_info, 12)
+    {
+        for (position, (_, actions)) in votes.iter().enumerate() {
+            if let PlutusData::Map(actions) = actions {
+                let keys: Vec<_> = actions.iter().map(|(key, _)| key).collect();
+                let path = format!("txInfo.votes[{}]", position);
+                check_order(&path, &keys, Order::Data, &mut divergences);
+            }
+        }
+    }
+
+    divergences
+}
+
+/// A value: policies, then the asset names of each policy, ordered bytewise.
+fn check_value(value: &PlutusData, path: &str, divergences: &mut Vec<String>) {
+    let PlutusData::Map(policies) = value else {
+        return;
+    };
+
+    let keys: Vec<_> = policies.iter().map(|(key, _)| key).collect();
+    check_order(path, &keys, Order::Data, divergences);
+    for (position, (_, assets)) in policies.iter().enumerate() {
+        if let PlutusData::Map(assets) = assets {
+            let keys: Vec<_> = assets.iter().map(|(key, _)| key).collect();
+            check_order(
+                &format!("{}[{}]", path, position),
+                &keys,
+                Order::Data,
+                divergences,
+            );
+        }
+    }
+}
+
+fn check_order(path: &str, keys: &[&PlutusData], order: Order, divergences: &mut Vec<String>) {
+    for (position, pair) in keys.windows(2).enumerate() {
+        match compare(pair[0], pair[1], order) {
+            Some(Ordering::Less) | None => {}
+            Some(Ordering::Equal) => divergences.push(format!(
+                "{}: entry {} duplicates entry {}",
+                path,
+                position + 1,
+                position
+            )),
+            Some(Ordering::Greater) => divergences.push(format!(
+                "{}: entry {} should come before entry {}",
+                path,
+                position + 1,
+                position
+            )),
+        }
+    }
+}
+
+/// Order two keys the way the ledger does, or `None` if it can't be told from the Data alone.
+fn compare(a: &PlutusData, b: &PlutusData, order: Order) -> Option<Ordering> {
+    match order {
+        Order::Data => Some(compare_data(a, b)),
+        Order::Credential => Some(compare_credentials(a, b)),
+        Order::StakingCredential => match (field(a, 0), field(b, 0)) {
+            (Some(a), Some(b)) if constructor(a).is_some() && constructor(b).is_some() => {
+                Some(compare_credentials(a, b))
+            }
+            _ => Some(compare_data(a, b)),
+        },
+        Order::Voter => Some(constructor(a).cmp(&constructor(b)).then_with(|| {
+            match (field(a, 0), field(b, 0)) {
+                // Committee members and DReps are credentials, stake pools key hashes.
+                (Some(a), Some(b)) if constructor(a).is_some() => compare_credentials(a, b),
+                (Some(a), Some(b)) => compare_data(a, b),
+                _ => Ordering::Equal,
+            }
+        })),
+        Order::Purpose(version) => {
+            let (tag_a, tag_b) = (constructor(a)?, constructor(b)?);
+            match purpose_rank(tag_a).cmp(&purpose_rank(tag_b)) {
+                Ordering::Equal => {
+                    let (a, b) = (field(a, 0)?, field(b, 0)?);
+                    match (tag_a, version) {
+                        // Certificates are ordered by their position in the body, which
+                        // PlutusV1 and PlutusV2 don't record.
+                        (3, PlutusVersion::PlutusV1 | PlutusVersion::PlutusV2) => None,
+                        (2, PlutusVersion::PlutusV3) => Some(compare_credentials(a, b)),
+                        (4, _) => compare(a, b, Order::Voter),
+                        (2, _) => compare(a, b, Order::StakingCredential),
+                        _ => Some(compare_data(a, b)),
+                    }
+                }
+                rank => Some(rank),
+            }
+        }
+    }
+}
+
+/// Position of a script purpose constructor (minting, spending, rewarding, certifying, voting,
+/// proposing) in the order of the ledger's redeemer tags.
+fn purpose_rank(tag: u64) -> u64 {
+    match tag {
+        0 => 1,
+        1 => 0,
+        2 => 3,
+        3 => 2,
+        tag => tag,
+    }
+}
+
+/// Plutus credentials are `PubKeyCredential` (0) then `ScriptCredential` (1), but the ledger
+/// orders script credentials first.
+fn compare_credentials(a: &PlutusData, b: &PlutusData) -> Ordering {
+    constructor(b)
+        .cmp(&constructor(a))
+        .then_with(|| match (field(a, 0), field(b, 0)) {
+            (Some(a), Some(b)) => compare_data(a, b),
+            _ => Ordering::Equal,
+        })
+}
+
+/// The order Haskell derives for Plutus types: by constructor then fields, bytes bytewise and
+/// integers numerically.
+fn compare_data(a: &PlutusData, b: &PlutusData) -> Ordering {
+    match (a, b) {
+        (PlutusData::Constr(x), PlutusData::Constr(y)) => constructor(a)
+            .cmp(&constructor(b))
+            .then_with(|| compare_all(x.fields.iter(), y.fields.iter())),
+        (PlutusData::BoundedBytes(x), PlutusData::BoundedBytes(y)) => x[..].cmp(&y[..]),
+        (PlutusData::BigInt(x), PlutusData::BigInt(y)) => integer(x).cmp(&integer(y)),
+        (PlutusData::Array(x), PlutusData::Array(y)) => compare_all(x.iter(), y.iter()),
+        (PlutusData::Map(x), PlutusData::Map(y)) => {
+            compare_all(x.iter().map(|(key, _)| key), y.iter().map(|(key, _)| key))
+        }
+        _ => kind(a).cmp(&kind(b)),
+    }
+}
+
+fn compare_all<'a>(
+    a: impl Iterator<Item = &'a PlutusData>,
+    mut b: impl Iterator<Item = &'a PlutusData>,
+) -> Ordering {
+    for x in a {
+        match b.next() {
+            Some(y) => match compare_data(x, y) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            },
+            None => return Ordering::Greater,
+        }
+    }
+
+    if b.next().is_some() {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
+fn kind(data: &PlutusData) -> u8 {
+    match data {
+        PlutusData::Constr(_) => 0,
+        PlutusData::Map(_) => 1,
+        PlutusData::Array(_) => 2,
+        PlutusData::BigInt(_) => 3,
+        PlutusData::BoundedBytes(_) => 4,
+    }
+}
+
+/// Integers small enough to be encoded inline; bignums only appear in keys of hand-made data,
+/// and sort after them.
+fn integer(int: &BigInt) -> (u8, i128) {
+    match int {
+        BigInt::Int(int) => (0, i128::from(int.0)),
+        BigInt::BigUInt(_) => (1, 0),
+        BigInt::BigNInt(_) => (0, i128::MIN),
+    }
+}
+
+/// Index of the constructor of a Constr, from its CBOR tag.
+fn constructor(data: &PlutusData) -> Option<u64> {
+    let PlutusData::Constr(constr) = data else {
+        return None;
+    };
+
+    Some(script_data::constructor(constr.tag, constr.any_constructor))
+}
+
+fn field(data: &PlutusData, index: usize) -> Option<&PlutusData> {
+    match data {
+        PlutusData::Constr(constr) => constr.fields.get(index),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amaru_kernel::cbor;
+
+    use super::*;
+
+    /// A PlutusV3 context whose TxInfo holds the given mint (index 4) and signatories (index 8),
+    /// every other field up to the signatories being an empty list.
+    fn context(mint: &str, signatories: &str) -> PlutusData {
+        let hex = format!(
+            "d87983d87989{}{}{}{}0000",
+            "80".repeat(4),
+            mint,
+            "80".repeat(3),
+            signatories
+        );
+        cbor::decode(&hex::decode(hex).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn divergences() {
+        let cases = [
+            ("a0", "8241014102", vec![]),
+            (
+                "a0",
+                "8241024101",
+                vec!["txInfo.signatories: entry 1 should come before entry 0"],
+            ),
+            (
+                "a0",
+                "8241014101",
+                vec!["txInfo.signatories: entry 1 duplicates entry 0"],
+            ),
+            (
+                "a24102a04101a0",
+                "80",
+                vec!["txInfo.mint: entry 1 should come before entry 0"],
+            ),
+        ];
+
+        for (mint, signatories, expected) in cases {
+            assert_eq!(
+                check(PlutusVersion::PlutusV3, &context(mint, signatories)),
+                expected,
+                "mint {} and signatories {}",
+                mint,
+                signatories
+            );
+        }
+    }
+
+    #[test]
+    fn contexts_that_are_not_constructors() {
+        let context: PlutusData = cbor::decode(&[0x00]).unwrap();
+
+        assert_eq!(
+            check(PlutusVersion::PlutusV3, &context),
+            vec!["context: not a constructor holding the TxInfo"]
+        );
+    }
+}
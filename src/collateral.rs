@@ -0,0 +1,305 @@
+use std::{collections::BTreeMap, fmt};
+
+use amaru_kernel::{
+    Address, MemoizedTransactionOutput, MintedTx, ShelleyPaymentPart, TransactionInput,
+};
+use amaru_plutus::script_context::{CurrencySymbol, TransactionOutput};
+use anyhow::Result;
+
+use crate::{
+    balance::{self, Asset},
+    collateral_inputs,
+    formatter::ReadableFormatter,
+    get_redeemers,
+    protocol::CollateralParameters,
+    resolver::ResolvedUtxos,
+};
+
+/// A reason for the ledger to reject the collateral of a transaction running scripts, named
+/// after the ledger error it raises.
+pub enum Problem {
+    NoCollateral,
+    /// The input couldn't be resolved, leaving the collateral amounts unknown.
+    Unresolved(TransactionInput),
+    ScriptLocked(TransactionInput),
+    TooManyInputs {
+        count: usize,
+        max: u64,
+    },
+    /// Native assets the collateral return doesn't give back, with their amounts.
+    NativeAssets(Vec<(String, i128)>),
+    Insufficient {
+        net: i128,
+        required: u64,
+        fee: u64,
+        percentage: u64,
+    },
+    IncorrectTotal {
+        stated: u64,
+        net: i128,
+    },
+}
+
+impl Problem {
+    /// Whether the ledger rejects the transaction for it, rather than nawi not knowing.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Problem::Unresolved(_))
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::NoCollateral => write!(
+                f,
+                "the transaction runs Plutus scripts but has no collateral (NoCollateralInputs)"
+            ),
+            Problem::Unresolved(input) => write!(
+                f,
+                "collateral input {} couldn't be resolved, the collateral amounts aren't checked",
+                input.format_readable()
+            ),
+            Problem::ScriptLocked(input) => write!(
+                f,
+                "collateral input {} is locked by a script (ScriptsNotPaidUTxO)",
+                input.format_readable()
+            ),
+            Problem::TooManyInputs { count, max } => write!(
+                f,
+                "{} collateral inputs, but maxCollateralInputs is {} (TooManyCollateralInputs)",
+                count, max
+            ),
+            Problem::NativeAssets(assets) => write!(
+                f,
+                "the collateral return doesn't give back {} (CollateralContainsNonADA)",
+                assets
+                    .iter()
+                    .map(|(asset, amount)| format!("{:+} {}", amount, asset))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Problem::Insufficient {
+                net,
+                required,
+                fee,
+                percentage,
+            } => write!(
+                f,
+                "the collateral is {} lovelace, but {}% of the {} lovelace fee is {} (InsufficientCollateral)",
+                net, percentage, fee, required
+            ),
+            Problem::IncorrectTotal { stated, net } => write!(
+                f,
+                "the total collateral field states {} lovelace, but the collateral is {} (IncorrectTotalCollateralField)",
+                stated, net
+            ),
+        }
+    }
+}
+
+/// The collateral the transaction puts up: what its collateral inputs hold minus what the
+/// collateral return gives back, asset by asset. Lovelace is always present.
+struct Net {
+    inputs: u64,
+    returned: u64,
+    assets: BTreeMap<Asset, i128>,
+}
+
+impl Net {
+    fn lovelace(&self) -> i128 {
+        self.assets.get(&None).copied().unwrap_or_default()
+    }
+}
+
+/// Every reason the ledger has to reject the collateral of the transaction, as it checks it
+/// when the transaction runs Plutus scripts (and only then). The checks against the protocol
+/// parameters are left out without them.
+pub fn check(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    parameters: Option<&CollateralParameters>,
+) -> Vec<Problem> {
+    check_with_net(transaction, utxos, parameters).0
+}
+
+fn check_with_net(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    parameters: Option<&CollateralParameters>,
+) -> (Vec<Problem>, Option<Net>) {
+    let body = &transaction.transaction_body;
+    let runs_scripts = get_redeemers(transaction).is_ok_and(|redeemers| !redeemers.is_empty());
+    if !runs_scripts {
+        return (Vec::new(), None);
+    }
+
+    let collateral = collateral_inputs(transaction);
+    if collateral.is_empty() {
+        return (vec![Problem::NoCollateral], None);
+    }
+
+    let mut problems = Vec::new();
+    if let Some(max) = parameters.and_then(|parameters| parameters.max_inputs)
+        && collateral.len() as u64 > max
+    {
+        problems.push(Problem::TooManyInputs {
+            count: collateral.len(),
+            max,
+        });
+    }
+
+    let mut net = Net {
+        inputs: 0,
+        returned: 0,
+        assets: BTreeMap::from([(None, 0)]),
+    };
+    let mut unresolved = false;
+    for input in &collateral {
+        let Some(output) = utxos.get(input) else {
+            problems.push(Problem::Unresolved(input.clone()));
+            unresolved = true;
+            continue;
+        };
+        if let Address::Shelley(address) = &output.address
+            && let ShelleyPaymentPart::Script(_) = address.payment()
+        {
+            problems.push(Problem::ScriptLocked(input.clone()));
+        }
+        net.inputs += add(&mut net.assets, output, 1);
+    }
+    if let Some(output) = &body.collateral_return
+        && let Ok(output) = MemoizedTransactionOutput::try_from(output.clone())
+    {
+        net.returned = add(&mut net.assets, &output, -1);
+    }
+    if unresolved {
+        return (problems, None);
+    }
+
+    let assets: Vec<_> = net
+        .assets
+        .iter()
+        .filter(|(asset, amount)| asset.is_some() && **amount != 0)
+        .map(|(asset, amount)| (balance::asset_name(asset), *amount))
+        .collect();
+    if !assets.is_empty() {
+        problems.push(Problem::NativeAssets(assets));
+    }
+
+    if let Some(parameters) = parameters {
+        let required = parameters.required(body.fee);
+        if net.lovelace() < i128::from(required) {
+            problems.push(Problem::Insufficient {
+                net: net.lovelace(),
+                required,
+                fee: body.fee,
+                percentage: parameters.percentage,
+            });
+        }
+    }
+
+    if let Some(stated) = body.total_collateral
+        && i128::from(stated) != net.lovelace()
+    {
+        problems.push(Problem::IncorrectTotal {
+            stated,
+            net: net.lovelace(),
+        });
+    }
+
+    (problems, Some(net))
+}
+
+/// The collateral of the transaction against what the ledger requires of it: the lovelace its
+/// inputs put up net of the collateral return, the required share of the fee and the total
+/// collateral field, then every problem found. Returns the report and the number of problems
+/// the ledger rejects the transaction for.
+pub fn describe(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    parameters: Result<CollateralParameters>,
+) -> (String, usize) {
+    let body = &transaction.transaction_body;
+    let (problems, net) = check_with_net(transaction, utxos, parameters.as_ref().ok());
+
+    let mut section = match &parameters {
+        Ok(parameters) => format!(
+            "Collateral check (collateralPercentage {}%):",
+            parameters.percentage
+        ),
+        Err(e) => format!("Collateral check (collateral percentage unknown, {:#}):", e),
+    };
+
+    if let Some(net) = &net {
+        section.push_str(&format!("\n  {:<18} {} lovelace", "Inputs:", net.inputs));
+        section.push_str(&format!("\n  {:<18} {} lovelace", "Return:", net.returned));
+        section.push_str(&format!(
+            "\n  {:<18} {} lovelace",
+            "Collateral:",
+            net.lovelace()
+        ));
+        if let Ok(parameters) = &parameters {
+            section.push_str(&format!(
+                "\n  {:<18} {} lovelace ({}% of the {} lovelace fee)",
+                "Required:",
+                parameters.required(body.fee),
+                parameters.percentage,
+                body.fee
+            ));
+        }
+        if let Some(stated) = body.total_collateral {
+            section.push_str(&format!(
+                "\n  {:<18} {} lovelace",
+                "Total collateral:", stated
+            ));
+        }
+    } else if problems.is_empty() {
+        section.push_str("\n  (none needed, the transaction runs no Plutus script)");
+    }
+
+    for problem in &problems {
+        section.push_str(&format!("\n  - {}", problem));
+    }
+    if net.is_some() && problems.is_empty() {
+        section.push_str("\n  The collateral meets every requirement");
+    }
+
+    let errors = problems.iter().filter(|problem| problem.is_error()).count();
+    (section, errors)
+}
+
+/// Add the value of an output to the net collateral with the given sign, returning its lovelace.
+fn add(assets: &mut BTreeMap<Asset, i128>, output: &MemoizedTransactionOutput, sign: i128) -> u64 {
+    let value = TransactionOutput::from(output).value;
+    let lovelace = value.ada().unwrap_or_default();
+    *assets.entry(None).or_default() += sign * lovelace as i128;
+
+    for (policy, tokens) in value.0.iter() {
+        let CurrencySymbol::Native(policy) = policy else {
+            continue;
+        };
+        for (name, amount) in tokens.iter() {
+            *assets.entry(Some((*policy, name.to_vec()))).or_default() +=
+                sign * i128::from(*amount);
+        }
+    }
+
+    lovelace
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_transaction;
+
+    /// A payment with a spending redeemer and no collateral.
+    const SPEND: &str = "84a30081825820000000000000000000000000000000000000000000000000000000000000000000018182581d60111111111111111111111111111111111111111111111111111111111a000f4240021a00030d40a1058184000000821a000f42401a3b9aca00f5f6";
+
+    #[test]
+    fn scripts_need_collateral() {
+        let bytes = hex::decode(SPEND).unwrap();
+        let transaction = decode_transaction(&bytes).unwrap();
+        let problems = check(&transaction, &ResolvedUtxos::new(), None);
+        assert!(matches!(problems.as_slice(), [Problem::NoCollateral]));
+    }
+}
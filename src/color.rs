@@ -0,0 +1,102 @@
+use std::{fmt::Display, io::IsTerminal, sync::OnceLock};
+
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Default, Clone, Copy, Debug, PartialEq)]
+#[value(rename_all = "kebab-case")]
+pub enum ColorChoice {
+    /// Color when writing to a terminal, unless NO_COLOR is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+static CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[1;36m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+const BOLD_YELLOW: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+/// Hex runs at least this long are hashes (script and key hashes are 56 digits).
+const HASH_LENGTH: usize = 56;
+
+pub fn init(choice: ColorChoice) {
+    let _ = CHOICE.set(choice);
+}
+
+fn enabled(terminal: bool) -> bool {
+    match CHOICE.get().copied().unwrap_or_default() {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        }
+    }
+}
+
+/// Print a warning to stderr, its prefix colored when stderr is a terminal.
+pub fn warning(message: impl Display) {
+    if enabled(std::io::stderr().is_terminal()) {
+        eprintln!("{}WARNING:{} {}", BOLD_YELLOW, RESET, message);
+    } else {
+        eprintln!("WARNING: {}", message);
+    }
+}
+
+/// Color the pretty output of `ReadableFormatter` for stdout: banners and section headers,
+/// hashes, and burnt (negative) amounts.
+pub fn paint(text: &str) -> String {
+    if !enabled(std::io::stdout().is_terminal()) {
+        return text.to_string();
+    }
+
+    text.lines().map(paint_line).collect::<Vec<_>>().join("\n")
+}
+
+fn paint_line(line: &str) -> String {
+    let trimmed = line.trim();
+
+    if !trimmed.is_empty() && trimmed.chars().all(|c| c == '=') {
+        return format!("{}{}{}", BOLD, line, RESET);
+    }
+    if trimmed.starts_with("Script Context (") {
+        return format!("{}{}{}", CYAN, line, RESET);
+    }
+    if trimmed.ends_with(':') || (trimmed.ends_with("(s)") && trimmed.contains(": ")) {
+        return format!("{}{}{}", BOLD, line, RESET);
+    }
+    if let Some((name, amount)) = line.rsplit_once(": -")
+        && !amount.is_empty()
+        && amount.chars().all(|c| c.is_ascii_digit())
+    {
+        return format!("{}: {}-{}{}", paint_hashes(name), RED, amount, RESET);
+    }
+
+    paint_hashes(line)
+}
+
+fn paint_hashes(line: &str) -> String {
+    let mut painted = String::new();
+    let mut run = String::new();
+
+    for c in line.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_hexdigit() {
+            run.push(c);
+            continue;
+        }
+        if run.len() >= HASH_LENGTH {
+            painted.push_str(&format!("{}{}{}", YELLOW, run, RESET));
+        } else {
+            painted.push_str(&run);
+        }
+        run.clear();
+        painted.push(c);
+    }
+    painted.pop();
+
+    painted
+}
@@ -0,0 +1,203 @@
+use amaru_kernel::{PlutusData, cbor};
+use anyhow::{Context, Result, bail};
+
+use crate::{PlutusVersion, formatter::ReadableFormatter};
+
+/// Names of the fields of the ScriptContext and of its TxInfo, by Plutus version.
+pub fn field_names(version: PlutusVersion) -> (&'static [&'static str], &'static [&'static str]) {
+    match version {
+        PlutusVersion::PlutusV1 => (
+            &["txInfo", "purpose"],
+            &[
+                "inputs",
+                "outputs",
+                "fee",
+                "mint",
+                "certificates",
+                "withdrawals",
+                "validRange",
+                "signatories",
+                "datums",
+                "id",
+            ],
+        ),
+        PlutusVersion::PlutusV2 => (
+            &["txInfo", "purpose"],
+            &[
+                "inputs",
+                "referenceInputs",
+                "outputs",
+                "fee",
+                "mint",
+                "certificates",
+                "withdrawals",
+                "validRange",
+                "signatories",
+                "redeemers",
+                "datums",
+                "id",
+            ],
+        ),
+        PlutusVersion::PlutusV3 => (
+            &["txInfo", "redeemer", "scriptInfo"],
+            &[
+                "inputs",
+                "referenceInputs",
+                "outputs",
+                "fee",
+                "mint",
+                "certificates",
+                "withdrawals",
+                "validRange",
+                "signatories",
+                "redeemers",
+                "datums",
+                "id",
+                "votes",
+                "proposalProcedures",
+                "currentTreasuryAmount",
+                "treasuryDonation",
+            ],
+        ),
+    }
+}
+
+/// Decode a reference script context, e.g. one dumped by the node's Plutus debugging tools.
+/// Dumps of the script arguments (datum, redeemer, context) are accepted too: the context is
+/// always the last of them.
+pub fn decode_reference(bytes: &[u8]) -> Result<PlutusData> {
+    let data: PlutusData =
+        cbor::decode(bytes).context("Failed to decode the reference context as PlutusData")?;
+
+    match data {
+        PlutusData::Array(arguments) => match arguments.last() {
+            Some(context @ PlutusData::Constr(_)) => Ok(context.clone()),
+            _ => bail!("The reference context is a list that doesn't end with a ScriptContext"),
+        },
+        context => Ok(context),
+    }
+}
+
+/// Where nawi's context first differs from the reference, with both values at that point.
+pub fn first_difference(
+    version: PlutusVersion,
+    computed: &PlutusData,
+    reference: &PlutusData,
+) -> Option<String> {
+    let mut found = Vec::new();
+    differences(version, computed, reference, &[], &mut found);
+
+    found.first().map(|(path, computed, reference)| {
+        describe(path, ("nawi", computed), ("reference", reference))
+    })
+}
+
+/// Every place where two Plutus data differ, with both values there. Constructors, lists and
+/// maps whose shapes differ are reported whole rather than descended into.
+pub fn all_differences(
+    version: PlutusVersion,
+    a: &PlutusData,
+    b: &PlutusData,
+    labels: (&str, &str),
+) -> Vec<String> {
+    let mut found = Vec::new();
+    differences(version, a, b, &[], &mut found);
+
+    found
+        .iter()
+        .map(|(path, a, b)| describe(path, (labels.0, a), (labels.1, b)))
+        .collect()
+}
+
+/// The Plutus version a script context was most likely built for, from its shape: PlutusV3
+/// contexts have three fields, and PlutusV1 TxInfos ten.
+pub fn guess_version(context: &PlutusData) -> PlutusVersion {
+    let PlutusData::Constr(context) = context else {
+        return PlutusVersion::default();
+    };
+
+    match (context.fields.len(), context.fields.first()) {
+        (2, Some(PlutusData::Constr(tx_info))) if tx_info.fields.len() == 10 => {
+            PlutusVersion::PlutusV1
+        }
+        (2, _) => PlutusVersion::PlutusV2,
+        _ => PlutusVersion::PlutusV3,
+    }
+}
+
+fn describe(path: &str, a: (&str, &PlutusData), b: (&str, &PlutusData)) -> String {
+    let width = a.0.len().max(b.0.len()) + 1;
+    let indent = format!("\n{}", " ".repeat(width + 3));
+
+    format!(
+        "{}\n  {:<width$} {}\n  {:<width$} {}",
+        path,
+        format!("{}:", a.0),
+        a.1.format_readable().replace('\n', &indent),
+        format!("{}:", b.0),
+        b.1.format_readable().replace('\n', &indent),
+    )
+}
+
+fn differences<'a>(
+    version: PlutusVersion,
+    a: &'a PlutusData,
+    b: &'a PlutusData,
+    path: &[usize],
+    found: &mut Vec<(String, &'a PlutusData, &'a PlutusData)>,
+) {
+    let children: Vec<(&PlutusData, &PlutusData)> = match (a, b) {
+        (PlutusData::Constr(x), PlutusData::Constr(y))
+            if x.tag == y.tag
+                && x.any_constructor == y.any_constructor
+                && x.fields.len() == y.fields.len() =>
+        {
+            x.fields.iter().zip(y.fields.iter()).collect()
+        }
+        (PlutusData::Array(x), PlutusData::Array(y)) if x.len() == y.len() => {
+            x.iter().zip(y.iter()).collect()
+        }
+        (PlutusData::Map(x), PlutusData::Map(y)) if x.len() == y.len() => x
+            .iter()
+            .zip(y.iter())
+            .flat_map(|((kx, vx), (ky, vy))| [(kx, ky), (vx, vy)])
+            .collect(),
+        (PlutusData::BigInt(_), PlutusData::BigInt(_))
+        | (PlutusData::BoundedBytes(_), PlutusData::BoundedBytes(_))
+            if a == b =>
+        {
+            return;
+        }
+        _ => {
+            found.push((describe_path(version, path), a, b));
+            return;
+        }
+    };
+
+    for (index, (x, y)) in children.into_iter().enumerate() {
+        let mut path = path.to_vec();
+        path.push(index);
+        differences(version, x, y, &path, found);
+    }
+}
+
+/// A readable path to a value of the context: field names down to the TxInfo fields, then
+/// positions. Map entries count twice, as key then value.
+fn describe_path(version: PlutusVersion, path: &[usize]) -> String {
+    let (context_fields, tx_info_fields) = field_names(version);
+
+    let mut described = String::from("context");
+    for (depth, index) in path.iter().enumerate() {
+        let name = match depth {
+            0 => context_fields.get(*index),
+            1 if path[0] == 0 => tx_info_fields.get(*index),
+            _ => None,
+        };
+        match name {
+            Some(name) => described.push_str(&format!(".{}", name)),
+            None => described.push_str(&format!("[{}]", index)),
+        }
+    }
+
+    described
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use figment::{
+    Figment,
+    providers::{Env, Format, Toml},
+};
+use std::{num::NonZeroUsize, path::PathBuf};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    blockfrost::BlockfrostConfig, evaluate::OgmiosConfig, http::HttpConfig, kupo::KupoConfig,
+    retry::RetryConfig, utxo_rpc::UtxoRpcConfig,
+};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    #[default]
+    Blockfrost,
+    Kupo,
+    #[serde(rename = "utxorpc")]
+    UtxoRpc,
+    File,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    /// A single provider, or an ordered list to fall through when an input can't be resolved.
+    #[serde(default = "default_providers", deserialize_with = "one_or_many")]
+    pub provider: Vec<Provider>,
+
+    #[serde(flatten)]
+    pub blockfrost: Option<BlockfrostConfig>,
+
+    pub kupo: Option<KupoConfig>,
+
+    pub utxorpc: Option<UtxoRpcConfig>,
+
+    /// Evaluates transactions for --cross-check.
+    pub ogmios: Option<OgmiosConfig>,
+
+    /// Snapshot read by the `file` provider.
+    pub utxo_file: Option<PathBuf>,
+
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Upper bound on the number of UTxO requests in flight at once.
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: NonZeroUsize,
+}
+
+fn default_max_concurrency() -> NonZeroUsize {
+    NonZeroUsize::new(10).expect("non-zero default")
+}
+
+fn default_providers() -> Vec<Provider> {
+    vec![Provider::default()]
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<Provider>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(Provider),
+        Many(Vec<Provider>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(provider) => vec![provider],
+        OneOrMany::Many(providers) => providers,
+    })
+}
+
+pub fn load() -> Result<Config> {
+    Figment::new()
+        .merge(Toml::file("nawi.toml"))
+        .merge(Env::prefixed("BLOCKFROST_"))
+        .merge(Env::prefixed("KUPO_").map(|key| format!("kupo.{}", key).into()))
+        .merge(Env::prefixed("UTXORPC_").map(|key| format!("utxorpc.{}", key).into()))
+        .merge(Env::prefixed("OGMIOS_").map(|key| format!("ogmios.{}", key).into()))
+        .merge(Env::prefixed("NAWI_").split("__"))
+        .extract()
+        .context("Failed to load configuration from nawi.toml and the environment")
+}
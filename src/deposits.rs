@@ -0,0 +1,220 @@
+use amaru_kernel::{Certificate, MintedTx};
+
+use crate::protocol::DepositParameters;
+
+/// Whether an entry locks lovelace in a deposit or releases it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Deposit,
+    Refund,
+}
+
+/// One deposit or refund of the transaction.
+pub struct Entry {
+    /// The certificate or proposal it comes from.
+    pub source: String,
+    pub direction: Direction,
+    /// None when neither the transaction nor the protocol parameters tell it.
+    pub amount: Option<u64>,
+    /// Where the amount comes from, and how it compares with the protocol parameters.
+    pub origin: String,
+    /// Whether the amount rests on an assumption the transaction can't confirm, e.g. that a
+    /// registered pool is new.
+    pub assumed: bool,
+}
+
+/// The deposits and refunds implied by the certificates and proposal procedures of a
+/// transaction. Conway certificates and proposals state their amounts, which the ledger requires
+/// to match the protocol parameters; older certificates leave them to the parameters.
+pub struct Deposits(pub Vec<Entry>);
+
+impl Deposits {
+    pub fn of(transaction: &MintedTx<'_>, parameters: &DepositParameters) -> Self {
+        let body = &transaction.transaction_body;
+        let mut entries = Vec::new();
+
+        for (index, certificate) in body.certificates.iter().flat_map(|c| c.iter()).enumerate() {
+            let source = |kind: &str| format!("certificate {} ({})", index, kind);
+            let entry = match certificate {
+                Certificate::StakeRegistration(_) => implied(
+                    source("stake registration"),
+                    Direction::Deposit,
+                    parameters.stake_credential,
+                    "keyDeposit",
+                    None,
+                ),
+                Certificate::StakeDeregistration(_) => implied(
+                    source("stake deregistration"),
+                    Direction::Refund,
+                    parameters.stake_credential,
+                    "keyDeposit",
+                    Some("assuming it hasn't changed since the registration"),
+                ),
+                Certificate::PoolRegistration { .. } => implied(
+                    source("pool registration"),
+                    Direction::Deposit,
+                    parameters.stake_pool,
+                    "poolDeposit",
+                    Some("charged only when the pool is new"),
+                ),
+                Certificate::Reg(_, coin) => stated(
+                    source("stake registration"),
+                    Direction::Deposit,
+                    *coin,
+                    parameters.stake_credential,
+                    "keyDeposit",
+                ),
+                Certificate::StakeRegDeleg(_, _, coin)
+                | Certificate::VoteRegDeleg(_, _, coin)
+                | Certificate::StakeVoteRegDeleg(_, _, _, coin) => stated(
+                    source("stake registration and delegation"),
+                    Direction::Deposit,
+                    *coin,
+                    parameters.stake_credential,
+                    "keyDeposit",
+                ),
+                Certificate::UnReg(_, coin) => stated(
+                    source("stake deregistration"),
+                    Direction::Refund,
+                    *coin,
+                    None,
+                    "keyDeposit",
+                ),
+                Certificate::RegDRepCert(_, coin, _) => stated(
+                    source("DRep registration"),
+                    Direction::Deposit,
+                    *coin,
+                    parameters.drep,
+                    "dRepDeposit",
+                ),
+                Certificate::UnRegDRepCert(_, coin) => stated(
+                    source("DRep deregistration"),
+                    Direction::Refund,
+                    *coin,
+                    None,
+                    "dRepDeposit",
+                ),
+                _ => continue,
+            };
+            entries.push(entry);
+        }
+
+        for (index, proposal) in body
+            .proposal_procedures
+            .iter()
+            .flat_map(|p| p.iter())
+            .enumerate()
+        {
+            entries.push(stated(
+                format!("proposal {}", index),
+                Direction::Deposit,
+                proposal.deposit,
+                parameters.gov_action,
+                "govActionDeposit",
+            ));
+        }
+
+        Self(entries)
+    }
+
+    pub fn deposits(&self) -> u64 {
+        self.total(Direction::Deposit)
+    }
+
+    pub fn refunds(&self) -> u64 {
+        self.total(Direction::Refund)
+    }
+
+    /// Number of entries whose amount is unknown.
+    pub fn unknown(&self) -> usize {
+        self.0.iter().filter(|entry| entry.amount.is_none()).count()
+    }
+
+    /// Number of entries whose amount is unknown or assumed.
+    pub fn uncertain(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|entry| entry.amount.is_none() || entry.assumed)
+            .count()
+    }
+
+    fn total(&self, direction: Direction) -> u64 {
+        self.0
+            .iter()
+            .filter(|entry| entry.direction == direction)
+            .filter_map(|entry| entry.amount)
+            .sum()
+    }
+
+    /// One line per deposit or refund. None when the transaction has neither.
+    pub fn describe(&self) -> Option<String> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let mut section = String::from("Deposits and refunds:");
+        for entry in &self.0 {
+            let direction = match entry.direction {
+                Direction::Deposit => "deposit",
+                Direction::Refund => "refund",
+            };
+            let amount = match entry.amount {
+                Some(amount) => format!("{} lovelace", amount),
+                None => "unknown".to_string(),
+            };
+            section.push_str(&format!(
+                "\n  {}: {} {} ({})",
+                entry.source, direction, amount, entry.origin
+            ));
+        }
+        Some(section)
+    }
+}
+
+/// An amount the certificate leaves to the protocol parameters, with what it assumes.
+fn implied(
+    source: String,
+    direction: Direction,
+    parameter: Option<u64>,
+    name: &str,
+    caveat: Option<&str>,
+) -> Entry {
+    let origin = match (parameter, caveat) {
+        (Some(_), Some(caveat)) => format!("from {}, {}", name, caveat),
+        (Some(_), None) => format!("from {}", name),
+        (None, _) => format!("{} not in the protocol parameters", name),
+    };
+    Entry {
+        source,
+        direction,
+        amount: parameter,
+        origin,
+        assumed: caveat.is_some(),
+    }
+}
+
+/// An amount stated in the certificate or proposal, checked against the protocol parameters when
+/// given. Refunds return what was paid, which the parameters may no longer tell.
+fn stated(
+    source: String,
+    direction: Direction,
+    amount: u64,
+    parameter: Option<u64>,
+    name: &str,
+) -> Entry {
+    let origin = match parameter {
+        Some(expected) if expected != amount => format!(
+            "stated, but {} is {}: the ledger rejects the mismatch",
+            name, expected
+        ),
+        Some(_) => format!("stated, matches {}", name),
+        None => "stated".to_string(),
+    };
+    Entry {
+        source,
+        direction,
+        amount: Some(amount),
+        origin,
+        assumed: false,
+    }
+}
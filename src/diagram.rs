@@ -0,0 +1,336 @@
+use std::path::Path;
+
+use amaru_kernel::{Address, ShelleyPaymentPart};
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use crate::RedeemerContext;
+
+enum Shape {
+    Transaction,
+    Output,
+    Policy,
+}
+
+struct Node {
+    id: String,
+    lines: Vec<String>,
+    shape: Shape,
+    /// Whether one of the selected redeemers points at it.
+    highlighted: bool,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    /// Reference inputs are read, not spent.
+    dashed: bool,
+}
+
+/// Draw the transaction as a Mermaid (.mmd) or Graphviz (.dot) graph: the UTxOs it spends and
+/// reads, the policies it mints under and the outputs it creates, with what the selected
+/// redeemers point at highlighted.
+pub fn write(path: &Path, transaction: &str, contexts: &[RedeemerContext]) -> Result<()> {
+    let mermaid = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("mmd" | "mermaid") => true,
+        Some("dot" | "gv") => false,
+        _ => bail!(
+            "Unsupported diagram file {}, expected a .mmd or .dot file",
+            path.display()
+        ),
+    };
+
+    let Some(first) = contexts.first() else {
+        bail!("No script context to draw");
+    };
+    let (nodes, edges) = graph(transaction, &first.json_context["txInfo"], contexts);
+
+    let document = if mermaid {
+        render_mermaid(&nodes, &edges)
+    } else {
+        render_dot(&nodes, &edges)
+    };
+
+    std::fs::write(path, document)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    eprintln!("Diagram -> {}", path.display());
+
+    Ok(())
+}
+
+fn graph(
+    transaction: &str,
+    tx_info: &Value,
+    contexts: &[RedeemerContext],
+) -> (Vec<Node>, Vec<Edge>) {
+    let pointed_at = |purpose: &str, index: usize| {
+        contexts
+            .iter()
+            .any(|context| context.purpose == purpose && context.index as usize == index)
+    };
+
+    let mut tx_lines = vec![
+        format!("Transaction {}", short(transaction)),
+        format!("fee {}", lovelace(&tx_info["fee"])),
+    ];
+    for context in contexts {
+        if matches!(
+            context.purpose.as_str(),
+            "Cert" | "Reward" | "Vote" | "Propose"
+        ) {
+            tx_lines.push(format!(
+                "redeemer {} ({} #{})",
+                context.redeemer, context.purpose, context.index
+            ));
+        }
+    }
+
+    let mut nodes = vec![Node {
+        id: "tx".to_string(),
+        lines: tx_lines,
+        shape: Shape::Transaction,
+        highlighted: false,
+    }];
+    let mut edges = Vec::new();
+
+    for (i, input) in entries(&tx_info["inputs"]) {
+        let id = format!("in{}", i);
+        let mut lines = vec![text(&input["outputReference"])];
+        lines.extend(output_lines(&input["output"]));
+        nodes.push(Node {
+            id: id.clone(),
+            lines,
+            shape: Shape::Output,
+            highlighted: pointed_at("Spend", i),
+        });
+        edges.push(Edge {
+            from: id,
+            to: "tx".to_string(),
+            dashed: false,
+        });
+    }
+
+    for (i, input) in entries(&tx_info["referenceInputs"]) {
+        let id = format!("ref{}", i);
+        let mut lines = vec![format!("reference {}", text(&input["outputReference"]))];
+        lines.extend(output_lines(&input["output"]));
+        nodes.push(Node {
+            id: id.clone(),
+            lines,
+            shape: Shape::Output,
+            highlighted: false,
+        });
+        edges.push(Edge {
+            from: id,
+            to: "tx".to_string(),
+            dashed: true,
+        });
+    }
+
+    // Mint redeemers point at policies in the order of their hashes, as the JSON map keeps them.
+    for (i, (policy, assets)) in tx_info["mint"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let id = format!("mint{}", i);
+        let mut lines = vec![format!("mint {}", short(policy))];
+        for (name, quantity) in assets.as_object().into_iter().flatten() {
+            lines.push(format!("{}: {}", asset_name(name), text(quantity)));
+        }
+        nodes.push(Node {
+            id: id.clone(),
+            lines,
+            shape: Shape::Policy,
+            highlighted: pointed_at("Mint", i),
+        });
+        edges.push(Edge {
+            from: id,
+            to: "tx".to_string(),
+            dashed: false,
+        });
+    }
+
+    for (i, output) in entries(&tx_info["outputs"]) {
+        let id = format!("out{}", i);
+        let mut lines = vec![format!("output #{}", i)];
+        lines.extend(output_lines(output));
+        nodes.push(Node {
+            id: id.clone(),
+            lines,
+            shape: Shape::Output,
+            highlighted: false,
+        });
+        edges.push(Edge {
+            from: "tx".to_string(),
+            to: id,
+            dashed: false,
+        });
+    }
+
+    (nodes, edges)
+}
+
+/// Address, value, script and datum of an output.
+fn output_lines(output: &Value) -> Vec<String> {
+    let address = text(&output["address"]);
+    let mut lines = vec![short_address(&address)];
+
+    if let Some(hash) = payment_script(&address) {
+        lines.push(format!("script {}", short(&hash)));
+    }
+
+    let assets = output["value"]["assets"]
+        .as_object()
+        .map(|policies| {
+            policies
+                .values()
+                .filter_map(Value::as_object)
+                .map(|assets| assets.len())
+                .sum::<usize>()
+        })
+        .unwrap_or_default();
+    let mut value = format!("{} lovelace", text(&output["value"]["lovelace"]));
+    if assets > 0 {
+        value.push_str(&format!(" + {} asset(s)", assets));
+    }
+    lines.push(value);
+
+    match &output["datum"] {
+        Value::Null => {}
+        datum if datum.get("hash").is_some() => {
+            lines.push(format!("datum hash {}", short(&text(&datum["hash"]))))
+        }
+        _ => lines.push("inline datum".to_string()),
+    }
+
+    if let Some(script) = output["referenceScript"].as_object() {
+        lines.push(format!(
+            "reference script {}",
+            short(&text(&script["hash"]))
+        ));
+    }
+
+    lines
+}
+
+/// The script hash of a script-locked address.
+fn payment_script(address: &str) -> Option<String> {
+    match Address::from_bech32(address).ok()? {
+        Address::Shelley(address) => match address.payment() {
+            ShelleyPaymentPart::Script(hash) => Some(hex::encode(hash)),
+            ShelleyPaymentPart::Key(_) => None,
+        },
+        _ => None,
+    }
+}
+
+fn asset_name(hex_name: &str) -> String {
+    hex::decode(hex_name)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .filter(|name| name.chars().all(|c| c.is_ascii_graphic()))
+        .unwrap_or_else(|| short(hex_name))
+}
+
+fn entries(value: &Value) -> impl Iterator<Item = (usize, &Value)> {
+    value.as_array().into_iter().flatten().enumerate()
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// PlutusV3 fees are lovelace, PlutusV1 fees a value.
+fn lovelace(fee: &Value) -> String {
+    format!("{} lovelace", text(fee.get("lovelace").unwrap_or(fee)))
+}
+
+fn short(hash: &str) -> String {
+    if hash.len() <= 12 {
+        return hash.to_string();
+    }
+
+    format!("{}…", &hash[..8])
+}
+
+fn short_address(address: &str) -> String {
+    if address.len() <= 24 {
+        return address.to_string();
+    }
+
+    format!("{}…{}", &address[..14], &address[address.len() - 6..])
+}
+
+fn render_mermaid(nodes: &[Node], edges: &[Edge]) -> String {
+    let mut document = String::from("flowchart LR\n");
+
+    for node in nodes {
+        let label = node
+            .lines
+            .iter()
+            .map(|line| line.replace('"', "#quot;"))
+            .collect::<Vec<_>>()
+            .join("<br/>");
+        let (open, close) = match node.shape {
+            Shape::Transaction => ("((", "))"),
+            Shape::Output => ("[", "]"),
+            Shape::Policy => ("{{", "}}"),
+        };
+        document.push_str(&format!("  {}{}\"{}\"{}\n", node.id, open, label, close));
+    }
+
+    for edge in edges {
+        let arrow = if edge.dashed { "-.->" } else { "-->" };
+        document.push_str(&format!("  {} {} {}\n", edge.from, arrow, edge.to));
+    }
+
+    let highlighted: Vec<_> = nodes
+        .iter()
+        .filter(|node| node.highlighted)
+        .map(|node| node.id.as_str())
+        .collect();
+    if !highlighted.is_empty() {
+        document.push_str("  classDef redeemer stroke:#d33,stroke-width:3px\n");
+        document.push_str(&format!("  class {} redeemer\n", highlighted.join(",")));
+    }
+
+    document
+}
+
+fn render_dot(nodes: &[Node], edges: &[Edge]) -> String {
+    let mut document = String::from(
+        "digraph transaction {\n  rankdir=LR;\n  node [shape=box, fontname=\"monospace\"];\n",
+    );
+
+    for node in nodes {
+        let label = node
+            .lines
+            .iter()
+            .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+            .collect::<Vec<_>>()
+            .join("\\n");
+        let mut attributes = vec![format!("label=\"{}\"", label)];
+        match node.shape {
+            Shape::Transaction => attributes.push("shape=ellipse".to_string()),
+            Shape::Policy => attributes.push("shape=hexagon".to_string()),
+            Shape::Output => {}
+        }
+        if node.highlighted {
+            attributes.push("color=\"#dd3333\", penwidth=3".to_string());
+        }
+        document.push_str(&format!("  {} [{}];\n", node.id, attributes.join(", ")));
+    }
+
+    for edge in edges {
+        let style = if edge.dashed { " [style=dashed]" } else { "" };
+        document.push_str(&format!("  {} -> {}{};\n", edge.from, edge.to, style));
+    }
+
+    document.push_str("}\n");
+    document
+}
@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use amaru_kernel::{PlutusData, cbor};
+use anyhow::{Context, Result, bail};
+use clap::Args as ClapArgs;
+
+use crate::{Args, compare, read_cbor_file};
+
+#[derive(ClapArgs, Debug)]
+pub struct DiffDataArgs {
+    /// First Plutus data (file or hex), e.g. the context nawi built
+    #[arg(value_name = "FILE|HEX")]
+    a: String,
+
+    /// Second Plutus data (file or hex), e.g. the context a validator was given
+    #[arg(value_name = "FILE|HEX")]
+    b: String,
+}
+
+/// Decode two CBOR blobs as Plutus data and report every path where they differ. Script
+/// contexts are described with field names, for the version given by --plutus-version or else
+/// guessed from the shape of the first one.
+pub fn run(args: &Args, diff: &DiffDataArgs) -> Result<()> {
+    let a = decode(&diff.a, "first")?;
+    let b = decode(&diff.b, "second")?;
+
+    let version = args
+        .plutus_version
+        .unwrap_or_else(|| compare::guess_version(&a));
+    let differences = compare::all_differences(version, &a, &b, ("a", "b"));
+
+    let Some(first) = differences.first() else {
+        println!("The data are identical");
+        return Ok(());
+    };
+
+    println!("First difference at {}", first);
+    if differences.len() > 1 {
+        println!("\nAll differences ({}):", differences.len());
+        for difference in &differences {
+            println!("\n{}", difference);
+        }
+    }
+
+    bail!("The data differ in {} place(s)", differences.len())
+}
+
+fn decode(input: &str, what: &str) -> Result<PlutusData> {
+    let path = Path::new(input);
+    let bytes = if path.is_file() {
+        read_cbor_file(path, what)?
+    } else {
+        hex::decode(input.trim())
+            .with_context(|| format!("The {} argument is neither a file nor valid hex", what))?
+    };
+
+    cbor::decode(&bytes)
+        .with_context(|| format!("Failed to decode the {} data as PlutusData", what))
+}
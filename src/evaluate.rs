@@ -0,0 +1,284 @@
+use amaru_kernel::{Redeemer, ScriptPurpose};
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{
+    config,
+    http::{HttpConfig, client_builder},
+    resolver::UtxoResolver,
+};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct OgmiosConfig {
+    pub url: String,
+}
+
+/// What the provider's evaluation of one redeemer came to.
+#[derive(Debug)]
+pub enum Outcome {
+    Budget { memory: u64, steps: u64 },
+    Failure(String),
+}
+
+/// The provider's evaluation of one redeemer, identified by its ledger pointer.
+#[derive(Debug)]
+pub struct Evaluation {
+    pub purpose: &'static str,
+    pub index: u32,
+    pub outcome: Outcome,
+}
+
+/// Evaluate the transaction through Ogmios if it is configured, or else through the provider.
+pub async fn evaluate(
+    resolver: &dyn UtxoResolver,
+    transaction: &[u8],
+) -> Result<(&'static str, Vec<Evaluation>)> {
+    let config = config::load().ok();
+
+    match config.as_ref().and_then(|config| config.ogmios.as_ref()) {
+        Some(ogmios) => {
+            let http = config
+                .as_ref()
+                .map(|config| config.http.clone())
+                .unwrap_or_default();
+            let evaluations = evaluate_with_ogmios(ogmios, &http, transaction).await?;
+            Ok(("Ogmios", evaluations))
+        }
+        None => {
+            let evaluations = resolver.evaluate_transaction(transaction).await?;
+            Ok((resolver.name(), evaluations))
+        }
+    }
+}
+
+async fn evaluate_with_ogmios(
+    config: &OgmiosConfig,
+    http: &HttpConfig,
+    transaction: &[u8],
+) -> Result<Vec<Evaluation>> {
+    let client = client_builder(http)?
+        .build()
+        .context("Failed to create the Ogmios client")?;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "evaluateTransaction",
+        "params": { "transaction": { "cbor": hex::encode(transaction) } },
+    });
+
+    let response: Value = client
+        .post(&config.url)
+        .json(&request)
+        .send()
+        .await
+        .context(format!("Failed to query Ogmios at {}", config.url))?
+        .json()
+        .await
+        .context(format!("Invalid response from Ogmios at {}", config.url))?;
+
+    parse_evaluation(&response)
+}
+
+/// Read an `evaluateTransaction` response, in the shape of Ogmios v6 or of Ogmios v5 (which
+/// Blockfrost serves by default).
+pub fn parse_evaluation(response: &Value) -> Result<Vec<Evaluation>> {
+    // Ogmios v6: a list of budgets, or an error listing the failing validators.
+    if let Some(results) = response.get("result").and_then(Value::as_array) {
+        return results
+            .iter()
+            .map(|result| {
+                let (purpose, index) = v6_validator(result)?;
+                let budget = &result["budget"];
+                Ok(Evaluation {
+                    purpose,
+                    index,
+                    outcome: Outcome::Budget {
+                        memory: budget["memory"].as_u64().unwrap_or_default(),
+                        steps: budget["cpu"].as_u64().unwrap_or_default(),
+                    },
+                })
+            })
+            .collect();
+    }
+
+    if let Some(error) = response.get("error") {
+        let Some(failures) = error.get("data").and_then(Value::as_array) else {
+            bail!(
+                "Evaluation failed: {}",
+                error["message"].as_str().unwrap_or("unknown error")
+            );
+        };
+
+        return failures
+            .iter()
+            .map(|failure| {
+                let (purpose, index) = v6_validator(failure)?;
+                Ok(Evaluation {
+                    purpose,
+                    index,
+                    outcome: Outcome::Failure(describe_failure(&failure["error"])),
+                })
+            })
+            .collect();
+    }
+
+    // Ogmios v5: budgets or failures keyed by `<purpose>:<index>`.
+    let result = &response["result"];
+    let (entries, failed) = match (
+        result["EvaluationResult"].as_object(),
+        result["EvaluationFailure"]["ScriptFailures"].as_object(),
+    ) {
+        (Some(entries), _) => (entries, false),
+        (None, Some(entries)) => (entries, true),
+        (None, None) => bail!(
+            "Unexpected evaluation response: {}",
+            response.to_string().chars().take(500).collect::<String>()
+        ),
+    };
+
+    entries
+        .iter()
+        .map(|(pointer, value)| {
+            let (purpose, index) = pointer
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid redeemer pointer in evaluation: {}", pointer))?;
+            Ok(Evaluation {
+                purpose: purpose_name(purpose)?,
+                index: index.parse().context(format!(
+                    "Invalid redeemer pointer in evaluation: {}",
+                    pointer
+                ))?,
+                outcome: if failed {
+                    Outcome::Failure(describe_failure(value))
+                } else {
+                    Outcome::Budget {
+                        memory: value["memory"].as_u64().unwrap_or_default(),
+                        steps: value["steps"].as_u64().unwrap_or_default(),
+                    }
+                },
+            })
+        })
+        .collect()
+}
+
+fn v6_validator(entry: &Value) -> Result<(&'static str, u32)> {
+    let validator = &entry["validator"];
+    let purpose = validator["purpose"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Evaluation entry without a validator purpose: {}", entry))?;
+    let index = validator["index"]
+        .as_u64()
+        .ok_or_else(|| anyhow!("Evaluation entry without a validator index: {}", entry))?;
+
+    Ok((purpose_name(purpose)?, index as u32))
+}
+
+/// The purpose names of `--redeemer`, from those of Ogmios v5 and v6.
+fn purpose_name(purpose: &str) -> Result<&'static str> {
+    match purpose {
+        "spend" => Ok("spend"),
+        "mint" => Ok("mint"),
+        "certificate" | "publish" => Ok("cert"),
+        "withdrawal" | "withdraw" => Ok("reward"),
+        "vote" => Ok("vote"),
+        "propose" => Ok("propose"),
+        _ => bail!("Unknown redeemer purpose in evaluation: {}", purpose),
+    }
+}
+
+fn describe_failure(error: &Value) -> String {
+    error
+        .get("message")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| error.to_string())
+}
+
+/// The `--redeemer` name of a redeemer's purpose.
+pub fn redeemer_purpose(redeemer: &Redeemer) -> &'static str {
+    script_purpose_name(redeemer.tag)
+}
+
+/// The `--redeemer` name of a script purpose.
+pub fn script_purpose_name(purpose: ScriptPurpose) -> &'static str {
+    match purpose {
+        ScriptPurpose::Spend => "spend",
+        ScriptPurpose::Mint => "mint",
+        ScriptPurpose::Cert => "cert",
+        ScriptPurpose::Reward => "reward",
+        ScriptPurpose::Vote => "vote",
+        ScriptPurpose::Propose => "propose",
+    }
+}
+
+/// Compare the provider's evaluation with the redeemers of the transaction and the budgets they
+/// declare. Returns the report and the number of disagreements.
+pub fn cross_check(
+    provider: &str,
+    redeemers: &[&Redeemer],
+    evaluations: &[Evaluation],
+) -> (String, usize) {
+    let mut report = format!("Cross-check against {}:", provider);
+    let mut disagreements = 0;
+
+    for redeemer in redeemers {
+        let purpose = redeemer_purpose(redeemer);
+        let pointer = format!("{}:{}", purpose, redeemer.index);
+        let declared = &redeemer.ex_units;
+
+        let verdict = match evaluations
+            .iter()
+            .find(|evaluation| evaluation.purpose == purpose && evaluation.index == redeemer.index)
+        {
+            None => {
+                disagreements += 1;
+                "MISSING  not evaluated by the provider".to_string()
+            }
+            Some(Evaluation {
+                outcome: Outcome::Failure(error),
+                ..
+            }) => {
+                disagreements += 1;
+                format!(
+                    "FAILED   the script fails with the ledger's own context: {}",
+                    error
+                )
+            }
+            Some(Evaluation {
+                outcome: Outcome::Budget { memory, steps },
+                ..
+            }) if *memory > declared.mem || *steps > declared.steps => {
+                disagreements += 1;
+                format!(
+                    "OVER     needs {} mem / {} steps, the transaction declares {} mem / {} steps",
+                    memory, steps, declared.mem, declared.steps
+                )
+            }
+            Some(Evaluation {
+                outcome: Outcome::Budget { memory, steps },
+                ..
+            }) => format!(
+                "ok       {} mem / {} steps, within the declared {} mem / {} steps",
+                memory, steps, declared.mem, declared.steps
+            ),
+        };
+
+        report.push_str(&format!("\n  {:<10} {}", pointer, verdict));
+    }
+
+    for evaluation in evaluations {
+        let known = redeemers.iter().any(|redeemer| {
+            redeemer_purpose(redeemer) == evaluation.purpose && redeemer.index == evaluation.index
+        });
+        if !known {
+            disagreements += 1;
+            report.push_str(&format!(
+                "\n  {:<10} UNKNOWN  evaluated by the provider, but not a redeemer nawi found",
+                format!("{}:{}", evaluation.purpose, evaluation.index)
+            ));
+        }
+    }
+
+    (report, disagreements)
+}
@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+
+use amaru_kernel::{Hasher, to_cbor};
+use anyhow::{Context, Result, bail};
+use clap::{ArgGroup, Args as ClapArgs, ValueEnum};
+
+use crate::{
+    Args, PlutusVersion, RedeemerContext, RedeemerSelector, aiken,
+    batch::{self, Source},
+    resolve_protocol_version,
+    resolver::UtxoResolver,
+};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+#[value(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    /// An Aiken module with one test per redeemer, running the validator on its context
+    AikenTest,
+    /// A Rust module embedding the CBOR of each context along with its hash
+    RustFixture,
+}
+
+#[derive(ClapArgs, Debug)]
+#[command(group(
+    ArgGroup::new("source")
+        .required(true)
+        .args(&["tx_file", "tx_hash"])
+))]
+pub struct ExportArgs {
+    /// Transaction file (CBOR, hex or JSON)
+    #[arg(long, value_name = "FILE")]
+    tx_file: Option<PathBuf>,
+
+    /// Transaction hash, fetched from the provider
+    #[arg(long, value_name = "HASH")]
+    tx_hash: Option<String>,
+
+    /// Redeemer to export: its position, <purpose>:<index>, or all
+    #[arg(short, long, value_name = "REDEEMER", default_value = "all")]
+    redeemer: RedeemerSelector,
+
+    /// What to generate
+    #[arg(long, value_name = "FORMAT")]
+    format: ExportFormat,
+
+    /// Validator under test, as <module>.<validator> (e.g. dex/pool.pool), for aiken-test
+    #[arg(long, value_name = "VALIDATOR", required_if_eq("format", "aiken-test"))]
+    validator: Option<String>,
+
+    /// Type the datum is cast to before calling the validator [default: Data]
+    #[arg(long, value_name = "TYPE")]
+    datum_type: Option<String>,
+
+    /// Type the redeemer is cast to before calling the validator [default: Data]
+    #[arg(long, value_name = "TYPE")]
+    redeemer_type: Option<String>,
+
+    /// Generate tests expecting the validator to fail
+    #[arg(long)]
+    expect_failure: bool,
+
+    /// Where to write the fixture [default: stdout]
+    #[arg(long, value_name = "FILE")]
+    out: Option<PathBuf>,
+}
+
+/// Turn the script contexts of a transaction into a test fixture, e.g. to replay a mainnet
+/// incident as a regression test.
+pub async fn run(args: &Args, export: &ExportArgs, resolver: &dyn UtxoResolver) -> Result<()> {
+    let source = match (&export.tx_file, &export.tx_hash) {
+        (Some(path), _) => Source::File(path.clone()),
+        (None, Some(hash)) => Source::Hash(hash.clone()),
+        (None, None) => bail!("Either --tx-file or --tx-hash is required"),
+    };
+
+    let protocol_version = resolve_protocol_version(args)?;
+    let contexts =
+        batch::process(args, resolver, &source, protocol_version, export.redeemer).await?;
+
+    let contents = match export.format {
+        ExportFormat::AikenTest => aiken::test_module(
+            &aiken::TestTarget {
+                validator: export.validator.as_deref().unwrap_or_default(),
+                datum_type: export.datum_type.as_deref(),
+                redeemer_type: export.redeemer_type.as_deref(),
+                expect_failure: export.expect_failure,
+            },
+            &source.name(),
+            &contexts,
+        )?,
+        ExportFormat::RustFixture => rust_fixture(&source.name(), &contexts),
+    };
+
+    match &export.out {
+        Some(path) => {
+            std::fs::write(path, contents)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("{} -> {}", source.name(), path.display());
+        }
+        None => print!("{}", contents),
+    }
+
+    Ok(())
+}
+
+/// A Rust module with the CBOR of each script context and its Blake2b-256 hash, plus a test
+/// checking that each still hashes the same, survives a CBOR round trip as PlutusData and has
+/// the fields of a script context. Golden tests of the context builder compare what they build
+/// against these.
+fn rust_fixture(transaction: &str, contexts: &[RedeemerContext]) -> String {
+    let mut fixtures = Vec::new();
+
+    for context in contexts {
+        let bytes = to_cbor(&context.plutus_data);
+        let name = format!(
+            "{}_{}_{}",
+            test_name(transaction),
+            context.purpose.to_lowercase(),
+            context.index
+        );
+        let constant = name.to_uppercase();
+        // TxInfo and purpose, then the redeemer between them since PlutusV3.
+        let fields = match context.version {
+            PlutusVersion::PlutusV3 => 3,
+            _ => 2,
+        };
+
+        fixtures.push(format!(
+            "/// Redeemer {} ({} #{}), {:?}.\n\
+             pub const {constant}: &str =\n    \"{}\";\n\
+             pub const {constant}_HASH: &str =\n    \"{}\";\n\n\
+             #[test]\n\
+             fn {name}() {{\n    \
+                 let bytes = hex::decode({constant}).unwrap();\n    \
+                 assert_eq!(hex::encode(Hasher::<256>::hash(&bytes)), {constant}_HASH);\n    \
+                 let data: PlutusData = cbor::decode(&bytes).expect(\"the fixture decodes as PlutusData\");\n    \
+                 assert_eq!(to_cbor(&data), bytes, \"the fixture survives a CBOR round trip\");\n    \
+                 let PlutusData::Constr(context) = &data else {{\n        \
+                     panic!(\"a script context is a constructor\");\n    \
+                 }};\n    \
+                 assert_eq!(context.fields.len(), {fields}, \"the fields of a {:?} script context\");\n\
+             }}",
+            context.redeemer,
+            context.purpose,
+            context.index,
+            context.version,
+            hex::encode(&bytes),
+            Hasher::<256>::hash(&bytes),
+            context.version,
+        ));
+    }
+
+    format!(
+        "//! Script contexts generated by nawi from transaction {}.\n\nuse amaru_kernel::{{Hasher, PlutusData, cbor, to_cbor}};\n\n{}\n",
+        transaction,
+        fixtures.join("\n\n")
+    )
+}
+
+/// A test name prefix from a transaction hash or file name: a lowercase identifier, short enough
+/// to read.
+pub fn test_name(transaction: &str) -> String {
+    let name: String = transaction
+        .chars()
+        .take(16)
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!("tx_{}", name)
+}
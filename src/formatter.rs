@@ -1,29 +1,171 @@
 use amaru_kernel::{
-    Address, AssetName, BigInt, Certificate, ComputeHash, DRep, Network, PlutusData, ScriptPurpose,
-    ShelleyDelegationPart, ShelleyPaymentPart, StakeAddress, StakeCredential, StakePayload,
-    TransactionInput,
+    AddrAttrProperty, Address, Anchor, AssetName, BigInt, Certificate, ComputeHash, DRep,
+    GovAction, GovActionId, Hash, Network, Nullable, PlutusData, ProposalProcedure,
+    ProtocolParamUpdate, Relay, ScriptPurpose, ShelleyDelegationPart, ShelleyPaymentPart,
+    StakeAddress, StakeCredential, StakePayload, TransactionInput, Voter, cbor,
 };
 use amaru_plutus::script_context::{
     CurrencySymbol, DatumOption, Mint, Redeemers, Script, ScriptContextV1, ScriptContextV3,
     TimeRange, TransactionOutput, TxInfoV1, TxInfoV3, Value, Withdrawals, v1, v3,
 };
-use chrono::DateTime;
-use std::borrow::Cow;
+use anyhow::anyhow;
+use bech32::{Bech32, Hrp};
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, Utc};
+use clap::ValueEnum;
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+    sync::OnceLock,
+};
+
+use crate::token_registry;
 
 pub trait ReadableFormatter {
     fn format_readable(&self) -> String;
 }
 
+/// How the bounds of the validity range are displayed.
+#[derive(ValueEnum, Default, Clone, Copy, Debug, PartialEq)]
+#[value(rename_all = "kebab-case")]
+pub enum TimeFormat {
+    /// ISO-8601 date and time, in --timezone
+    #[default]
+    Iso,
+    /// POSIX time in milliseconds, as scripts compare against
+    Posix,
+    /// Slot numbers, as the transaction states its validity interval
+    Slot,
+    /// All three
+    All,
+}
+
+/// The timezone ISO-8601 times are rendered in.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Timezone {
+    #[default]
+    Utc,
+    Local,
+    Offset(FixedOffset),
+}
+
+impl FromStr for Timezone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utc" | "z" => Ok(Self::Utc),
+            "local" => Ok(Self::Local),
+            _ => s.parse().map(Self::Offset).map_err(|_| {
+                anyhow!(
+                    "Invalid timezone: {s}. Valid options: utc, local, or an offset such as +02:00"
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Rendering {
+    deterministic: bool,
+    time_format: TimeFormat,
+    timezone: Timezone,
+    hints: bool,
+}
+
+static RENDERING: OnceLock<Rendering> = OnceLock::new();
+
+thread_local! {
+    /// Slots of the validity interval of the transaction being formatted.
+    static VALIDITY_SLOTS: Cell<(Option<u64>, Option<u64>)> = const { Cell::new((None, None)) };
+    /// The TxInfo sections to print (all of them when empty) and those to leave out.
+    static SECTIONS: RefCell<(Vec<Section>, Vec<Section>)> =
+        const { RefCell::new((Vec::new(), Vec::new())) };
+}
+
+/// Set how the pretty output is rendered. Deterministic output is byte-comparable across
+/// machines: times are always in UTC, and assets sorted by policy and name. Hints annotate Plutus
+/// data with what its nodes look like.
+pub fn init(deterministic: bool, time_format: TimeFormat, timezone: Timezone, hints: bool) {
+    let _ = RENDERING.set(Rendering {
+        deterministic,
+        time_format,
+        timezone: if deterministic {
+            Timezone::Utc
+        } else {
+            timezone
+        },
+        hints,
+    });
+}
+
+fn rendering() -> &'static Rendering {
+    RENDERING.get_or_init(Rendering::default)
+}
+
+fn deterministic() -> bool {
+    rendering().deterministic
+}
+
+/// Format with the slots of the transaction's validity interval at hand, which the script
+/// context only holds as POSIX times.
+pub fn with_validity_slots<T>(slots: (Option<u64>, Option<u64>), format: impl FnOnce() -> T) -> T {
+    VALIDITY_SLOTS.with(|cell| cell.set(slots));
+    let formatted = format();
+    VALIDITY_SLOTS.with(|cell| cell.set((None, None)));
+    formatted
+}
+
+/// Format the TxInfo with only the sections `only` lists (all of them when empty) and `skip`
+/// doesn't. The rest of the context is formatted as is.
+pub fn with_sections<T>(only: &[Section], skip: &[Section], format: impl FnOnce() -> T) -> T {
+    SECTIONS.with(|cell| *cell.borrow_mut() = (only.to_vec(), skip.to_vec()));
+    let formatted = format();
+    SECTIONS.with(|cell| *cell.borrow_mut() = (Vec::new(), Vec::new()));
+    formatted
+}
+
+fn shown(section: Section) -> bool {
+    SECTIONS.with(|cell| {
+        let (only, skip) = &*cell.borrow();
+        (only.is_empty() || only.contains(&section)) && !skip.contains(&section)
+    })
+}
+
+/// A section of the pretty TxInfo, as selected by --only and --skip.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[value(rename_all = "kebab-case")]
+pub enum Section {
+    Id,
+    Inputs,
+    ReferenceInputs,
+    Outputs,
+    Fee,
+    Mint,
+    Certificates,
+    Withdrawals,
+    ValidityRange,
+    Signatories,
+    Redeemers,
+    Votes,
+    Proposals,
+    Treasury,
+}
+
 impl ReadableFormatter for ScriptContextV3<'_> {
     fn format_readable(&self) -> String {
         let separator = "=".repeat(80);
         format!(
-            "\n{}\nScript Context (Plutus V3)\n{}\n\nTransaction Info:\n{}\nRedeemer:\n  Purpose: {:?}\n  Index: {}\n\nScript Info:\n{}\n{}\n",
+            "\n{}\nScript Context (Plutus V3)\n{}\n\nTransaction Info:\n{}\nRedeemer:\n  Purpose: {:?}\n  Index: {}\n  Data: {}\n  Ex Units: {} steps, {} mem\n\nScript Info:\n{}\n{}\n",
             separator,
             separator,
             self.tx_info.format_readable(),
             self.redeemer.tag,
             self.redeemer.index,
+            self.redeemer.data.format_readable(),
+            self.redeemer.ex_units.steps,
+            self.redeemer.ex_units.mem,
             format_script_info(self),
             separator
         )
@@ -34,21 +176,25 @@ impl ReadableFormatter for TxInfoV3<'_> {
     fn format_readable(&self) -> String {
         let mut output = String::new();
 
-        output.push_str(&format!("  Transaction ID: {}\n", hex::encode(&self.id)));
+        if shown(Section::Id) {
+            output.push_str(&format!("  Transaction ID: {}\n", hex::encode(&self.id)));
+        }
 
-        output.push_str(&format!("\n  Inputs: {} input(s)\n", self.inputs.len()));
-        for (i, output_ref) in self.inputs.iter().enumerate() {
-            output.push_str(&format!(
-                "    [{}] {}\n",
-                i,
-                output_ref.input.format_readable()
-            ));
-            for line in output_ref.output.format_readable().lines() {
-                output.push_str(&format!("        {}\n", line));
+        if shown(Section::Inputs) {
+            output.push_str(&format!("\n  Inputs: {} input(s)\n", self.inputs.len()));
+            for (i, output_ref) in self.inputs.iter().enumerate() {
+                output.push_str(&format!(
+                    "    [{}] {}\n",
+                    i,
+                    output_ref.input.format_readable()
+                ));
+                for line in output_ref.output.format_readable().lines() {
+                    output.push_str(&format!("        {}\n", line));
+                }
             }
         }
 
-        if !self.reference_inputs.is_empty() {
+        if shown(Section::ReferenceInputs) && !self.reference_inputs.is_empty() {
             output.push_str(&format!(
                 "\n  Reference Inputs: {} input(s)\n",
                 self.reference_inputs.len()
@@ -65,64 +211,131 @@ impl ReadableFormatter for TxInfoV3<'_> {
             }
         }
 
-        output.push_str(&format!("\n  Outputs: {} output(s)\n", self.outputs.len()));
-        for (i, tx_output) in self.outputs.iter().enumerate() {
-            output.push_str(&format!("    [{}]\n", i));
-            for line in tx_output.format_readable().lines() {
-                output.push_str(&format!("        {}\n", line));
+        if shown(Section::Outputs) {
+            output.push_str(&format!("\n  Outputs: {} output(s)\n", self.outputs.len()));
+            for (i, tx_output) in self.outputs.iter().enumerate() {
+                output.push_str(&format!("    [{}]\n", i));
+                for line in tx_output.format_readable().lines() {
+                    output.push_str(&format!("        {}\n", line));
+                }
             }
         }
 
-        output.push_str(&format!("\n  Fee: {} lovelace\n", self.fee));
+        if shown(Section::Fee) {
+            output.push_str(&format!("\n  Fee: {} lovelace\n", self.fee));
+        }
 
-        output.push_str("\n  Minted Assets:\n");
-        for line in self.mint.format_readable().lines() {
-            output.push_str(&format!("    {}\n", line));
+        if shown(Section::Mint) {
+            output.push_str("\n  Minted Assets:\n");
+            for line in self.mint.format_readable().lines() {
+                output.push_str(&format!("    {}\n", line));
+            }
         }
 
-        output.push_str(&format!(
-            "\n  Certificates: {} certificate(s)\n",
-            self.certificates.len()
-        ));
-        for (i, cert) in self.certificates.iter().enumerate() {
-            output.push_str(&format!("    [{}] ", i));
-            for (j, line) in cert.format_readable().lines().enumerate() {
-                if j == 0 {
-                    output.push_str(&format!("{}\n", line));
-                } else {
-                    output.push_str(&format!("        {}\n", line));
+        if shown(Section::Certificates) {
+            output.push_str(&format!(
+                "\n  Certificates: {} certificate(s)\n",
+                self.certificates.len()
+            ));
+            for (i, cert) in self.certificates.iter().enumerate() {
+                output.push_str(&format!("    [{}] ", i));
+                for (j, line) in cert.format_readable().lines().enumerate() {
+                    if j == 0 {
+                        output.push_str(&format!("{}\n", line));
+                    } else {
+                        output.push_str(&format!("        {}\n", line));
+                    }
                 }
             }
         }
 
-        output.push_str(&format!(
-            "\n  Withdrawals: {} withdrawal(s)\n",
-            self.withdrawals.0.len()
-        ));
-        for line in self.withdrawals.format_readable().lines() {
-            output.push_str(&format!("    {}\n", line));
+        if shown(Section::Withdrawals) {
+            output.push_str(&format!(
+                "\n  Withdrawals: {} withdrawal(s)\n",
+                self.withdrawals.0.len()
+            ));
+            for line in self.withdrawals.format_readable().lines() {
+                output.push_str(&format!("    {}\n", line));
+            }
         }
 
-        output.push_str("\n  Validity Range:\n");
-        for line in self.valid_range.format_readable().lines() {
-            output.push_str(&format!("    {}\n", line));
+        if shown(Section::ValidityRange) {
+            output.push_str("\n  Validity Range:\n");
+            for line in self.valid_range.format_readable().lines() {
+                output.push_str(&format!("    {}\n", line));
+            }
         }
 
-        output.push_str(&format!(
-            "\n  Required Signers: {} signer(s)\n",
-            self.signatories.0.len()
-        ));
-        for (i, sig) in self.signatories.0.iter().enumerate() {
-            output.push_str(&format!("    [{}] {}\n", i, hex::encode(sig)));
+        if shown(Section::Signatories) {
+            output.push_str(&format!(
+                "\n  Required Signers: {} signer(s)\n",
+                self.signatories.0.len()
+            ));
+            for (i, sig) in self.signatories.0.iter().enumerate() {
+                output.push_str(&format!("    [{}] {}\n", i, hex::encode(sig)));
+            }
         }
 
-        output.push_str(&format!(
-            "\n  Redeemers: {} redeemer(s)\n",
-            self.redeemers.0.len()
-        ));
-        if !self.redeemers.0.is_empty() {
-            for line in self.redeemers.format_readable().lines() {
-                output.push_str(&format!("    {}\n", line));
+        if shown(Section::Redeemers) {
+            output.push_str(&format!(
+                "\n  Redeemers: {} redeemer(s)\n",
+                self.redeemers.0.len()
+            ));
+            if !self.redeemers.0.is_empty() {
+                for line in self.redeemers.format_readable().lines() {
+                    output.push_str(&format!("    {}\n", line));
+                }
+            }
+        }
+
+        if shown(Section::Votes) && !self.votes.0.is_empty() {
+            output.push_str(&format!("\n  Votes: {} voter(s)\n", self.votes.0.len()));
+            for (i, (voter, actions)) in self.votes.0.iter().enumerate() {
+                output.push_str(&format!("    [{}] {}\n", i, voter.format_readable()));
+                for (action_id, procedure) in actions.iter() {
+                    output.push_str(&format!(
+                        "        Action: {}\n",
+                        action_id.format_readable()
+                    ));
+                    output.push_str(&format!("          Vote: {:?}\n", procedure.vote));
+                    for line in format!("Anchor: {}", procedure.anchor.format_readable()).lines() {
+                        output.push_str(&format!("          {}\n", line));
+                    }
+                }
+            }
+        }
+
+        if shown(Section::Proposals) && !self.proposal_procedures.is_empty() {
+            output.push_str(&format!(
+                "\n  Proposals: {} proposal(s)\n",
+                self.proposal_procedures.len()
+            ));
+            for (i, proposal) in self.proposal_procedures.iter().enumerate() {
+                for (j, line) in proposal.format_readable().lines().enumerate() {
+                    if j == 0 {
+                        output.push_str(&format!("    [{}] {}\n", i, line));
+                    } else {
+                        output.push_str(&format!("        {}\n", line));
+                    }
+                }
+            }
+        }
+
+        if shown(Section::Treasury) && self.current_treasury_amount.is_some()
+            || self.treasury_donation.is_some()
+        {
+            output.push_str("\n  Treasury:\n");
+            if let Some(amount) = &self.current_treasury_amount {
+                output.push_str(&format!(
+                    "    Current Amount: {} lovelace\n",
+                    u64::from(*amount)
+                ));
+            }
+            if let Some(donation) = &self.treasury_donation {
+                output.push_str(&format!(
+                    "    Donation: {} lovelace\n",
+                    u64::from(*donation)
+                ));
             }
         }
 
@@ -148,78 +361,98 @@ impl ReadableFormatter for TxInfoV1<'_> {
     fn format_readable(&self) -> String {
         let mut output = String::new();
 
-        output.push_str(&format!("  Transaction ID: {}\n", hex::encode(&self.id)));
+        if shown(Section::Id) {
+            output.push_str(&format!("  Transaction ID: {}\n", hex::encode(&self.id)));
+        }
 
-        output.push_str(&format!("\n  Inputs: {} input(s)\n", self.inputs.len()));
-        for (i, output_ref) in self.inputs.iter().enumerate() {
-            output.push_str(&format!(
-                "    [{}] {}\n",
-                i,
-                output_ref.input.format_readable()
-            ));
-            for line in output_ref.output.format_readable().lines() {
-                output.push_str(&format!("        {}\n", line));
+        if shown(Section::Inputs) {
+            output.push_str(&format!("\n  Inputs: {} input(s)\n", self.inputs.len()));
+            for (i, output_ref) in self.inputs.iter().enumerate() {
+                output.push_str(&format!(
+                    "    [{}] {}\n",
+                    i,
+                    output_ref.input.format_readable()
+                ));
+                for line in output_ref.output.format_readable().lines() {
+                    output.push_str(&format!("        {}\n", line));
+                }
             }
         }
 
-        output.push_str(&format!("\n  Outputs: {} output(s)\n", self.outputs.len()));
-        for (i, tx_output) in self.outputs.iter().enumerate() {
-            output.push_str(&format!("    [{}]\n", i));
-            for line in tx_output.format_readable().lines() {
-                output.push_str(&format!("        {}\n", line));
+        if shown(Section::Outputs) {
+            output.push_str(&format!("\n  Outputs: {} output(s)\n", self.outputs.len()));
+            for (i, tx_output) in self.outputs.iter().enumerate() {
+                output.push_str(&format!("    [{}]\n", i));
+                for line in tx_output.format_readable().lines() {
+                    output.push_str(&format!("        {}\n", line));
+                }
             }
         }
 
-        output.push_str(&format!("\n  Fee: {}\n", self.fee.format_readable()));
+        if shown(Section::Fee) {
+            output.push_str(&format!("\n  Fee: {}\n", self.fee.format_readable()));
+        }
 
-        output.push_str("\n  Minted Assets:\n");
-        for line in self.mint.format_readable().lines() {
-            output.push_str(&format!("    {}\n", line));
+        if shown(Section::Mint) {
+            output.push_str("\n  Minted Assets:\n");
+            for line in self.mint.format_readable().lines() {
+                output.push_str(&format!("    {}\n", line));
+            }
         }
 
-        output.push_str(&format!(
-            "\n  Certificates: {} certificate(s)\n",
-            self.certificates.len()
-        ));
-        for (i, cert) in self.certificates.iter().enumerate() {
-            output.push_str(&format!("    [{}] ", i));
-            for (j, line) in cert.format_readable().lines().enumerate() {
-                if j == 0 {
-                    output.push_str(&format!("{}\n", line));
-                } else {
-                    output.push_str(&format!("        {}\n", line));
+        if shown(Section::Certificates) {
+            output.push_str(&format!(
+                "\n  Certificates: {} certificate(s)\n",
+                self.certificates.len()
+            ));
+            for (i, cert) in self.certificates.iter().enumerate() {
+                output.push_str(&format!("    [{}] ", i));
+                for (j, line) in cert.format_readable().lines().enumerate() {
+                    if j == 0 {
+                        output.push_str(&format!("{}\n", line));
+                    } else {
+                        output.push_str(&format!("        {}\n", line));
+                    }
                 }
             }
         }
 
-        output.push_str(&format!(
-            "\n  Withdrawals: {} withdrawal(s)\n",
-            self.withdrawals.0.len()
-        ));
-        for line in self.withdrawals.format_readable().lines() {
-            output.push_str(&format!("    {}\n", line));
+        if shown(Section::Withdrawals) {
+            output.push_str(&format!(
+                "\n  Withdrawals: {} withdrawal(s)\n",
+                self.withdrawals.0.len()
+            ));
+            for line in self.withdrawals.format_readable().lines() {
+                output.push_str(&format!("    {}\n", line));
+            }
         }
 
-        output.push_str("\n  Validity Range:\n");
-        for line in self.valid_range.format_readable().lines() {
-            output.push_str(&format!("    {}\n", line));
+        if shown(Section::ValidityRange) {
+            output.push_str("\n  Validity Range:\n");
+            for line in self.valid_range.format_readable().lines() {
+                output.push_str(&format!("    {}\n", line));
+            }
         }
 
-        output.push_str(&format!(
-            "\n  Required Signers: {} signer(s)\n",
-            self.signatories.0.len()
-        ));
-        for (i, sig) in self.signatories.0.iter().enumerate() {
-            output.push_str(&format!("    [{}] {}\n", i, hex::encode(sig)));
+        if shown(Section::Signatories) {
+            output.push_str(&format!(
+                "\n  Required Signers: {} signer(s)\n",
+                self.signatories.0.len()
+            ));
+            for (i, sig) in self.signatories.0.iter().enumerate() {
+                output.push_str(&format!("    [{}] {}\n", i, hex::encode(sig)));
+            }
         }
 
-        output.push_str(&format!(
-            "\n  Redeemers: {} redeemer(s)\n",
-            self.redeemers.0.len()
-        ));
-        if !self.redeemers.0.is_empty() {
-            for line in self.redeemers.format_readable().lines() {
-                output.push_str(&format!("    {}\n", line));
+        if shown(Section::Redeemers) {
+            output.push_str(&format!(
+                "\n  Redeemers: {} redeemer(s)\n",
+                self.redeemers.0.len()
+            ));
+            if !self.redeemers.0.is_empty() {
+                for line in self.redeemers.format_readable().lines() {
+                    output.push_str(&format!("    {}\n", line));
+                }
             }
         }
 
@@ -240,7 +473,7 @@ impl ReadableFormatter for TransactionInput {
 impl ReadableFormatter for TransactionOutput<'_> {
     fn format_readable(&self) -> String {
         format!(
-            "Address: {}\nValue:\n{}\nDatum: {}\nScript: {}",
+            "Address: {}\nValue:\n{}\nDatum: {}\nReference Script: {}",
             self.address.as_ref().format_readable(),
             indent_lines(&self.value.format_readable(), 2),
             self.datum.format_readable(),
@@ -257,22 +490,29 @@ impl ReadableFormatter for Value<'_> {
             result.push_str(&format!("ADA: {} lovelace\n", ada));
         }
 
-        let native_assets: Vec<_> = self
+        let mut native_assets: Vec<_> = self
             .0
             .iter()
             .filter(|(cs, _)| !matches!(cs, CurrencySymbol::Ada))
             .collect();
+        if deterministic() {
+            native_assets.sort_by_key(|(policy, _)| match policy {
+                CurrencySymbol::Native(hash) => hex::encode(hash),
+                CurrencySymbol::Ada => String::new(),
+            });
+        }
 
         if !native_assets.is_empty() {
             result.push_str(&format!("Assets: {} policies\n", native_assets.len()));
             for (policy, asset_map) in native_assets {
                 if let CurrencySymbol::Native(hash) = policy {
                     result.push_str(&format!("  Policy: {}\n", hex::encode(hash)));
-                    for (asset_name, amount) in asset_map.iter() {
+                    for (asset_name, asset, amount) in sorted_assets(asset_map.iter()) {
                         result.push_str(&format!(
-                            "    {}: {}\n",
-                            asset_name.format_readable(),
-                            amount
+                            "    {}: {}{}\n",
+                            asset_name,
+                            amount,
+                            registry_amount(hash, &asset.to_vec(), i128::from(*amount))
                         ));
                     }
                 }
@@ -292,30 +532,38 @@ impl ReadableFormatter for Mint<'_> {
         let mut result = String::new();
         result.push_str(&format!("Policies: {}\n", self.0.len()));
 
-        for (policy_hash, asset_map) in &self.0 {
+        let mut policies: Vec<_> = self.0.iter().collect();
+        if deterministic() {
+            policies.sort_by_key(|(policy_hash, _)| hex::encode(policy_hash));
+        }
+
+        for (policy_hash, asset_map) in policies {
             result.push_str(&format!("  Policy: {}\n", hex::encode(policy_hash)));
 
-            let minting: Vec<_> = asset_map.iter().filter(|(_, amt)| **amt > 0).collect();
-            let burning: Vec<_> = asset_map.iter().filter(|(_, amt)| **amt < 0).collect();
+            let assets = sorted_assets(asset_map.iter());
+            let minting: Vec<_> = assets.iter().filter(|(_, _, amt)| **amt > 0).collect();
+            let burning: Vec<_> = assets.iter().filter(|(_, _, amt)| **amt < 0).collect();
 
             if !minting.is_empty() {
                 result.push_str("    Minting:\n");
-                for (asset_name, amount) in minting {
+                for (asset_name, asset, amount) in minting {
                     result.push_str(&format!(
-                        "      {}: +{}\n",
-                        asset_name.format_readable(),
-                        amount
+                        "      {}: +{}{}\n",
+                        asset_name,
+                        amount,
+                        registry_amount(policy_hash, &asset.to_vec(), i128::from(**amount))
                     ));
                 }
             }
 
             if !burning.is_empty() {
                 result.push_str("    Burning:\n");
-                for (asset_name, amount) in burning {
+                for (asset_name, asset, amount) in burning {
                     result.push_str(&format!(
-                        "      {}: {}\n",
-                        asset_name.format_readable(),
-                        amount
+                        "      {}: {}{}\n",
+                        asset_name,
+                        amount,
+                        registry_amount(policy_hash, &asset.to_vec(), i128::from(**amount))
                     ));
                 }
             }
@@ -325,10 +573,50 @@ impl ReadableFormatter for Mint<'_> {
     }
 }
 
+/// Shelley and stake addresses lead with their bech32 form, to copy into explorers, followed by
+/// the credentials they hold. The prefix (`addr`/`addr_test`, `stake`/`stake_test`) follows the
+/// network of the address itself. Byron addresses lead with their base58 form, followed by what
+/// their payload holds: the hash of their root, their type, the network magic of testnet ones,
+/// and the encrypted derivation path of legacy wallets.
 impl ReadableFormatter for Address {
     fn format_readable(&self) -> String {
-        match self {
-            Address::Byron(_) => "Byron(...)".to_string(),
+        let breakdown = match self {
+            Address::Byron(addr) => {
+                let base58 = addr.to_base58();
+                let Ok(payload) = addr.decode() else {
+                    return format!("Byron({})", base58);
+                };
+
+                let mut network = "mainnet".to_string();
+                let mut attributes = Vec::new();
+                for attribute in payload.attributes.iter() {
+                    match attribute {
+                        AddrAttrProperty::NetworkTag(tag) => {
+                            network = match cbor::decode::<u32>(tag.as_slice()) {
+                                Ok(magic) => format!("testnet (magic {})", magic),
+                                Err(_) => format!("tag {}", hex::encode(tag.as_slice())),
+                            }
+                        }
+                        AddrAttrProperty::AddrDerivationPath(path) => attributes.push(format!(
+                            "derivation path: {} (encrypted)",
+                            hex::encode(path.as_slice())
+                        )),
+                        AddrAttrProperty::Unparsed(key, value) => attributes.push(format!(
+                            "attribute {}: {}",
+                            key,
+                            hex::encode(value.as_slice())
+                        )),
+                    }
+                }
+
+                let mut details = vec![
+                    format!("root: {}", hex::encode(payload.root)),
+                    format!("type: {:?}", payload.addrtype),
+                    format!("network: {}", network),
+                ];
+                details.extend(attributes);
+                return format!("{} Byron {{ {} }}", base58, details.join(", "));
+            }
             Address::Shelley(addr) => {
                 let payment = match addr.payment() {
                     ShelleyPaymentPart::Key(hash) => format!("Key({})", hex::encode(hash)),
@@ -349,20 +637,24 @@ impl ReadableFormatter for Address {
                 };
                 format!("Stake {{ {} }}", payload)
             }
-        }
+        };
+
+        with_bech32(self.to_bech32().ok(), breakdown)
     }
 }
 
 impl ReadableFormatter for TimeRange {
     fn format_readable(&self) -> String {
+        let (lower_slot, upper_slot) = VALIDITY_SLOTS.with(Cell::get);
+
         let lower = match &self.lower_bound {
             None => "∞".to_string(),
-            Some(ms) => format_time_ms_local(ms.clone().into()),
+            Some(ms) => format_bound(ms.clone().into(), lower_slot),
         };
 
         let upper = match &self.upper_bound {
             None => "∞".to_string(),
-            Some(ms) => format_time_ms_local(ms.clone().into()),
+            Some(ms) => format_bound(ms.clone().into(), upper_slot),
         };
 
         format!("Lower: {}\nUpper: {}", lower, upper)
@@ -384,9 +676,27 @@ impl ReadableFormatter for Option<Script<'_>> {
         match self {
             None => "None".to_string(),
             Some(Script::Native(script)) => format!("Native({})", script.compute_hash()),
-            Some(Script::PlutusV1(script)) => format!("PlutusV1({})", script.compute_hash()),
-            Some(Script::PlutusV2(script)) => format!("PlutusV2({})", script.compute_hash()),
-            Some(Script::PlutusV3(script)) => format!("PlutusV3({})", script.compute_hash()),
+            Some(Script::PlutusV1(script)) => {
+                format!(
+                    "PlutusV1({}, {} bytes)",
+                    script.compute_hash(),
+                    script.0.len()
+                )
+            }
+            Some(Script::PlutusV2(script)) => {
+                format!(
+                    "PlutusV2({}, {} bytes)",
+                    script.compute_hash(),
+                    script.0.len()
+                )
+            }
+            Some(Script::PlutusV3(script)) => {
+                format!(
+                    "PlutusV3({}, {} bytes)",
+                    script.compute_hash(),
+                    script.0.len()
+                )
+            }
         }
     }
 }
@@ -471,17 +781,68 @@ impl<'a> ReadableFormatter for v1::ScriptPurpose<'a> {
     }
 }
 
+/// An asset name as text when it reads as such, always followed by its hex, with its CIP-67 label
+/// (e.g. `(222)` for user NFTs, `(100)` for reference NFTs) decoded: `(222) SpaceBud [000de140…]`.
+/// Names with surrounding whitespace are quoted rather than trimmed.
 impl ReadableFormatter for AssetName {
     fn format_readable(&self) -> String {
         if self.is_empty() {
             return "<empty>".to_string();
         }
 
-        String::from_utf8(self.to_vec())
+        let bytes = self.to_vec();
+        let (label, name) = match cip67_label(&bytes) {
+            Some(label) => (Some(label), &bytes[4..]),
+            None => (None, &bytes[..]),
+        };
+
+        let text = std::str::from_utf8(name)
             .ok()
-            .map(|s| s.trim().to_string())
-            .unwrap_or_else(|| hex::encode(self.to_vec()))
+            .filter(|text| !text.is_empty() && !text.chars().any(char::is_control))
+            .map(|text| {
+                if text.trim() == text {
+                    text.to_string()
+                } else {
+                    format!("{:?}", text)
+                }
+            });
+
+        match (label, text) {
+            (Some(label), Some(text)) => format!("({}) {} [{}]", label, text, hex::encode(&bytes)),
+            (Some(label), None) => format!("({}) [{}]", label, hex::encode(&bytes)),
+            (None, Some(text)) => format!("{} [{}]", text, hex::encode(&bytes)),
+            (None, None) => hex::encode(&bytes),
+        }
+    }
+}
+
+/// The CIP-67 label an asset name starts with: 4 bytes holding, between two zero nibbles, the
+/// label on 16 bits and its CRC-8 checksum.
+pub fn cip67_label(bytes: &[u8]) -> Option<u16> {
+    let [b0, b1, b2, b3, ..] = *bytes else {
+        return None;
+    };
+    if b0 >> 4 != 0 || b3 & 0x0f != 0 {
+        return None;
     }
+
+    let label = (u16::from(b0 & 0x0f) << 12) | (u16::from(b1) << 4) | u16::from(b2 >> 4);
+    let checksum = ((b2 & 0x0f) << 4) | (b3 >> 4);
+
+    (crc8(&label.to_be_bytes()) == checksum).then_some(label)
+}
+
+/// CRC-8 with polynomial 0x07, as CIP-67 checksums labels.
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
 }
 
 impl<'a> ReadableFormatter for Cow<'a, AssetName> {
@@ -494,10 +855,28 @@ fn format_script_info(ctx: &v3::ScriptContext) -> String {
     match ctx.redeemer.tag {
         ScriptPurpose::Spend => {
             if let Some(input_idx) = ctx.tx_info.inputs.get(ctx.redeemer.index as usize) {
-                format!(
+                let mut result = format!(
                     "  Type: Spending\n  Input: {}\n",
                     input_idx.input.format_readable()
-                )
+                );
+                // The datum handed to the script: the output's inline datum, the preimage of its
+                // hash, or the one given with --datum.
+                match &ctx.datum {
+                    Some(datum) => {
+                        let source = match &input_idx.output.datum {
+                            DatumOption::Hash(hash) => format!("for hash {}", hex::encode(hash)),
+                            DatumOption::Inline(_) => "inline".to_string(),
+                            DatumOption::None => "given with --datum".to_string(),
+                        };
+                        result.push_str(&format!(
+                            "  Datum ({}):\n{}\n",
+                            source,
+                            indent_lines(&datum.format_readable(), 4)
+                        ));
+                    }
+                    None => result.push_str("  Datum: (none)\n"),
+                }
+                result
             } else {
                 format!(
                     "  Type: Spending\n  Input: Invalid index {}\n",
@@ -514,7 +893,40 @@ fn format_script_info(ctx: &v3::ScriptContext) -> String {
             "  Type: Withdrawal\n  Withdrawal Index: {}\n",
             ctx.redeemer.index
         ),
-        _ => "  Type: Unknown\n".to_string(),
+        ScriptPurpose::Vote => match ctx.tx_info.votes.0.iter().nth(ctx.redeemer.index as usize) {
+            Some((voter, actions)) => format!(
+                "  Type: Voting\n  Voter: {}\n  Votes: {} vote(s)\n",
+                voter.format_readable(),
+                actions.len()
+            ),
+            None => format!(
+                "  Type: Voting\n  Voter: Invalid index {}\n",
+                ctx.redeemer.index
+            ),
+        },
+        ScriptPurpose::Propose => {
+            match ctx
+                .tx_info
+                .proposal_procedures
+                .get(ctx.redeemer.index as usize)
+            {
+                Some(proposal) => format!(
+                    "  Type: Proposing\n  Proposal Index: {}\n  Action: {}\n  Deposit: {} lovelace\n",
+                    ctx.redeemer.index,
+                    proposal
+                        .gov_action
+                        .format_readable()
+                        .lines()
+                        .next()
+                        .unwrap_or_default(),
+                    proposal.deposit
+                ),
+                None => format!(
+                    "  Type: Proposing\n  Proposal Index: Invalid index {}\n",
+                    ctx.redeemer.index
+                ),
+            }
+        }
     }
 }
 
@@ -531,29 +943,61 @@ impl ReadableFormatter for amaru_kernel::Certificate {
                 format!(
                     "StakeDelegation\n  Credential: {}\n  Pool: {}",
                     cred.format_readable(),
-                    hex::encode(pool)
+                    pool_id(pool)
                 )
             }
             Certificate::PoolRegistration {
                 operator,
                 vrf_keyhash,
-                pledge: _,
-                cost: _,
-                margin: _,
-                reward_account: _,
-                pool_owners: _,
-                relays: _,
-                pool_metadata: _,
+                pledge,
+                cost,
+                margin,
+                reward_account,
+                pool_owners,
+                relays,
+                pool_metadata,
             } => {
                 let mut result = String::from("PoolRegistration\n");
-                result.push_str(&format!("  Operator: {}\n", hex::encode(operator)));
-                result.push_str(&format!("  VRF Keyhash: {}", hex::encode(vrf_keyhash)));
-                result.to_string()
+                result.push_str(&format!("  Operator: {}\n", pool_id(operator)));
+                result.push_str(&format!("  VRF Keyhash: {}\n", hex::encode(vrf_keyhash)));
+                result.push_str(&format!("  Pledge: {} lovelace\n", pledge));
+                result.push_str(&format!("  Cost: {} lovelace\n", cost));
+                result.push_str(&format!(
+                    "  Margin: {}/{} ({:.2}%)\n",
+                    margin.numerator,
+                    margin.denominator,
+                    margin.numerator as f64 * 100.0 / margin.denominator as f64
+                ));
+                result.push_str(&format!(
+                    "  Reward Account: {}\n",
+                    reward_account_readable(reward_account)
+                ));
+
+                result.push_str(&format!("  Owners: {} owner(s)\n", pool_owners.len()));
+                for (i, owner) in pool_owners.iter().enumerate() {
+                    result.push_str(&format!("    [{}] {}\n", i, hex::encode(owner)));
+                }
+
+                result.push_str(&format!("  Relays: {} relay(s)\n", relays.len()));
+                for (i, relay) in relays.iter().enumerate() {
+                    result.push_str(&format!("    [{}] {}\n", i, format_relay(relay)));
+                }
+
+                match pool_metadata {
+                    Nullable::Some(metadata) => result.push_str(&format!(
+                        "  Metadata: {}\n    Hash: {}",
+                        metadata.url,
+                        hex::encode(metadata.hash)
+                    )),
+                    Nullable::Null | Nullable::Undefined => result.push_str("  Metadata: (none)"),
+                }
+
+                result
             }
             Certificate::PoolRetirement(pool, epoch) => {
                 format!(
                     "PoolRetirement\n  Pool: {}\n  Epoch: {}",
-                    hex::encode(pool),
+                    pool_id(pool),
                     epoch
                 )
             }
@@ -582,7 +1026,7 @@ impl ReadableFormatter for amaru_kernel::Certificate {
                 format!(
                     "StakeVoteDeleg\n  Credential: {}\n  Pool: {}\n  DRep: {}",
                     cred.format_readable(),
-                    hex::encode(pool),
+                    pool_id(pool),
                     drep.format_readable()
                 )
             }
@@ -590,7 +1034,7 @@ impl ReadableFormatter for amaru_kernel::Certificate {
                 format!(
                     "StakeRegDeleg\n  Credential: {}\n  Pool: {}\n  Deposit: {} lovelace",
                     cred.format_readable(),
-                    hex::encode(pool),
+                    pool_id(pool),
                     coin
                 )
             }
@@ -606,7 +1050,7 @@ impl ReadableFormatter for amaru_kernel::Certificate {
                 format!(
                     "StakeVoteRegDeleg\n  Credential: {}\n  Pool: {}\n  DRep: {}\n  Deposit: {} lovelace",
                     cred.format_readable(),
-                    hex::encode(pool),
+                    pool_id(pool),
                     drep.format_readable(),
                     coin
                 )
@@ -614,29 +1058,38 @@ impl ReadableFormatter for amaru_kernel::Certificate {
             Certificate::AuthCommitteeHot(cold, hot) => {
                 format!(
                     "AuthCommitteeHot\n  Cold: {}\n  Hot: {}",
-                    cold.format_readable(),
-                    hot.format_readable()
+                    governance_id(Governance::CommitteeCold, cold),
+                    governance_id(Governance::CommitteeHot, hot)
                 )
             }
-            Certificate::ResignCommitteeCold(cold, _) => {
-                format!("ResignCommitteeCold\n  Cold: {}", cold.format_readable(),)
+            Certificate::ResignCommitteeCold(cold, anchor) => {
+                format!(
+                    "ResignCommitteeCold\n  Cold: {}\n  Anchor: {}",
+                    governance_id(Governance::CommitteeCold, cold),
+                    anchor.format_readable()
+                )
             }
-            Certificate::RegDRepCert(cred, coin, _) => {
+            Certificate::RegDRepCert(cred, coin, anchor) => {
                 format!(
-                    "RegDRepCert\n  Credential: {}\n  Deposit: {} lovelace",
-                    cred.format_readable(),
+                    "RegDRepCert\n  Credential: {}\n  Deposit: {} lovelace\n  Anchor: {}",
+                    governance_id(Governance::DRep, cred),
                     coin,
+                    anchor.format_readable()
                 )
             }
             Certificate::UnRegDRepCert(cred, coin) => {
                 format!(
                     "UnRegDRepCert\n  Credential: {}\n  Refund: {} lovelace",
-                    cred.format_readable(),
+                    governance_id(Governance::DRep, cred),
                     coin
                 )
             }
-            Certificate::UpdateDRepCert(cred, _) => {
-                format!("UpdateDRepCert\n  Credential: {}", cred.format_readable())
+            Certificate::UpdateDRepCert(cred, anchor) => {
+                format!(
+                    "UpdateDRepCert\n  Credential: {}\n  Anchor: {}",
+                    governance_id(Governance::DRep, cred),
+                    anchor.format_readable()
+                )
             }
         }
     }
@@ -655,11 +1108,238 @@ impl ReadableFormatter for StakeCredential {
     }
 }
 
+impl ReadableFormatter for Voter {
+    fn format_readable(&self) -> String {
+        match self {
+            Voter::ConstitutionalCommitteeKey(hash) => format!(
+                "Committee {}",
+                governance_id(
+                    Governance::CommitteeHot,
+                    &StakeCredential::AddrKeyhash(*hash)
+                )
+            ),
+            Voter::ConstitutionalCommitteeScript(hash) => format!(
+                "Committee {}",
+                governance_id(
+                    Governance::CommitteeHot,
+                    &StakeCredential::ScriptHash(*hash)
+                )
+            ),
+            Voter::DRepKey(hash) => format!(
+                "DRep {}",
+                governance_id(Governance::DRep, &StakeCredential::AddrKeyhash(*hash))
+            ),
+            Voter::DRepScript(hash) => format!(
+                "DRep {}",
+                governance_id(Governance::DRep, &StakeCredential::ScriptHash(*hash))
+            ),
+            Voter::StakePoolKey(hash) => format!("Stake Pool {}", pool_id(hash)),
+        }
+    }
+}
+
+impl ReadableFormatter for ProposalProcedure {
+    fn format_readable(&self) -> String {
+        format!(
+            "{}\n  Deposit: {} lovelace\n  Return Account: {}\n  Anchor: {}",
+            self.gov_action.format_readable(),
+            self.deposit,
+            reward_account_readable(&self.reward_account),
+            self.anchor.format_readable()
+        )
+    }
+}
+
+/// The kind of a governance action, then what it would enact.
+impl ReadableFormatter for GovAction {
+    fn format_readable(&self) -> String {
+        let previous = |id: &Nullable<GovActionId>| match id {
+            Nullable::Some(id) => id.format_readable(),
+            Nullable::Null | Nullable::Undefined => "(none)".to_string(),
+        };
+        let guardrail = |hash: &Nullable<Hash<28>>| match hash {
+            Nullable::Some(hash) => hex::encode(hash),
+            Nullable::Null | Nullable::Undefined => "(none)".to_string(),
+        };
+
+        match self {
+            GovAction::ParameterChange(previous_action, update, guardrail_script) => {
+                let mut result = format!(
+                    "ParameterChange\n  Previous Action: {}\n  Guardrail Script: {}\n  Parameters:",
+                    previous(previous_action),
+                    guardrail(guardrail_script)
+                );
+                for change in parameter_changes(update) {
+                    result.push_str(&format!("\n    {}", change));
+                }
+                result
+            }
+            GovAction::HardForkInitiation(previous_action, (major, minor)) => format!(
+                "HardForkInitiation\n  Previous Action: {}\n  Protocol Version: {}.{}",
+                previous(previous_action),
+                major,
+                minor
+            ),
+            GovAction::TreasuryWithdrawals(withdrawals, guardrail_script) => {
+                let mut result = format!(
+                    "TreasuryWithdrawals\n  Guardrail Script: {}\n  Withdrawals: {} withdrawal(s)",
+                    guardrail(guardrail_script),
+                    withdrawals.len()
+                );
+                for (account, amount) in withdrawals.iter() {
+                    result.push_str(&format!(
+                        "\n    {}: {} lovelace",
+                        reward_account_readable(account),
+                        amount
+                    ));
+                }
+                result
+            }
+            GovAction::NoConfidence(previous_action) => format!(
+                "NoConfidence\n  Previous Action: {}",
+                previous(previous_action)
+            ),
+            GovAction::UpdateCommittee(previous_action, removed, added, threshold) => {
+                let mut result = format!(
+                    "UpdateCommittee\n  Previous Action: {}\n  Removed: {} member(s)",
+                    previous(previous_action),
+                    removed.len()
+                );
+                for member in removed.iter() {
+                    result.push_str(&format!(
+                        "\n    {}",
+                        governance_id(Governance::CommitteeCold, member)
+                    ));
+                }
+                result.push_str(&format!("\n  Added: {} member(s)", added.len()));
+                for (member, epoch) in added.iter() {
+                    result.push_str(&format!(
+                        "\n    {} until epoch {}",
+                        governance_id(Governance::CommitteeCold, member),
+                        epoch
+                    ));
+                }
+                result.push_str(&format!(
+                    "\n  Threshold: {}/{}",
+                    threshold.numerator, threshold.denominator
+                ));
+                result
+            }
+            GovAction::NewConstitution(previous_action, constitution) => format!(
+                "NewConstitution\n  Previous Action: {}\n  Constitution: {}\n  Guardrail Script: {}",
+                previous(previous_action),
+                constitution.anchor.format_readable(),
+                guardrail(&constitution.guardrail_script)
+            ),
+            GovAction::Information => "Information".to_string(),
+        }
+    }
+}
+
+/// The protocol parameters a ParameterChange action sets, by their name in the ledger.
+fn parameter_changes(update: &ProtocolParamUpdate) -> Vec<String> {
+    macro_rules! changes {
+        ($($field:ident),* $(,)?) => {
+            [$(update
+                .$field
+                .as_ref()
+                .map(|value| format!("{}: {:?}", stringify!($field), value))),*]
+        };
+    }
+
+    changes!(
+        minfee_a,
+        minfee_b,
+        max_block_body_size,
+        max_transaction_size,
+        max_block_header_size,
+        key_deposit,
+        pool_deposit,
+        maximum_epoch,
+        desired_number_of_stake_pools,
+        pool_pledge_influence,
+        expansion_rate,
+        treasury_growth_rate,
+        min_pool_cost,
+        ada_per_utxo_byte,
+        cost_models_for_script_languages,
+        execution_costs,
+        max_tx_ex_units,
+        max_block_ex_units,
+        max_value_size,
+        collateral_percentage,
+        max_collateral_inputs,
+        pool_voting_thresholds,
+        drep_voting_thresholds,
+        min_committee_size,
+        committee_term_limit,
+        governance_action_validity_period,
+        governance_action_deposit,
+        drep_deposit,
+        drep_inactivity_period,
+        minfee_refscript_cost_per_byte,
+    )
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// A reward account (of a pool, or returning a proposal deposit) as a stake address.
+fn reward_account_readable(bytes: &[u8]) -> String {
+    match Address::from_bytes(bytes) {
+        Ok(address) => address.format_readable(),
+        Err(_) => hex::encode(bytes),
+    }
+}
+
+/// A governance action by the transaction that proposed it and its position there.
+/// The CIP-129 identifier of a governance action, then its transaction hash and index.
+impl ReadableFormatter for GovActionId {
+    fn format_readable(&self) -> String {
+        let reference = format!("{}#{}", hex::encode(self.transaction_id), self.action_index);
+
+        // CIP-129 appends the index in as few big-endian bytes as it takes, at least one.
+        let index = self.action_index.to_be_bytes();
+        let first = index
+            .iter()
+            .position(|byte| *byte != 0)
+            .unwrap_or(index.len() - 1);
+        let mut bytes = self.transaction_id.to_vec();
+        bytes.extend_from_slice(&index[first..]);
+
+        match bech32("gov_action", &bytes) {
+            Some(id) => format!("{} ({})", id, reference),
+            None => reference,
+        }
+    }
+}
+
+/// The off-chain document of a governance action or credential: its URL, then the hash of its
+/// contents on the next line.
+impl ReadableFormatter for Anchor {
+    fn format_readable(&self) -> String {
+        format!("{}\n    Hash: {}", self.url, hex::encode(self.content_hash))
+    }
+}
+
+impl ReadableFormatter for Nullable<Anchor> {
+    fn format_readable(&self) -> String {
+        match self {
+            Nullable::Some(anchor) => anchor.format_readable(),
+            Nullable::Null | Nullable::Undefined => "(none)".to_string(),
+        }
+    }
+}
+
 impl ReadableFormatter for DRep {
     fn format_readable(&self) -> String {
         match self {
-            DRep::Key(hash) => format!("Key({})", hex::encode(hash)),
-            DRep::Script(hash) => format!("Script({})", hex::encode(hash)),
+            DRep::Key(hash) => {
+                governance_id(Governance::DRep, &StakeCredential::AddrKeyhash(*hash))
+            }
+            DRep::Script(hash) => {
+                governance_id(Governance::DRep, &StakeCredential::ScriptHash(*hash))
+            }
             DRep::Abstain => "Abstain".to_string(),
             DRep::NoConfidence => "NoConfidence".to_string(),
         }
@@ -701,7 +1381,93 @@ impl ReadableFormatter for StakeAddress {
             StakePayload::Script(hash) => format!("Script({})", hex::encode(hash)),
         };
 
-        format!("{} {{ {} }}", network, payload)
+        with_bech32(
+            self.to_bech32().ok(),
+            format!("{} {{ {} }}", network, payload),
+        )
+    }
+}
+
+/// A relay of a pool registration: its IP addresses or DNS name, and port.
+fn format_relay(relay: &Relay) -> String {
+    let port = |port: &Nullable<u32>| match port {
+        Nullable::Some(port) => format!(":{}", port),
+        Nullable::Null | Nullable::Undefined => String::new(),
+    };
+
+    match relay {
+        Relay::SingleHostAddr(relay_port, ipv4, ipv6) => {
+            let mut hosts = Vec::new();
+            if let Nullable::Some(ipv4) = ipv4 {
+                hosts.push(match <[u8; 4]>::try_from(ipv4.as_slice()) {
+                    Ok(octets) => format!("{}{}", Ipv4Addr::from(octets), port(relay_port)),
+                    Err(_) => hex::encode(ipv4.as_slice()),
+                });
+            }
+            if let Nullable::Some(ipv6) = ipv6 {
+                // The ledger serializes IPv6 addresses as four little-endian 32-bit words.
+                hosts.push(match <[u8; 16]>::try_from(ipv6.as_slice()) {
+                    Ok(mut octets) => {
+                        octets.chunks_mut(4).for_each(<[u8]>::reverse);
+                        format!("[{}]{}", Ipv6Addr::from(octets), port(relay_port))
+                    }
+                    Err(_) => hex::encode(ipv6.as_slice()),
+                });
+            }
+            format!("Address {}", hosts.join(", "))
+        }
+        Relay::SingleHostName(relay_port, name) => {
+            format!("Host {}{}", name, port(relay_port))
+        }
+        Relay::MultiHostName(name) => format!("DNS SRV {}", name),
+    }
+}
+
+/// A pool id in its bech32 form (`pool1…`), as operators know it, followed by its hex.
+fn pool_id(pool: impl AsRef<[u8]>) -> String {
+    match bech32("pool", pool.as_ref()) {
+        Some(id) => format!("{} ({})", id, hex::encode(pool)),
+        None => hex::encode(pool),
+    }
+}
+
+/// The governance roles CIP-129 gives identifiers to.
+#[derive(Clone, Copy)]
+enum Governance {
+    CommitteeHot,
+    CommitteeCold,
+    DRep,
+}
+
+/// A governance credential by its CIP-129 identifier (`drep1…`, `cc_hot1…`, `cc_cold1…`),
+/// followed by the credential. The identifier prefixes the hash with a header byte: the role in
+/// the high nibble, and 2 for a key hash or 3 for a script hash in the low one.
+fn governance_id(role: Governance, credential: &StakeCredential) -> String {
+    let (hrp, role) = match role {
+        Governance::CommitteeHot => ("cc_hot", 0x00),
+        Governance::CommitteeCold => ("cc_cold", 0x10),
+        Governance::DRep => ("drep", 0x20),
+    };
+    let (kind, hash) = match credential {
+        StakeCredential::AddrKeyhash(hash) => (0x02, hash),
+        StakeCredential::ScriptHash(hash) => (0x03, hash),
+    };
+
+    let mut bytes = vec![role | kind];
+    bytes.extend_from_slice(hash.as_ref());
+
+    with_bech32(bech32(hrp, &bytes), credential.format_readable())
+}
+
+fn bech32(hrp: &str, bytes: &[u8]) -> Option<String> {
+    let hrp = Hrp::parse(hrp).ok()?;
+    bech32::encode::<Bech32>(hrp, bytes).ok()
+}
+
+fn with_bech32(bech32: Option<String>, breakdown: String) -> String {
+    match bech32 {
+        Some(bech32) => format!("{} {}", bech32, breakdown),
+        None => breakdown,
     }
 }
 
@@ -711,7 +1477,21 @@ impl ReadableFormatter for PlutusData {
     }
 }
 
+/// Format a node of Plutus data, with a hint at the end of its first line when --hints is on and
+/// the node looks like something known.
 fn format_plutus_data(data: &PlutusData, indent: usize) -> String {
+    let formatted = format_plutus_node(data, indent);
+    let Some(hint) = rendering().hints.then(|| data_hint(data)).flatten() else {
+        return formatted;
+    };
+
+    match formatted.split_once('\n') {
+        Some((first, rest)) => format!("{}  # {}\n{}", first, hint, rest),
+        None => format!("{}  # {}", formatted, hint),
+    }
+}
+
+fn format_plutus_node(data: &PlutusData, indent: usize) -> String {
     let indent_str = "  ".repeat(indent);
     let next_indent_str = "  ".repeat(indent + 1);
 
@@ -795,26 +1575,274 @@ fn format_plutus_data(data: &PlutusData, indent: usize) -> String {
     }
 }
 
+/// POSIX times in milliseconds from the launch of Cardano to 2100, the range integers are taken
+/// for deadlines and timestamps in.
+const PLAUSIBLE_POSIX_MS: std::ops::RangeInclusive<i128> = 1_506_203_091_000..=4_102_444_800_000;
+
+/// What a node of Plutus data looks like, by its shape alone: an address, a credential, an asset
+/// class, a hash, an address in raw bytes, or a POSIX time.
+fn data_hint(data: &PlutusData) -> Option<String> {
+    match data {
+        PlutusData::Constr(constr) => match (constr.tag, constr.fields.as_slice()) {
+            (121, [first, second]) => {
+                address_hint(first, second).or_else(|| asset_class_hint(first, second))
+            }
+            (121 | 122, [_]) => credential_hint(data).map(|kind| format!("{} credential", kind)),
+            _ => None,
+        },
+        PlutusData::Array(array) => match array.as_slice() {
+            [policy, name] => asset_class_hint(policy, name),
+            _ => None,
+        },
+        PlutusData::BoundedBytes(bytes) => match bytes.len() {
+            28 => Some("28-byte hash: key, script or policy id".to_string()),
+            32 => Some("32-byte hash: transaction id or datum hash".to_string()),
+            29 | 57 => match Address::from_bytes(bytes) {
+                Ok(address @ Address::Shelley(_)) | Ok(address @ Address::Stake(_)) => {
+                    Some(address.format_readable())
+                }
+                _ => None,
+            },
+            _ => None,
+        },
+        PlutusData::BigInt(BigInt::Int(int)) => {
+            let int = i128::from(int.0);
+            PLAUSIBLE_POSIX_MS
+                .contains(&int)
+                .then(|| format!("POSIX time {}", format_time_ms(int as u64)))
+        }
+        PlutusData::BigInt(_) => None,
+    }
+}
+
+fn address_hint(payment: &PlutusData, staking: &PlutusData) -> Option<String> {
+    Some(format!(
+        "address, {} payment credential, {}",
+        credential_hint(payment)?,
+        staking_hint(staking)?
+    ))
+}
+
+/// `key` or `script`, when the node is a credential holding a 28-byte hash.
+fn credential_hint(data: &PlutusData) -> Option<&'static str> {
+    let PlutusData::Constr(constr) = data else {
+        return None;
+    };
+    let [PlutusData::BoundedBytes(hash)] = constr.fields.as_slice() else {
+        return None;
+    };
+    if hash.len() != 28 {
+        return None;
+    }
+
+    match constr.tag {
+        121 => Some("key"),
+        122 => Some("script"),
+        _ => None,
+    }
+}
+
+/// The staking part of an address: none, a credential, or a pointer.
+fn staking_hint(data: &PlutusData) -> Option<String> {
+    let PlutusData::Constr(constr) = data else {
+        return None;
+    };
+
+    match (constr.tag, constr.fields.as_slice()) {
+        (122, []) => Some("no stake credential".to_string()),
+        (121, [PlutusData::Constr(staking)]) => match (staking.tag, staking.fields.as_slice()) {
+            (121, [credential]) => {
+                credential_hint(credential).map(|kind| format!("{} stake credential", kind))
+            }
+            (122, [_, _, _]) => Some("stake pointer".to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A policy id (or the empty one of ada) and an asset name of at most 32 bytes.
+fn asset_class_hint(policy: &PlutusData, name: &PlutusData) -> Option<String> {
+    let (PlutusData::BoundedBytes(policy), PlutusData::BoundedBytes(name)) = (policy, name) else {
+        return None;
+    };
+
+    match (policy.len(), name.len()) {
+        (0, 0) => Some("asset class of ada".to_string()),
+        (28, 0..=32) => Some(format!(
+            "asset class {}.{}",
+            hex::encode(policy.to_vec()),
+            AssetName::from(name.to_vec()).format_readable()
+        )),
+        _ => None,
+    }
+}
+
+/// Whether a node fits on the line of its parent. Hinted ones get a line of their own, for their
+/// hint to end it.
 fn is_simple(data: &PlutusData) -> bool {
     match data {
-        PlutusData::BigInt(_) | PlutusData::BoundedBytes(_) => true,
+        PlutusData::BigInt(_) | PlutusData::BoundedBytes(_) => {
+            !rendering().hints || data_hint(data).is_none()
+        }
         PlutusData::Constr(constr) => constr.fields.is_empty(),
         PlutusData::Map(pairs) => pairs.is_empty(),
         PlutusData::Array(array) => array.is_empty(),
     }
 }
 
-fn format_time_ms_local(time_ms: u64) -> String {
-    match DateTime::from_timestamp_millis(time_ms as i64) {
-        Some(dt) => dt.format("%Y-%m-%d %H:%M:%S %Z").to_string(),
-        None => format!("Invalid timestamp: {} ms", time_ms),
+/// Asset names rendered, alongside the names themselves and their amounts, sorted by rendered
+/// name in deterministic mode.
+fn sorted_assets<'a, N: ReadableFormatter + 'a, A: 'a>(
+    assets: impl Iterator<Item = (&'a N, &'a A)>,
+) -> Vec<(String, &'a N, &'a A)> {
+    let mut assets: Vec<_> = assets
+        .map(|(name, amount)| (name.format_readable(), name, amount))
+        .collect();
+    if deterministic() {
+        assets.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    }
+    assets
+}
+
+/// The amount in the unit of the token registry given with --token-registry, e.g.
+/// ` (12.5 SUNDAE)`, or nothing for assets it doesn't know.
+fn registry_amount(policy: &Hash<28>, name: &[u8], amount: i128) -> String {
+    token_registry::format_amount(policy, name, amount)
+        .map(|amount| format!(" ({})", amount))
+        .unwrap_or_default()
+}
+
+/// A bound of the validity range in the chosen time format. Without its slot (e.g. for a
+/// context not built from a transaction), the POSIX time stands in for it.
+fn format_bound(time_ms: u64, slot: Option<u64>) -> String {
+    let posix = format!("{} ms", time_ms);
+    let slot = slot.map(|slot| format!("slot {}", slot));
+
+    match rendering().time_format {
+        TimeFormat::Iso => format_time_ms(time_ms),
+        TimeFormat::Posix => posix,
+        TimeFormat::Slot => slot.unwrap_or(posix),
+        TimeFormat::All => match slot {
+            Some(slot) => format!("{} ({}, {})", format_time_ms(time_ms), posix, slot),
+            None => format!("{} ({})", format_time_ms(time_ms), posix),
+        },
+    }
+}
+
+/// ISO-8601, down to the millisecond scripts see.
+fn format_time_ms(time_ms: u64) -> String {
+    let Some(dt) = DateTime::<Utc>::from_timestamp_millis(time_ms as i64) else {
+        return format!("Invalid timestamp: {} ms", time_ms);
+    };
+
+    match rendering().timezone {
+        Timezone::Utc => dt.to_rfc3339_opts(SecondsFormat::Millis, true),
+        Timezone::Local => dt
+            .with_timezone(&Local)
+            .to_rfc3339_opts(SecondsFormat::Millis, true),
+        Timezone::Offset(offset) => dt
+            .with_timezone(&offset)
+            .to_rfc3339_opts(SecondsFormat::Millis, true),
     }
 }
 
-fn indent_lines(text: &str, spaces: usize) -> String {
+pub fn indent_lines(text: &str, spaces: usize) -> String {
     let indent = " ".repeat(spaces);
     text.lines()
         .map(|line| format!("{}{}", indent, line))
         .collect::<Vec<_>>()
         .join("\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cip67_labels() {
+        let cases = [
+            ("000643b0", Some(100)),
+            ("000de140", Some(222)),
+            ("0014df10", Some(333)),
+            ("000643b04e4654", Some(100)),
+            ("000643b1", None),
+            ("000643a0", None),
+            ("100643b0", None),
+            ("000643", None),
+            ("", None),
+        ];
+        for (name, expected) in cases {
+            let bytes = hex::decode(name).unwrap();
+            assert_eq!(cip67_label(&bytes), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn crc8_checksums() {
+        let cases = [(100u16, 0x3b), (222, 0x14), (333, 0xf1), (444, 0x28)];
+        for (label, expected) in cases {
+            assert_eq!(crc8(&label.to_be_bytes()), expected, "{}", label);
+        }
+    }
+
+    #[test]
+    fn governance_ids() {
+        let hash = Hash::<28>::from_str("0655f3a1c76788d839212adc459b188b84e680f30ae944c593fa18ae")
+            .unwrap();
+        let key = StakeCredential::AddrKeyhash(hash);
+        let script = StakeCredential::ScriptHash(hash);
+        let cases = [
+            (
+                Governance::DRep,
+                &key,
+                "drep1ygr9tuapcanc3kpeyy4dc3vmrz9cfe5q7v9wj3x9j0ap3tswtre9j",
+            ),
+            (
+                Governance::DRep,
+                &script,
+                "drep1yvr9tuapcanc3kpeyy4dc3vmrz9cfe5q7v9wj3x9j0ap3tsweng94",
+            ),
+            (
+                Governance::CommitteeHot,
+                &key,
+                "cc_hot1qgr9tuapcanc3kpeyy4dc3vmrz9cfe5q7v9wj3x9j0ap3tsul3nkl",
+            ),
+            (
+                Governance::CommitteeCold,
+                &script,
+                "cc_cold1zvr9tuapcanc3kpeyy4dc3vmrz9cfe5q7v9wj3x9j0ap3ts2347v3",
+            ),
+        ];
+        for (role, credential, expected) in cases {
+            let id = governance_id(role, credential);
+            assert_eq!(id.split(' ').next(), Some(expected), "{}", id);
+        }
+    }
+
+    #[test]
+    fn gov_action_ids() {
+        let cases = [
+            (
+                17,
+                "gov_action1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqpzklpgpf",
+            ),
+            (
+                0,
+                "gov_action1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq2803ez",
+            ),
+            (
+                256,
+                "gov_action1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqzqqhppxxg",
+            ),
+        ];
+        for (action_index, expected) in cases {
+            let id = GovActionId {
+                transaction_id: Hash::new([0; 32]),
+                action_index,
+            }
+            .format_readable();
+            assert_eq!(id.split(' ').next(), Some(expected), "{}", id);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use reqwest::{Certificate, ClientBuilder, Proxy};
+use serde::Deserialize;
+
+/// Settings shared by every HTTP-based provider.
+///
+/// `HTTPS_PROXY`/`HTTP_PROXY` are honoured without any configuration; `proxy` only needs to be
+/// set to override them.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub proxy: Option<String>,
+    /// PEM bundle of additional root certificates, for networks that intercept TLS.
+    pub ca_bundle: Option<PathBuf>,
+}
+
+pub fn client_builder(config: &HttpConfig) -> Result<ClientBuilder> {
+    let mut builder = ClientBuilder::new();
+
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(Proxy::all(proxy).context(format!("Invalid proxy URL {}", proxy))?);
+    }
+
+    if let Some(path) = &config.ca_bundle {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("Failed to read CA bundle: {}", path.display()))?;
+        let certificates = Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid CA bundle: {}", path.display()))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
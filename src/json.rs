@@ -0,0 +1,286 @@
+use amaru_kernel::{Address, ComputeHash, PlutusData, StakeAddress, TransactionInput};
+use amaru_plutus::script_context::{
+    CurrencySymbol, DatumOption, Mint, Redeemers, Script, ScriptContextV1, ScriptContextV3,
+    TimeRange, TransactionOutput, TxInfoV1, TxInfoV3, Value, Withdrawals, v1, v3,
+};
+use serde_json::{Map, json};
+
+use crate::{formatter::ReadableFormatter, script_data};
+
+/// The machine-readable counterpart of `ReadableFormatter`, mirroring the pretty output field for
+/// field. Certificates and script purposes keep their readable form.
+pub trait JsonFormatter {
+    fn to_json(&self) -> serde_json::Value;
+}
+
+impl JsonFormatter for ScriptContextV3<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "version": "PlutusV3",
+            "txInfo": self.tx_info.to_json(),
+            "redeemer": {
+                "purpose": format!("{:?}", self.redeemer.tag),
+                "index": self.redeemer.index,
+            },
+        })
+    }
+}
+
+impl JsonFormatter for TxInfoV3<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "id": hex::encode(&self.id),
+            "inputs": self
+                .inputs
+                .iter()
+                .map(|output_ref| resolved_input(&output_ref.input, &output_ref.output))
+                .collect::<Vec<_>>(),
+            "referenceInputs": self
+                .reference_inputs
+                .iter()
+                .map(|output_ref| resolved_input(&output_ref.input, &output_ref.output))
+                .collect::<Vec<_>>(),
+            "outputs": self.outputs.iter().map(|output| output.to_json()).collect::<Vec<_>>(),
+            "fee": self.fee,
+            "mint": self.mint.to_json(),
+            "certificates": self
+                .certificates
+                .iter()
+                .map(|cert| cert.format_readable())
+                .collect::<Vec<_>>(),
+            "withdrawals": self.withdrawals.to_json(),
+            "validRange": self.valid_range.to_json(),
+            "signatories": self.signatories.0.iter().map(hex::encode).collect::<Vec<_>>(),
+            "redeemers": self.redeemers.to_json(),
+        })
+    }
+}
+
+impl JsonFormatter for ScriptContextV1<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "version": "PlutusV1",
+            "txInfo": self.tx_info.to_json(),
+            "purpose": self.purpose.format_readable(),
+        })
+    }
+}
+
+impl JsonFormatter for TxInfoV1<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "id": hex::encode(&self.id),
+            "inputs": self
+                .inputs
+                .iter()
+                .map(|output_ref| resolved_input(&output_ref.input, &output_ref.output))
+                .collect::<Vec<_>>(),
+            "outputs": self.outputs.iter().map(|output| output.to_json()).collect::<Vec<_>>(),
+            "fee": self.fee.to_json(),
+            "mint": self.mint.to_json(),
+            "certificates": self
+                .certificates
+                .iter()
+                .map(|cert| cert.format_readable())
+                .collect::<Vec<_>>(),
+            "withdrawals": self.withdrawals.to_json(),
+            "validRange": self.valid_range.to_json(),
+            "signatories": self.signatories.0.iter().map(hex::encode).collect::<Vec<_>>(),
+            "redeemers": self.redeemers.to_json(),
+        })
+    }
+}
+
+fn resolved_input(input: &TransactionInput, output: &TransactionOutput<'_>) -> serde_json::Value {
+    json!({
+        "outputReference": input.format_readable(),
+        "output": output.to_json(),
+    })
+}
+
+impl JsonFormatter for TransactionOutput<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "address": address(self.address.as_ref()),
+            "value": self.value.to_json(),
+            "datum": self.datum.to_json(),
+            "referenceScript": self.script.to_json(),
+        })
+    }
+}
+
+/// Bech32 for Shelley addresses, base58 for Byron ones.
+pub fn address(address: &Address) -> String {
+    match address {
+        Address::Byron(addr) => addr.to_base58(),
+        address => address
+            .to_bech32()
+            .unwrap_or_else(|_| address.format_readable()),
+    }
+}
+
+impl JsonFormatter for Value<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        let mut assets = Map::new();
+        for (policy, asset_map) in self.0.iter() {
+            if let CurrencySymbol::Native(hash) = policy {
+                let amounts: Map<_, _> = asset_map
+                    .iter()
+                    .map(|(asset_name, amount)| (hex::encode(asset_name.to_vec()), json!(amount)))
+                    .collect();
+                assets.insert(hex::encode(hash), amounts.into());
+            }
+        }
+
+        json!({
+            "lovelace": self.ada().unwrap_or_default(),
+            "assets": assets,
+        })
+    }
+}
+
+impl JsonFormatter for Mint<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        let policies: Map<_, _> = self
+            .0
+            .iter()
+            .map(|(policy_hash, asset_map)| {
+                let amounts: Map<_, _> = asset_map
+                    .iter()
+                    .map(|(asset_name, amount)| (hex::encode(asset_name.to_vec()), json!(amount)))
+                    .collect();
+                (hex::encode(policy_hash), amounts.into())
+            })
+            .collect();
+
+        policies.into()
+    }
+}
+
+impl JsonFormatter for TimeRange {
+    /// Bounds in POSIX milliseconds, `null` when unbounded.
+    fn to_json(&self) -> serde_json::Value {
+        let bound = |bound: &Option<_>| bound.clone().map(|ms| -> u64 { ms.into() });
+
+        json!({
+            "lower": bound(&self.lower_bound),
+            "upper": bound(&self.upper_bound),
+        })
+    }
+}
+
+impl JsonFormatter for DatumOption<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            DatumOption::None => serde_json::Value::Null,
+            DatumOption::Hash(hash) => json!({ "hash": hex::encode(hash) }),
+            DatumOption::Inline(data) => json!({ "inline": data.to_json() }),
+        }
+    }
+}
+
+impl JsonFormatter for Option<Script<'_>> {
+    fn to_json(&self) -> serde_json::Value {
+        let (language, hash, size) = match self {
+            None => return serde_json::Value::Null,
+            Some(Script::Native(script)) => ("Native", script.compute_hash(), None),
+            Some(Script::PlutusV1(script)) => {
+                ("PlutusV1", script.compute_hash(), Some(script.0.len()))
+            }
+            Some(Script::PlutusV2(script)) => {
+                ("PlutusV2", script.compute_hash(), Some(script.0.len()))
+            }
+            Some(Script::PlutusV3(script)) => {
+                ("PlutusV3", script.compute_hash(), Some(script.0.len()))
+            }
+        };
+
+        json!({
+            "language": language,
+            "hash": hash.to_string(),
+            "size": size,
+        })
+    }
+}
+
+impl<'a> JsonFormatter for Redeemers<'a, v3::ScriptPurpose<'a>> {
+    fn to_json(&self) -> serde_json::Value {
+        self.0
+            .iter()
+            .map(|(purpose, redeemer)| {
+                json!({
+                    "purpose": purpose.format_readable(),
+                    "index": redeemer.index,
+                    "data": redeemer.data.to_json(),
+                    "exUnits": { "mem": redeemer.ex_units.mem, "steps": redeemer.ex_units.steps },
+                })
+            })
+            .collect()
+    }
+}
+
+impl<'a> JsonFormatter for Redeemers<'a, v1::ScriptPurpose<'a>> {
+    fn to_json(&self) -> serde_json::Value {
+        self.0
+            .iter()
+            .map(|(purpose, redeemer)| {
+                json!({
+                    "purpose": purpose.format_readable(),
+                    "index": redeemer.index,
+                    "data": redeemer.data.to_json(),
+                    "exUnits": { "mem": redeemer.ex_units.mem, "steps": redeemer.ex_units.steps },
+                })
+            })
+            .collect()
+    }
+}
+
+impl JsonFormatter for Withdrawals {
+    fn to_json(&self) -> serde_json::Value {
+        self.0
+            .iter()
+            .map(|(stake_addr, amount)| {
+                let stake_addr = StakeAddress::from(stake_addr.clone());
+                json!({
+                    "stakeAddress": stake_addr
+                        .to_bech32()
+                        .unwrap_or_else(|_| stake_addr.format_readable()),
+                    "lovelace": amount,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Data in the detailed schema of cardano-cli, as `script_data` writes it: `constructor`/`fields`,
+/// `map`, `list`, `int` and `bytes`. Integers beyond the 64-bit range of JSON numbers are given
+/// as decimal strings.
+impl JsonFormatter for PlutusData {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            PlutusData::Constr(constr) => json!({
+                "constructor": script_data::constructor(constr.tag, constr.any_constructor),
+                "fields": constr.fields.iter().map(|field| field.to_json()).collect::<Vec<_>>(),
+            }),
+            PlutusData::Map(pairs) => json!({
+                "map": pairs
+                    .iter()
+                    .map(|(k, v)| json!({ "k": k.to_json(), "v": v.to_json() }))
+                    .collect::<Vec<_>>(),
+            }),
+            PlutusData::Array(array) => json!({
+                "list": array.iter().map(|element| element.to_json()).collect::<Vec<_>>(),
+            }),
+            PlutusData::BigInt(int) => {
+                let digits = script_data::integer(int);
+                let int: serde_json::Value = match (digits.parse::<i64>(), digits.parse::<u64>()) {
+                    (Ok(int), _) => int.into(),
+                    (_, Ok(int)) => int.into(),
+                    _ => digits.into(),
+                };
+                json!({ "int": int })
+            }
+            PlutusData::BoundedBytes(bytes) => json!({ "bytes": hex::encode(bytes.to_vec()) }),
+        }
+    }
+}
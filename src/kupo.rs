@@ -0,0 +1,243 @@
+use std::{collections::BTreeMap, num::NonZeroUsize};
+
+use amaru_kernel::{Hash, MemoizedTransactionOutput, PlutusData, TransactionInput, cbor};
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use serde::{Deserialize, de::DeserializeOwned};
+use tokio::sync::Semaphore;
+
+use crate::{
+    http::{HttpConfig, client_builder},
+    output::{OutputDatum, OutputParts, ReferenceScript, parse_address},
+    resolver::{ResolvedUtxos, UtxoResolver},
+    retry::{RetryConfig, retry},
+    stats::{ProviderStats, RequestStats},
+};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct KupoConfig {
+    pub url: String,
+}
+
+pub struct Kupo {
+    client: reqwest::Client,
+    url: String,
+    retry: RetryConfig,
+    max_concurrency: NonZeroUsize,
+    stats: RequestStats,
+}
+
+#[derive(Debug, Deserialize)]
+struct Match {
+    address: String,
+    value: MatchValue,
+    datum_hash: Option<String>,
+    datum_type: Option<DatumType>,
+    script_hash: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MatchValue {
+    coins: u64,
+    #[serde(default)]
+    assets: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DatumType {
+    Hash,
+    Inline,
+}
+
+#[derive(Debug, Deserialize)]
+struct DatumResponse {
+    datum: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScriptResponse {
+    language: String,
+    script: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Health {
+    most_recent_checkpoint: Option<u64>,
+}
+
+impl Kupo {
+    pub fn new(
+        config: &KupoConfig,
+        http: &HttpConfig,
+        retry: &RetryConfig,
+        max_concurrency: NonZeroUsize,
+    ) -> Result<Self> {
+        let client = client_builder(http)?
+            .build()
+            .context("Failed to create the Kupo client")?;
+
+        Ok(Self {
+            client,
+            url: config.url.trim_end_matches('/').to_string(),
+            retry: retry.clone(),
+            max_concurrency,
+            stats: RequestStats::default(),
+        })
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}/{}", self.url, path);
+
+        let request = || async {
+            self.client
+                .get(&url)
+                .header("Accept", "application/json")
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+        };
+
+        retry(&self.retry, &self.stats, is_transient, request)
+            .await
+            .context(format!("Failed to query Kupo at {}", url))?
+            .json()
+            .await
+            .context(format!("Invalid response from Kupo at {}", url))
+    }
+
+    async fn fetch_utxo(
+        &self,
+        input: &TransactionInput,
+    ) -> Result<Option<(TransactionInput, MemoizedTransactionOutput)>> {
+        let tx_hash = hex::encode(input.transaction_id);
+
+        // Kupo output reference patterns are written `{output_index}@{transaction_id}`.
+        let matches: Vec<Match> = self
+            .get(&format!("matches/{}@{}", input.index, tx_hash))
+            .await?;
+
+        let Some(found) = matches.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let output = self
+            .output_parts(found)
+            .await
+            .context(format!(
+                "Failed to resolve {}#{} from Kupo",
+                tx_hash, input.index
+            ))?
+            .into_memoized()?;
+
+        Ok(Some((input.clone(), output)))
+    }
+
+    async fn output_parts(&self, found: Match) -> Result<OutputParts> {
+        let mut assets: BTreeMap<Hash<28>, BTreeMap<Vec<u8>, u64>> = BTreeMap::new();
+        for (unit, quantity) in found.value.assets {
+            let (policy, name) = unit.split_once('.').unwrap_or((&unit, ""));
+            let policy: Hash<28> = policy
+                .parse()
+                .map_err(|_| anyhow!("Invalid policy id in asset {}", unit))?;
+            let name = hex::decode(name).context(format!("Invalid asset name in {}", unit))?;
+            assets.entry(policy).or_default().insert(name, quantity);
+        }
+
+        let datum = match (found.datum_type, found.datum_hash) {
+            (Some(DatumType::Inline), Some(hash)) => {
+                let response: Option<DatumResponse> = self.get(&format!("datums/{}", hash)).await?;
+                let datum =
+                    response.ok_or_else(|| anyhow!("Kupo has no datum for hash {}", hash))?;
+                OutputDatum::Inline(
+                    hex::decode(&datum.datum).context("Invalid datum hex from Kupo")?,
+                )
+            }
+            (_, Some(hash)) => OutputDatum::Hash(
+                hash.parse()
+                    .map_err(|_| anyhow!("Invalid datum hash {} from Kupo", hash))?,
+            ),
+            (_, None) => OutputDatum::None,
+        };
+
+        let script = match found.script_hash {
+            Some(hash) => {
+                let response: Option<ScriptResponse> =
+                    self.get(&format!("scripts/{}", hash)).await?;
+                let script =
+                    response.ok_or_else(|| anyhow!("Kupo has no script for hash {}", hash))?;
+                let bytes = hex::decode(&script.script).context("Invalid script hex from Kupo")?;
+                Some(match script.language.as_str() {
+                    "native" => ReferenceScript::Native(bytes),
+                    "plutus:v1" => ReferenceScript::PlutusV1(bytes),
+                    "plutus:v2" => ReferenceScript::PlutusV2(bytes),
+                    "plutus:v3" => ReferenceScript::PlutusV3(bytes),
+                    other => bail!("Unknown script language from Kupo: {}", other),
+                })
+            }
+            None => None,
+        };
+
+        Ok(OutputParts {
+            address: parse_address(&found.address)?,
+            lovelace: found.value.coins,
+            assets,
+            datum,
+            script,
+        })
+    }
+}
+
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_connect()
+        || error.is_timeout()
+        || error
+            .status()
+            .is_some_and(|status| status.is_server_error())
+}
+
+#[async_trait]
+impl UtxoResolver for Kupo {
+    async fn resolve(&self, inputs: &[TransactionInput]) -> Result<ResolvedUtxos> {
+        let semaphore = Semaphore::new(self.max_concurrency.get());
+        let futures = inputs.iter().map(|input| async {
+            let _permit = semaphore.acquire().await?;
+            self.fetch_utxo(input).await
+        });
+
+        let results = try_join_all(futures)
+            .await
+            .context("Failed to fetch UTxOs from Kupo")?;
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    async fn tip(&self) -> Result<u64> {
+        let health: Health = self.get("health").await?;
+
+        health
+            .most_recent_checkpoint
+            .ok_or(anyhow!("Kupo has not synchronized any block yet"))
+    }
+
+    async fn resolve_datum(&self, hash: &Hash<32>) -> Result<Option<PlutusData>> {
+        let response: Option<DatumResponse> =
+            self.get(&format!("datums/{}", hex::encode(hash))).await?;
+
+        response
+            .map(|datum| {
+                let bytes = hex::decode(&datum.datum).context("Invalid datum hex from Kupo")?;
+                cbor::decode(&bytes).context("Failed to decode datum from Kupo")
+            })
+            .transpose()
+    }
+
+    async fn stats(&self) -> Vec<ProviderStats> {
+        vec![self.stats.report(self.name(), None)]
+    }
+
+    fn name(&self) -> &'static str {
+        "Kupo"
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use amaru_kernel::{AuxiliaryData, MintedTransactionBody, MintedWitnessSet, cbor};
+use anyhow::{Context, Result, bail};
+
+use crate::transaction::{RawTransaction, encode_map, parse_witness_set, raw_entries};
+
+/// Body fields a transaction can't do without; a failure in one of them is fatal.
+const REQUIRED_BODY_FIELDS: [u64; 3] = [0, 1, 2];
+
+/// Decode a transaction part by part, dropping every field that fails to decode and reporting
+/// where it was. Returns a transaction that decodes, along with one message per dropped field.
+pub fn repair(bytes: &[u8]) -> Result<(Vec<u8>, Vec<String>)> {
+    let transaction = RawTransaction::parse(bytes)
+        .context("Failed to split the transaction into body, witness set and auxiliary data")?;
+    let mut problems = Vec::new();
+
+    let body_offset = transaction.body.as_ptr() as usize - bytes.as_ptr() as usize;
+    let mut body: BTreeMap<u64, &[u8]> =
+        raw_entries(&mut cbor::Decoder::new(transaction.body), transaction.body)
+            .context("Transaction body is not a CBOR map")?;
+    let offsets: BTreeMap<u64, usize> = body
+        .iter()
+        .map(|(key, value)| {
+            (
+                *key,
+                body_offset + value.as_ptr() as usize - transaction.body.as_ptr() as usize,
+            )
+        })
+        .collect();
+
+    while let Err(error) = cbor::decode::<MintedTransactionBody<'_>>(&encode_map(&body)) {
+        let Some(culprit) = find_culprit(&body, |candidate| {
+            cbor::decode::<MintedTransactionBody<'_>>(&encode_map(candidate)).is_ok()
+        }) else {
+            bail!(
+                "Failed to decode the transaction body, in a required field or several fields at once: {}",
+                error
+            );
+        };
+
+        if REQUIRED_BODY_FIELDS.contains(&culprit) {
+            bail!(
+                "Failed to decode transaction body field {} ({}) at byte offset {}: {}",
+                culprit,
+                body_field_name(culprit),
+                offsets[&culprit],
+                error
+            );
+        }
+
+        problems.push(format!(
+            "transaction body field {} ({}) at byte offset {}: {}. Dropped it, so the transaction id no longer matches the original",
+            culprit,
+            body_field_name(culprit),
+            offsets[&culprit],
+            error
+        ));
+        body.remove(&culprit);
+    }
+
+    let mut witness_set = transaction.witness_set.clone();
+    while let Err(error) = cbor::decode::<MintedWitnessSet<'_>>(&encode_map(&witness_set)) {
+        let culprit = find_culprit(&witness_set, |candidate| {
+            cbor::decode::<MintedWitnessSet<'_>>(&encode_map(candidate)).is_ok()
+        })
+        .or_else(|| witness_set.keys().next().copied())
+        .context("Failed to decode an empty witness set")?;
+
+        problems.push(format!(
+            "witness set field {} ({}): {}. Dropped it",
+            culprit,
+            witness_field_name(culprit),
+            error
+        ));
+        witness_set.remove(&culprit);
+    }
+
+    let mut auxiliary_data = transaction.auxiliary_data;
+    if let Some(Err(error)) = auxiliary_data.map(cbor::decode::<AuxiliaryData>) {
+        let offset =
+            auxiliary_data.map_or(0, |data| data.as_ptr() as usize - bytes.as_ptr() as usize);
+        problems.push(format!(
+            "auxiliary data at byte offset {}: {}. Dropped it",
+            offset, error
+        ));
+        auxiliary_data = None;
+    }
+
+    let body = encode_map(&body);
+    let repaired = RawTransaction {
+        body: &body,
+        witness_set,
+        is_valid: transaction.is_valid,
+        auxiliary_data,
+    }
+    .to_bytes();
+
+    Ok((repaired, problems))
+}
+
+/// The field whose removal alone makes the map decode.
+fn find_culprit<V: Clone>(
+    fields: &BTreeMap<u64, V>,
+    decodes: impl Fn(&BTreeMap<u64, V>) -> bool,
+) -> Option<u64> {
+    fields.keys().copied().find(|key| {
+        let mut candidate = fields.clone();
+        candidate.remove(key);
+        decodes(&candidate)
+    })
+}
+
+fn body_field_name(key: u64) -> &'static str {
+    match key {
+        0 => "inputs",
+        1 => "outputs",
+        2 => "fee",
+        3 => "validity interval end",
+        4 => "certificates",
+        5 => "withdrawals",
+        6 => "protocol parameter update",
+        7 => "auxiliary data hash",
+        8 => "validity interval start",
+        9 => "mint",
+        11 => "script data hash",
+        13 => "collateral",
+        14 => "required signers",
+        15 => "network id",
+        16 => "collateral return",
+        17 => "total collateral",
+        18 => "reference inputs",
+        19 => "voting procedures",
+        20 => "proposal procedures",
+        21 => "treasury value",
+        22 => "donation",
+        _ => "unknown",
+    }
+}
+
+fn witness_field_name(key: u64) -> &'static str {
+    match key {
+        0 => "vkey witnesses",
+        1 => "native scripts",
+        2 => "bootstrap witnesses",
+        3 => "PlutusV1 scripts",
+        4 => "plutus data",
+        5 => "redeemers",
+        6 => "PlutusV2 scripts",
+        7 => "PlutusV3 scripts",
+        _ => "unknown",
+    }
+}
@@ -0,0 +1,692 @@
+use std::{borrow::Cow, collections::BTreeSet, fmt, path::PathBuf};
+
+use amaru_kernel::{
+    Address, Hash, Hasher, MintedTx, Redeemer, ScriptPurpose, ShelleyPaymentPart, TransactionInput,
+};
+use anyhow::{Result, anyhow, bail};
+use clap::{ArgGroup, Args as ClapArgs};
+
+use crate::{
+    Args, ContextInputs, PlutusVersion, balance,
+    batch::Source,
+    build_script_context, canonical,
+    collateral::{self, Problem},
+    collateral_inputs, decode_transaction,
+    deposits::Deposits,
+    evaluate, format_budget, format_fee,
+    formatter::ReadableFormatter,
+    get_redeemers, min_ada, missing_scripts, network, plutus_version,
+    protocol::{self, CollateralParameters},
+    repair_if_lenient, resolve_deposit_parameters, resolve_protocol_parameters,
+    resolve_protocol_version, resolve_slot, resolve_transaction,
+    resolver::{ResolvedUtxos, UtxoResolver},
+    script_hash, size,
+    transaction::{self, WitnessFields},
+};
+
+#[derive(ClapArgs, Debug)]
+#[command(group(
+    ArgGroup::new("source")
+        .required(true)
+        .args(&["tx_file", "tx_hash"])
+))]
+pub struct LintArgs {
+    /// Transaction file (CBOR, hex or JSON)
+    #[arg(long, value_name = "FILE")]
+    tx_file: Option<PathBuf>,
+
+    /// Transaction hash, fetched from the provider
+    #[arg(long, value_name = "HASH")]
+    tx_hash: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Severity {
+    /// The ledger rejects the transaction.
+    Error,
+    /// Likely a mistake, or a rejection nawi can't confirm.
+    Warning,
+    /// A check that couldn't run.
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+        }
+    }
+}
+
+/// Stable codes of the findings, grouped by check. New checks get new codes; codes are never
+/// reused.
+const REDEEMER_OUT_OF_RANGE: &str = "L001";
+const REDEEMER_WITHOUT_SCRIPT: &str = "L002";
+const MISSING_REDEEMER: &str = "L003";
+const NO_COLLATERAL: &str = "L010";
+const UNRESOLVED_COLLATERAL: &str = "L011";
+const SCRIPT_LOCKED_COLLATERAL: &str = "L012";
+const COLLATERAL_ASSETS_NOT_RETURNED: &str = "L013";
+const INSUFFICIENT_COLLATERAL: &str = "L014";
+const INCORRECT_TOTAL_COLLATERAL: &str = "L015";
+const TOO_MANY_COLLATERAL_INPUTS: &str = "L016";
+const MISSING_SCRIPT: &str = "L020";
+const MISSING_SIGNATURE: &str = "L021";
+const NATIVE_SCRIPT_FAILS: &str = "L022";
+const WRONG_NETWORK: &str = "L030";
+const FEE_TOO_SMALL: &str = "L040";
+const TRANSACTION_TOO_LARGE: &str = "L041";
+const EXECUTION_UNITS_TOO_HIGH: &str = "L042";
+const OUTPUT_TOO_SMALL: &str = "L043";
+const VALUE_NOT_CONSERVED: &str = "L050";
+const UNORDERED_CONTEXT: &str = "L060";
+const CONTEXT_NOT_BUILT: &str = "L061";
+const CHECK_SKIPPED: &str = "L090";
+
+struct Finding {
+    severity: Severity,
+    code: &'static str,
+    message: String,
+}
+
+#[derive(Default)]
+struct Findings(Vec<Finding>);
+
+impl Findings {
+    fn push(&mut self, severity: Severity, code: &'static str, message: impl Into<String>) {
+        self.0.push(Finding {
+            severity,
+            code,
+            message: message.into(),
+        });
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.0
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    }
+}
+
+/// Run every static check over a transaction before it is submitted, printing one line per
+/// finding with its severity and code. Fails when any finding is an error.
+pub async fn run(args: &Args, lint: &LintArgs, resolver: &dyn UtxoResolver) -> Result<()> {
+    let source = match (&lint.tx_file, &lint.tx_hash) {
+        (Some(path), _) => Source::File(path.clone()),
+        (None, Some(hash)) => Source::Hash(hash.clone()),
+        (None, None) => bail!("Either --tx-file or --tx-hash is required"),
+    };
+
+    let tx_bytes = transaction::complete(source.load(resolver).await?, &WitnessFields::new())?;
+    let tx_bytes = repair_if_lenient(args, tx_bytes)?;
+    let transaction = decode_transaction(&tx_bytes)?;
+    let (utxos, datums, placeholders) = resolve_transaction(args, resolver, &transaction).await?;
+    let redeemers = redeemers(&transaction);
+
+    let mut findings = Findings::default();
+    check_redeemer_pointers(&transaction, &utxos, &redeemers, &mut findings);
+    check_witnesses(&transaction, &utxos, &mut findings);
+    for mismatch in network::mismatches(*args.network, &transaction, &utxos) {
+        let severity = if mismatch.rejected {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        findings.push(severity, WRONG_NETWORK, mismatch.description);
+    }
+
+    let parameters = resolve_protocol_parameters(args, resolver).await;
+    let collateral_parameters = match &parameters {
+        Ok(parameters) => CollateralParameters::from_json(parameters),
+        Err(e) => Err(anyhow!("{:#}", e)),
+    };
+    if let Err(e) = &collateral_parameters
+        && !redeemers.is_empty()
+    {
+        findings.push(
+            Severity::Info,
+            CHECK_SKIPPED,
+            format!("collateral amount not checked: {:#}", e),
+        );
+    }
+    check_collateral(
+        &transaction,
+        &utxos,
+        collateral_parameters.as_ref().ok(),
+        &mut findings,
+    );
+
+    match parameters {
+        Ok(parameters) => {
+            match format_fee(args, resolver, &transaction, &utxos, &tx_bytes).await {
+                Ok((_, shortfall)) if shortfall > 0 => findings.push(
+                    Severity::Error,
+                    FEE_TOO_SMALL,
+                    format!(
+                        "the declared fee is {} lovelace below the minimum (FeeTooSmallUTxO), see --fee",
+                        shortfall
+                    ),
+                ),
+                Ok(_) => {}
+                Err(e) => skipped(&mut findings, "fee", e),
+            }
+
+            match protocol::coins_per_utxo_byte(&parameters) {
+                Ok(coins_per_byte) => {
+                    for output in min_ada::outputs(&transaction, coins_per_byte) {
+                        if output.shortfall() > 0 {
+                            findings.push(
+                                Severity::Error,
+                                OUTPUT_TOO_SMALL,
+                                format!(
+                                    "{} holds {} lovelace less than the {} its size requires (BabbageOutputTooSmallUTxO), see --min-ada",
+                                    output.name,
+                                    output.shortfall(),
+                                    output.minimum
+                                ),
+                            );
+                        }
+                    }
+                }
+                Err(e) => skipped(&mut findings, "minimum lovelace", e),
+            }
+
+            let max_size = protocol::max_transaction_size(&parameters);
+            match size::describe(&tx_bytes, max_size) {
+                Ok((_, excess)) if excess > 0 => findings.push(
+                    Severity::Error,
+                    TRANSACTION_TOO_LARGE,
+                    format!(
+                        "the transaction is {} bytes over the maximum size (MaxTxSizeUTxO), see --size",
+                        excess
+                    ),
+                ),
+                Ok(_) => {}
+                Err(e) => skipped(&mut findings, "size", e),
+            }
+
+            if !redeemers.is_empty() {
+                match format_budget(args, resolver, &transaction).await {
+                    Ok((_, overruns)) if overruns > 0 => findings.push(
+                        Severity::Error,
+                        EXECUTION_UNITS_TOO_HIGH,
+                        format!(
+                            "the redeemers exceed {} execution unit limit(s) (ExUnitsTooBigUTxO), see --budget",
+                            overruns
+                        ),
+                    ),
+                    Ok(_) => {}
+                    Err(e) => skipped(&mut findings, "execution units", e),
+                }
+            }
+        }
+        Err(e) => skipped(
+            &mut findings,
+            "fee, size, execution units and minimum lovelace",
+            e,
+        ),
+    }
+
+    let deposit_parameters = resolve_deposit_parameters(args, resolver, &transaction).await;
+    let deposits = Deposits::of(&transaction, &deposit_parameters);
+    check_balance(
+        &transaction,
+        &utxos,
+        &placeholders,
+        &deposits,
+        &mut findings,
+    );
+
+    if !redeemers.is_empty() {
+        match resolve_slot(args, resolver, &transaction).await {
+            Ok(slot) => {
+                let inputs = ContextInputs {
+                    transaction: &transaction,
+                    utxos: &utxos,
+                    datums: &datums,
+                    datum: None,
+                    network: args.network,
+                    slot,
+                    protocol_version: resolve_protocol_version(args)?,
+                };
+                check_contexts(args, &inputs, &redeemers, &mut findings);
+            }
+            Err(e) => skipped(&mut findings, "script contexts", e),
+        }
+    }
+
+    for finding in &findings.0 {
+        println!(
+            "{}[{}]: {}",
+            finding.severity, finding.code, finding.message
+        );
+    }
+    let errors = findings.count(Severity::Error);
+    println!(
+        "{}{} error(s), {} warning(s)",
+        if findings.0.is_empty() { "" } else { "\n" },
+        errors,
+        findings.count(Severity::Warning)
+    );
+
+    if errors > 0 {
+        bail!("{} lint error(s)", errors);
+    }
+    Ok(())
+}
+
+/// The redeemers of the transaction, none when it runs no Plutus script.
+fn redeemers(transaction: &MintedTx<'_>) -> Vec<Redeemer> {
+    get_redeemers(transaction)
+        .unwrap_or_default()
+        .into_iter()
+        .map(Cow::into_owned)
+        .collect()
+}
+
+fn skipped(findings: &mut Findings, check: &str, error: anyhow::Error) {
+    findings.push(
+        Severity::Info,
+        CHECK_SKIPPED,
+        format!("{} not checked: {:#}", check, error),
+    );
+}
+
+const PURPOSES: [ScriptPurpose; 6] = [
+    ScriptPurpose::Spend,
+    ScriptPurpose::Mint,
+    ScriptPurpose::Cert,
+    ScriptPurpose::Reward,
+    ScriptPurpose::Vote,
+    ScriptPurpose::Propose,
+];
+
+/// Number of items redeemers of a purpose can point at.
+fn pointer_count(transaction: &MintedTx<'_>, purpose: ScriptPurpose) -> usize {
+    let body = &transaction.transaction_body;
+    match purpose {
+        ScriptPurpose::Spend => body.inputs.iter().count(),
+        ScriptPurpose::Mint => body.mint.iter().flat_map(|m| m.iter()).count(),
+        ScriptPurpose::Cert => body.certificates.iter().flat_map(|c| c.iter()).count(),
+        ScriptPurpose::Reward => body.withdrawals.iter().flat_map(|w| w.iter()).count(),
+        ScriptPurpose::Vote => body.voting_procedures.iter().flat_map(|v| v.iter()).count(),
+        ScriptPurpose::Propose => body
+            .proposal_procedures
+            .iter()
+            .flat_map(|p| p.iter())
+            .count(),
+    }
+}
+
+/// Every redeemer points at an item locked by or acting for a script, and every item needing a
+/// Plutus script has a redeemer.
+fn check_redeemer_pointers(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    redeemers: &[Redeemer],
+    findings: &mut Findings,
+) {
+    for (position, redeemer) in redeemers.iter().enumerate() {
+        let pointer = format!(
+            "redeemer {} ({}:{})",
+            position,
+            evaluate::redeemer_purpose(redeemer),
+            redeemer.index
+        );
+        let count = pointer_count(transaction, redeemer.tag);
+        if redeemer.index as usize >= count {
+            findings.push(
+                Severity::Error,
+                REDEEMER_OUT_OF_RANGE,
+                format!(
+                    "{} points past the {} item(s) of its purpose (ExtraRedeemers)",
+                    pointer, count
+                ),
+            );
+        } else if script_hash::target_script_hash(transaction, utxos, redeemer).is_none() {
+            findings.push(
+                Severity::Error,
+                REDEEMER_WITHOUT_SCRIPT,
+                format!(
+                    "{} points at an item no script locks or acts for (ExtraRedeemers)",
+                    pointer
+                ),
+            );
+        }
+    }
+
+    let pointers: BTreeSet<_> = redeemers
+        .iter()
+        .map(|redeemer| {
+            (
+                evaluate::redeemer_purpose(redeemer),
+                redeemer.index as usize,
+            )
+        })
+        .collect();
+    for purpose in PURPOSES {
+        for index in 0..pointer_count(transaction, purpose) {
+            let Some(hash) = script_hash::pointer_script_hash(transaction, utxos, purpose, index)
+            else {
+                continue;
+            };
+            // Native scripts take no redeemer, and unknown scripts are reported as missing.
+            if script_hash::script_version(transaction, utxos, &hash).is_none() {
+                continue;
+            }
+            let name = evaluate::script_purpose_name(purpose);
+            if !pointers.contains(&(name, index)) {
+                findings.push(
+                    Severity::Error,
+                    MISSING_REDEEMER,
+                    format!(
+                        "{}:{} runs Plutus script {} but has no redeemer (MissingRedeemers)",
+                        name,
+                        index,
+                        hex::encode(hash)
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// The collateral of a transaction running Plutus scripts is what the ledger requires.
+fn check_collateral(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    parameters: Option<&CollateralParameters>,
+    findings: &mut Findings,
+) {
+    for problem in collateral::check(transaction, utxos, parameters) {
+        let (severity, code) = match problem {
+            Problem::NoCollateral => (Severity::Error, NO_COLLATERAL),
+            Problem::Unresolved(_) => (Severity::Warning, UNRESOLVED_COLLATERAL),
+            Problem::ScriptLocked(_) => (Severity::Error, SCRIPT_LOCKED_COLLATERAL),
+            Problem::NativeAssets(_) => (Severity::Error, COLLATERAL_ASSETS_NOT_RETURNED),
+            Problem::Insufficient { .. } => (Severity::Error, INSUFFICIENT_COLLATERAL),
+            Problem::IncorrectTotal { .. } => (Severity::Error, INCORRECT_TOTAL_COLLATERAL),
+            Problem::TooManyInputs { .. } => (Severity::Error, TOO_MANY_COLLATERAL_INPUTS),
+        };
+        findings.push(severity, code, problem.to_string());
+    }
+}
+
+/// Every needed script is provided, and, once the transaction is signed, every required signer
+/// and key-locked input has a signature and every native script passes.
+fn check_witnesses(transaction: &MintedTx<'_>, utxos: &ResolvedUtxos, findings: &mut Findings) {
+    for (what, hash) in missing_scripts(transaction, utxos) {
+        findings.push(
+            Severity::Error,
+            MISSING_SCRIPT,
+            format!(
+                "{} needs script {}, which is neither witnessed nor referenced (MissingScriptWitnessesUTXOW)",
+                what,
+                hex::encode(hash)
+            ),
+        );
+    }
+
+    let Some(witnesses) = &transaction.transaction_witness_set.vkeywitness else {
+        findings.push(
+            Severity::Info,
+            CHECK_SKIPPED,
+            "signatures and native scripts not checked: the transaction isn't signed yet",
+        );
+        return;
+    };
+    let signed: BTreeSet<Hash<28>> = witnesses
+        .iter()
+        .map(|witness| Hasher::<224>::hash(&witness.vkey))
+        .collect();
+
+    let body = &transaction.transaction_body;
+    let mut needed: Vec<(String, Hash<28>)> = body
+        .required_signers
+        .iter()
+        .flat_map(|signers| signers.iter())
+        .map(|signer| ("required signer".to_string(), *signer))
+        .collect();
+    for input in body
+        .inputs
+        .iter()
+        .chain(collateral_inputs(transaction).iter())
+    {
+        if let Some(output) = utxos.get(input)
+            && let Address::Shelley(address) = &output.address
+            && let ShelleyPaymentPart::Key(hash) = address.payment()
+        {
+            needed.push((
+                format!("the key locking input {}", input.format_readable()),
+                *hash,
+            ));
+        }
+    }
+
+    for (what, hash) in needed {
+        if !signed.contains(&hash) {
+            findings.push(
+                Severity::Warning,
+                MISSING_SIGNATURE,
+                format!(
+                    "{} {} has no signature (MissingVKeyWitnessesUTXOW)",
+                    what,
+                    hex::encode(hash)
+                ),
+            );
+        }
+    }
+
+    for (what, hash, evaluation) in native_script::evaluations(transaction, utxos) {
+        if !evaluation.passes {
+            findings.push(
+                Severity::Error,
+                NATIVE_SCRIPT_FAILS,
+                format!(
+                    "{} needs native script {}, which fails: {} (ScriptWitnessNotValidatingUTXOW), see --native-scripts",
+                    what,
+                    hex::encode(hash),
+                    evaluation.failures.join("; ")
+                ),
+            );
+        }
+    }
+}
+
+/// The transaction preserves value. The balance counts inputs given a placeholder as empty,
+/// leaves out unknown deposits and assumes some: it only shows the ledger rejects the
+/// transaction when it knows everything, and otherwise warns.
+fn check_balance(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    placeholders: &BTreeSet<TransactionInput>,
+    deposits: &Deposits,
+    findings: &mut Findings,
+) {
+    let (_, discrepancies) = balance::describe(transaction, utxos, deposits);
+    if discrepancies == 0 {
+        return;
+    }
+
+    let unresolved = transaction
+        .transaction_body
+        .inputs
+        .iter()
+        .filter(|input| placeholders.contains(input))
+        .count();
+    if unresolved > 0 || deposits.uncertain() > 0 {
+        findings.push(
+            Severity::Warning,
+            VALUE_NOT_CONSERVED,
+            format!(
+                "{} asset(s) out of balance, with {} input(s) standing in as placeholders and {} deposit(s) or refund(s) unknown or assumed, see --balance",
+                discrepancies,
+                unresolved,
+                deposits.uncertain()
+            ),
+        );
+    } else {
+        findings.push(
+            Severity::Error,
+            VALUE_NOT_CONSERVED,
+            format!(
+                "{} asset(s) out of balance (ValueNotConserved), see --balance",
+                discrepancies
+            ),
+        );
+    }
+}
+
+/// The script context of every redeemer can be built, and is ordered as the ledger orders it.
+/// PlutusV2 contexts aren't built yet, so their redeemers are skipped.
+fn check_contexts(
+    args: &Args,
+    inputs: &ContextInputs<'_>,
+    redeemers: &[Redeemer],
+    findings: &mut Findings,
+) {
+    for (position, redeemer) in redeemers.iter().enumerate() {
+        let version = plutus_version(args, inputs.transaction, inputs.utxos, redeemer);
+        if version == PlutusVersion::PlutusV2 {
+            findings.push(
+                Severity::Info,
+                CHECK_SKIPPED,
+                format!(
+                    "script context of redeemer {} not checked: nawi can't build PlutusV2 contexts yet",
+                    position
+                ),
+            );
+            continue;
+        }
+        match build_script_context(version, inputs, redeemer) {
+            Ok((_, _, plutus_data)) => {
+                for divergence in canonical::check(version, &plutus_data) {
+                    findings.push(
+                        Severity::Warning,
+                        UNORDERED_CONTEXT,
+                        format!(
+                            "the script context of redeemer {} is not ordered as the ledger orders it, {}",
+                            position, divergence
+                        ),
+                    );
+                }
+            }
+            Err(e) => findings.push(
+                Severity::Error,
+                CONTEXT_NOT_BUILT,
+                format!(
+                    "the script context of redeemer {} can't be built: {:#}",
+                    position, e
+                ),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use amaru_kernel::network::NetworkName;
+
+    use super::*;
+    use crate::{
+        output::placeholder_output, protocol::DepositParameters, resolver::ResolvedDatums,
+    };
+
+    /// A payment of one input to one output at a key address, with no redeemer and no signature.
+    const PAYMENT: &str = "84a30081825820000000000000000000000000000000000000000000000000000000000000000000018182581d60111111111111111111111111111111111111111111111111111111111a000f4240021a00030d40a0f5f6";
+
+    /// The same payment, with a spending redeemer.
+    const SPEND: &str = "84a30081825820000000000000000000000000000000000000000000000000000000000000000000018182581d60111111111111111111111111111111111111111111111111111111111a000f4240021a00030d40a1058184000000821a000f42401a3b9aca00f5f6";
+
+    #[test]
+    fn transactions_without_redeemers() {
+        let bytes = hex::decode(PAYMENT).unwrap();
+        let transaction = decode_transaction(&bytes).unwrap();
+        let redeemers = redeemers(&transaction);
+        assert!(redeemers.is_empty());
+
+        let mut findings = Findings::default();
+        check_redeemer_pointers(
+            &transaction,
+            &ResolvedUtxos::new(),
+            &redeemers,
+            &mut findings,
+        );
+        check_collateral(&transaction, &ResolvedUtxos::new(), None, &mut findings);
+        assert!(findings.0.is_empty());
+    }
+
+    #[test]
+    fn plutus_v2_contexts_are_skipped() {
+        let args = Args::try_parse_from([
+            "nawi",
+            "--tx-file",
+            "tx.cbor",
+            "--redeemer",
+            "all",
+            "--plutus-version",
+            "PlutusV2",
+        ])
+        .unwrap();
+        let bytes = hex::decode(SPEND).unwrap();
+        let transaction = decode_transaction(&bytes).unwrap();
+        let redeemers = redeemers(&transaction);
+        let (utxos, datums) = (ResolvedUtxos::new(), ResolvedDatums::new());
+        let inputs = ContextInputs {
+            transaction: &transaction,
+            utxos: &utxos,
+            datums: &datums,
+            datum: None,
+            network: args.network,
+            slot: 0,
+            protocol_version: None,
+        };
+
+        let mut findings = Findings::default();
+        check_contexts(&args, &inputs, &redeemers, &mut findings);
+        assert_eq!(findings.0.len(), 1);
+        assert_eq!(findings.0[0].severity, Severity::Info);
+        assert_eq!(findings.0[0].code, CHECK_SKIPPED);
+    }
+
+    #[test]
+    fn placeholder_inputs_make_value_conservation_a_warning() {
+        let bytes = hex::decode(PAYMENT).unwrap();
+        let transaction = decode_transaction(&bytes).unwrap();
+        let input = transaction
+            .transaction_body
+            .inputs
+            .iter()
+            .next()
+            .unwrap()
+            .clone();
+        let utxos = ResolvedUtxos::from([(
+            input.clone(),
+            placeholder_output(NetworkName::Preprod).unwrap(),
+        )]);
+        let deposits = Deposits::of(&transaction, &DepositParameters::default());
+
+        let cases = [
+            (BTreeSet::from([input]), Severity::Warning),
+            (BTreeSet::new(), Severity::Error),
+        ];
+        for (placeholders, severity) in cases {
+            let mut findings = Findings::default();
+            check_balance(
+                &transaction,
+                &utxos,
+                &placeholders,
+                &deposits,
+                &mut findings,
+            );
+            assert_eq!(findings.0.len(), 1);
+            assert_eq!(findings.0[0].code, VALUE_NOT_CONSERVED);
+            assert_eq!(findings.0[0].severity, severity);
+        }
+    }
+}
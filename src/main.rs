@@ -1,29 +1,89 @@
-use std::{borrow::Cow, collections::BTreeMap, ops::Deref, path::PathBuf, str::FromStr};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    num::NonZeroUsize,
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use amaru_kernel::{
-    MemoizedDatum, MemoizedTransactionOutput, MintedTx, OriginalHash, PlutusData, Redeemer,
-    ScriptPurpose, TransactionInput, cbor, network::NetworkName, normalize_redeemers, to_cbor,
+    Address, ComputeHash, Hash, MemoizedDatum, MemoizedScript, MemoizedTransactionOutput, MintedTx,
+    OriginalHash, PlutusData, Redeemer, ScriptPurpose, ShelleyPaymentPart, TransactionInput, cbor,
+    network::NetworkName, normalize_redeemers, to_cbor,
 };
 use amaru_plutus::{
     ToPlutusData,
-    script_context::{ScriptContextV1, TxInfoV1, TxInfoV3, v3},
+    script_context::{ScriptContextV1, TransactionOutput, TxInfoV1, TxInfoV3, v3},
 };
 use anyhow::{Context, Result, anyhow, bail};
-use clap::{ArgGroup, Parser, ValueEnum};
-use figment::{
-    Figment,
-    providers::{Env, Format, Toml},
-};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use serde_json::{Value, json};
 
 use crate::{
-    blockfrost::{Blockfrost, BlockfrostConfig},
-    formatter::ReadableFormatter,
+    color::ColorChoice,
+    deposits::Deposits,
+    formatter::{
+        ReadableFormatter, Section, TimeFormat, Timezone, indent_lines, with_sections,
+        with_validity_slots,
+    },
+    json::JsonFormatter,
+    protocol::{
+        CollateralParameters, CostModels, DepositParameters, ExUnits, ExecutionLimits,
+        FeeParameters, ProtocolVersion,
+    },
+    resolver::{ResolvedDatums, ResolvedUtxos, UtxoResolver},
+    snapshot::UtxoFile,
+    transaction::{RawTransaction, WitnessFields},
 };
 
+mod aiken;
+mod balance;
+mod batch;
+mod block;
 mod blockfrost;
+mod blueprint;
+mod canonical;
+mod collateral;
+mod color;
+mod compare;
+mod config;
+mod deposits;
+mod diagram;
+mod diff;
+mod evaluate;
+mod export;
 mod formatter;
+mod http;
+mod json;
+mod kupo;
+mod lenient;
+mod lint;
+mod metadata;
+mod min_ada;
+mod native_script;
+mod network;
+mod output;
+mod pager;
+mod protocol;
+mod query;
+mod report;
+mod resolver;
+mod retry;
+mod roundtrip;
+mod schema;
+mod script_data;
+mod script_hash;
+mod size;
+mod snapshot;
+mod stats;
+mod token_metadata;
+mod token_registry;
+mod transaction;
+mod translation;
+mod utxo_rpc;
 
-#[derive(ValueEnum, Default, Clone, Copy, Debug)]
+#[derive(ValueEnum, Default, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[value(rename_all = "verbatim")]
 pub enum PlutusVersion {
     PlutusV1,
@@ -32,13 +92,19 @@ pub enum PlutusVersion {
     PlutusV3,
 }
 
-#[derive(ValueEnum, Default, Clone, Copy, Debug)]
+#[derive(ValueEnum, Default, Clone, Copy, Debug, PartialEq)]
 #[value(rename_all = "kebab-case")]
 pub enum OutputFormat {
     Pretty,
     Cbor,
     #[default]
     Both,
+    /// A JSON document mirroring the pretty output, with the CBOR hex.
+    Json,
+    /// The context as cardano-cli's detailed-schema script data.
+    ScriptData,
+    /// The context as an Aiken Data expression.
+    Aiken,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -80,15 +146,189 @@ impl From<NetworkNameAdapter> for NetworkName {
     }
 }
 
+/// The slot to build the script context at.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum SlotSelector {
+    Number(u64),
+    /// The most recent slot known to the provider.
+    Tip,
+    /// A slot within the transaction's validity interval.
+    Validity,
+}
+
+impl FromStr for SlotSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tip" => Ok(Self::Tip),
+            "validity" => Ok(Self::Validity),
+            _ => s.parse().map(Self::Number).map_err(|_| {
+                anyhow!("Invalid slot: {s}. Valid options: a slot number, tip, validity")
+            }),
+        }
+    }
+}
+
+/// Which redeemer to build the script context for: a position in the normalized redeemer list,
+/// a purpose-qualified pointer as used by the ledger (e.g. `spend:0`, `mint:1`), or all of them.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum RedeemerSelector {
+    Position(usize),
+    Pointer(ScriptPurpose, u32),
+    All,
+}
+
+impl FromStr for RedeemerSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+
+        let Some((tag, index)) = s.split_once(':') else {
+            return s.parse().map(Self::Position).map_err(|_| {
+                anyhow!("Invalid redeemer {s}, expected an index, <purpose>:<index> or all")
+            });
+        };
+
+        let purpose = match tag.to_lowercase().as_str() {
+            "spend" => ScriptPurpose::Spend,
+            "mint" => ScriptPurpose::Mint,
+            "cert" => ScriptPurpose::Cert,
+            "reward" => ScriptPurpose::Reward,
+            "vote" => ScriptPurpose::Vote,
+            "propose" => ScriptPurpose::Propose,
+            _ => {
+                return Err(anyhow!(
+                    "Unknown redeemer purpose: {tag}. Valid options: spend, mint, cert, reward, vote, propose"
+                ));
+            }
+        };
+
+        let index = index
+            .parse()
+            .map_err(|_| anyhow!("Invalid redeemer index in {s}"))?;
+
+        Ok(Self::Pointer(purpose, index))
+    }
+}
+
+impl RedeemerSelector {
+    /// The selected redeemers, along with their position in the normalized list.
+    fn select<'a>(&self, redeemers: &'a [Cow<'a, Redeemer>]) -> Result<Vec<(usize, &'a Redeemer)>> {
+        match self {
+            Self::Position(position) => redeemers
+                .get(*position)
+                .map(|redeemer| vec![(*position, redeemer.as_ref())])
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Invalid redeemer index {}. Transaction has {} redeemer(s)",
+                        position,
+                        redeemers.len()
+                    )
+                }),
+            Self::Pointer(purpose, index) => redeemers
+                .iter()
+                .position(|redeemer| redeemer.tag == *purpose && redeemer.index == *index)
+                .map(|position| vec![(position, redeemers[position].as_ref())])
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Transaction has no {:?} redeemer at index {}",
+                        purpose,
+                        index
+                    )
+                }),
+            Self::All => Ok(redeemers.iter().map(Cow::as_ref).enumerate().collect()),
+        }
+    }
+}
+
+/// The script context built for one redeemer of a transaction.
+struct RedeemerContext {
+    /// Position of the redeemer in the normalized list.
+    redeemer: usize,
+    purpose: String,
+    index: u32,
+    version: PlutusVersion,
+    pretty_context: String,
+    json_context: Value,
+    plutus_data: PlutusData,
+    /// The datum handed to the script, for spending redeemers.
+    datum: Option<PlutusData>,
+    redeemer_data: PlutusData,
+}
+
+impl RedeemerContext {
+    /// The formatted context, headed with the redeemer it was built for. JSON documents go
+    /// without the heading, to stay valid JSON.
+    fn format_labelled(&self, format: OutputFormat) -> String {
+        if matches!(format, OutputFormat::Json | OutputFormat::ScriptData) {
+            return self.format(format);
+        }
+        if format == OutputFormat::Aiken {
+            return format!(
+                "// Redeemer {} ({} #{})\n{}",
+                self.redeemer,
+                self.purpose,
+                self.index,
+                self.format(format)
+            );
+        }
+
+        format!(
+            "Redeemer {} ({} #{}):\n{}",
+            self.redeemer,
+            self.purpose,
+            self.index,
+            self.format(format)
+        )
+    }
+
+    fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Pretty => self.pretty_context.clone(),
+            OutputFormat::Cbor => format_script_context(&self.plutus_data),
+            OutputFormat::Both => format!(
+                "{}\n{}",
+                self.pretty_context,
+                format_script_context(&self.plutus_data)
+            ),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(&self.to_json()).expect("JSON values always serialize")
+            }
+            OutputFormat::ScriptData => script_data::detailed_schema(&self.plutus_data),
+            OutputFormat::Aiken => aiken::data_literal(&self.plutus_data),
+        }
+    }
+
+    /// The JSON document of `--output json`.
+    fn to_json(&self) -> Value {
+        json!({
+            "redeemer": self.redeemer,
+            "purpose": self.purpose,
+            "index": self.index,
+            "version": format!("{:?}", self.version),
+            "context": self.json_context,
+            "cbor": hex::encode(to_cbor(&self.plutus_data)),
+        })
+    }
+}
+
 /// 👁️  Nawi: The eye of Amaru.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 #[command(group(
     ArgGroup::new("input")
         .required(true)
-        .args(&["tx_file", "bytes"])
+        .args(&["tx_file", "bytes", "tx_hash", "block_file", "block_hash"])
 ))]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the transaction file (e.g. path/to/tx.cbor)
     #[arg(short, long, value_name = "FILE")]
     tx_file: Option<PathBuf>,
@@ -97,102 +337,1793 @@ struct Args {
     #[arg(short, long, value_name = "HEX")]
     bytes: Option<String>,
 
-    /// The index of the redeemer for which you want to construct the ScriptContext
-    #[arg(short, long, value_name = "INDEX")]
-    redeemer: u8,
+    /// Hash of a transaction to fetch from the provider
+    #[arg(long, value_name = "HASH")]
+    tx_hash: Option<String>,
+
+    /// Fetch --tx-hash from the provider's mempool, before it lands on-chain
+    #[arg(long, requires = "tx_hash")]
+    mempool: bool,
+
+    /// Path to a block file (CBOR, hex or a text envelope) containing the transaction
+    #[arg(long, value_name = "FILE", requires = "tx_index")]
+    block_file: Option<PathBuf>,
+
+    /// Hash of a block to fetch the transaction from
+    #[arg(long, value_name = "HASH", requires = "tx_index")]
+    block_hash: Option<String>,
+
+    /// Position of the transaction within the block given by --block-file or --block-hash
+    #[arg(long, value_name = "INDEX")]
+    tx_index: Option<usize>,
+
+    /// Witness set kept apart from the transaction body, merged into the transaction
+    #[arg(long, value_name = "FILE")]
+    witness_file: Option<PathBuf>,
+
+    /// Redeemers (CBOR of the witness set's redeemers field) for a body that has none yet
+    #[arg(long, value_name = "FILE")]
+    redeemers_file: Option<PathBuf>,
+
+    /// Datums (CBOR array of PlutusData) for a body that has none yet
+    #[arg(long, value_name = "FILE")]
+    datums_file: Option<PathBuf>,
+
+    /// The redeemer to construct the ScriptContext for: an index into the transaction's
+    /// redeemers, <purpose>:<index> (spend, mint, cert, reward, vote, propose), e.g. spend:0, or
+    /// all to build one context per redeemer
+    #[arg(
+        short,
+        long,
+        value_name = "REDEEMER",
+        required_unless_present = "script_hash"
+    )]
+    redeemer: Option<RedeemerSelector>,
+
+    /// Build the context of every redeemer executing the validator with this hash
+    #[arg(long, value_name = "HASH", conflicts_with = "redeemer")]
+    script_hash: Option<String>,
+
+    /// When to color the pretty output and warnings
+    #[arg(long, value_name = "WHEN", default_value = "auto", global = true)]
+    color: ColorChoice,
+
+    /// Render output that can be byte-compared across machines: fixed UTC timestamps, sorted
+    /// assets, and no color
+    #[arg(long, global = true)]
+    deterministic: bool,
+
+    /// Annotate Plutus data with what it looks like: addresses, credentials, asset classes,
+    /// hashes, and POSIX times
+    #[arg(long, global = true)]
+    hints: bool,
+
+    /// Print straight to the terminal rather than through $PAGER when the output is long
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// How to display the validity range: iso, posix (milliseconds), slot, or all
+    #[arg(long, value_name = "FORMAT", default_value = "iso", global = true)]
+    time: TimeFormat,
+
+    /// Timezone of ISO-8601 times: utc, local, or an offset such as +02:00
+    #[arg(long, value_name = "TZ", default_value = "utc", global = true)]
+    timezone: Timezone,
 
     /// Network to use for resolving UTxOs
-    #[arg(short, long, default_value = "mainnet", value_name = "NETWORK")]
+    #[arg(
+        short,
+        long,
+        default_value = "mainnet",
+        value_name = "NETWORK",
+        global = true
+    )]
     network: NetworkNameAdapter,
 
-    /// Plutus language version
-    #[arg(short, long, default_value = "PlutusV3", value_name = "VERSION")]
-    plutus_version: PlutusVersion,
+    /// Plutus language version [default: the version of the script the redeemer executes]
+    #[arg(short, long, value_name = "VERSION", global = true)]
+    plutus_version: Option<PlutusVersion>,
 
-    /// Slot number of the transaction
-    #[arg(short, long, value_name = "SLOT")]
-    slot: Option<u64>,
+    /// Major protocol version the transaction executes under, checked against the Plutus version
+    /// and the Conway features it uses [default: the one of --protocol-params]
+    #[arg(long, value_name = "MAJOR", global = true)]
+    protocol_version: Option<u64>,
+
+    /// Slot number of the transaction, `tip` for the chain tip, or `validity` to derive it from
+    /// the validity interval [default: validity if the transaction has one, else tip]
+    #[arg(short, long, value_name = "SLOT", global = true)]
+    slot: Option<SlotSelector>,
 
     /// Output format of the ScriptContext
-    #[arg(short, long, default_value = "both", value_name = "FORMAT")]
+    #[arg(
+        short,
+        long,
+        default_value = "both",
+        value_name = "FORMAT",
+        global = true
+    )]
     output: OutputFormat,
+
+    /// Resolve UTxOs from a snapshot written by --save-utxos instead of the provider
+    #[arg(long, value_name = "FILE", global = true)]
+    utxo_file: Option<PathBuf>,
+
+    /// Datum to hand to spending validators, replacing the one of the spent output (file or hex)
+    #[arg(long, value_name = "FILE|HEX")]
+    datum: Option<String>,
+
+    /// Write one `redeemer-<n>.context` file per script context instead of printing them
+    #[arg(long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+
+    /// Print one JSON object per script context, one per line, for jq and data pipelines
+    #[arg(long, conflicts_with = "out_dir")]
+    ndjson: bool,
+
+    /// Print only these sections of the pretty TxInfo (e.g. inputs,outputs,mint)
+    #[arg(
+        long,
+        value_name = "SECTIONS",
+        value_delimiter = ',',
+        conflicts_with = "skip",
+        global = true
+    )]
+    only: Vec<Section>,
+
+    /// Leave these sections out of the pretty TxInfo
+    #[arg(long, value_name = "SECTIONS", value_delimiter = ',', global = true)]
+    skip: Vec<Section>,
+
+    /// Print one line per redeemer: purpose, script hash, execution units, context size, and
+    /// whether its context could be built
+    #[arg(long, global = true)]
+    summary: bool,
+
+    /// Print only this node of each script context, e.g. tx_info.outputs[2].value, in the format
+    /// of --output
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["ndjson", "out_dir"])]
+    query: Option<String>,
+
+    /// Print only the CBOR hex of each script context, one per line, for shell scripts
+    #[arg(short, long, conflicts_with_all = ["ndjson", "out_dir", "query"])]
+    quiet: bool,
+
+    /// Write the script contexts to a file instead of stdout
+    #[arg(long, value_name = "FILE", conflicts_with = "out_dir")]
+    out: Option<PathBuf>,
+
+    /// Write the CBOR hex of each script context to a file, one per line, leaving the pretty
+    /// report alone on stdout (or in --out)
+    #[arg(long, value_name = "FILE")]
+    cbor_out: Option<PathBuf>,
+
+    /// Write a shareable Markdown (.md) or HTML (.html) report of the script contexts
+    #[arg(long, value_name = "FILE")]
+    report: Option<PathBuf>,
+
+    /// Draw the transaction as a Mermaid (.mmd) or Graphviz (.dot) diagram, highlighting what
+    /// the selected redeemers point at
+    #[arg(long, value_name = "FILE")]
+    diagram: Option<PathBuf>,
+
+    /// Write the resolved UTxOs (and slot) to a snapshot file for offline reuse
+    #[arg(long, value_name = "FILE")]
+    save_utxos: Option<PathBuf>,
+
+    /// Maximum number of UTxO requests sent to the provider in parallel [default: 10]
+    #[arg(long, value_name = "N", global = true)]
+    max_concurrency: Option<NonZeroUsize>,
+
+    /// Look up the preimage of hashed spend datums through the provider
+    #[arg(long, global = true)]
+    resolve_datums: bool,
+
+    /// Continue with placeholder outputs (no value, no datum) for UTxOs that can't be resolved
+    #[arg(long, global = true)]
+    allow_missing_utxos: bool,
+
+    /// Inject or replace a resolved output, as hex-encoded output CBOR or a file (repeatable)
+    #[arg(long = "override-utxo", value_name = "TXID#IX=OUTPUT", global = true)]
+    override_utxos: Vec<String>,
+
+    /// Print provider request statistics to stderr after the run
+    #[arg(long, global = true)]
+    stats: bool,
+
+    /// Drop transaction fields that fail to decode, reporting where they are, instead of failing
+    #[arg(long, global = true)]
+    lenient: bool,
+
+    /// Annotate the pretty output with what the ledger dropped, coerced or defaulted while
+    /// translating the transaction for the chosen Plutus version
+    #[arg(long, global = true)]
+    explain_translation: bool,
+
+    /// Fail when the ordering of a map or set of the script context differs from the ledger's,
+    /// rather than warning
+    #[arg(long, global = true)]
+    canonical: bool,
+
+    /// JSON file with the cost models to use, bare or within protocol parameters [default:
+    /// fetched from the provider]
+    #[arg(long, value_name = "FILE", global = true)]
+    cost_models: Option<PathBuf>,
+
+    /// Show the language views (cost models) that enter the script integrity hash
+    #[arg(long)]
+    language_views: bool,
+
+    /// Show the reference scripts of the inputs and reference inputs, with their sizes and the
+    /// fee Conway charges for them
+    #[arg(long)]
+    reference_scripts: bool,
+
+    /// Show which script each redeemer of the transaction runs, following the ledger's pointer
+    /// rules, and where the transaction provides it
+    #[arg(long)]
+    redeemer_scripts: bool,
+
+    /// Evaluate the native scripts the transaction needs against its signatures and validity
+    /// interval, clause by clause
+    #[arg(long)]
+    native_scripts: bool,
+
+    /// Base price of reference scripts in lovelace per byte [default: from --protocol-params,
+    /// or fetched from the provider]
+    #[arg(long, value_name = "LOVELACE", global = true)]
+    ref_script_cost_per_byte: Option<f64>,
+
+    /// Compare the execution units each redeemer declares with the limits per transaction and
+    /// per block
+    #[arg(long)]
+    budget: bool,
+
+    /// Break the minimum fee down into its size, execution unit and reference script parts, and
+    /// compare it with the declared fee
+    #[arg(long)]
+    fee: bool,
+
+    /// Balance the value the transaction consumes against the value it produces, asset by asset
+    #[arg(long)]
+    balance: bool,
+
+    /// Check the collateral against the required share of the fee, the collateral return and the
+    /// total collateral field
+    #[arg(long)]
+    collateral: bool,
+
+    /// Check that every output holds the minimum lovelace its size requires
+    #[arg(long)]
+    min_ada: bool,
+
+    /// Show the serialized size of the body, each witness-set field and the auxiliary data,
+    /// against the maximum transaction size
+    #[arg(long)]
+    size: bool,
+
+    /// JSON file with the protocol parameters (cardano-cli, Ogmios or Blockfrost), for the
+    /// execution unit limits, fee parameters, deposits, collateral percentage, cost per UTxO
+    /// byte, maximum transaction size and reference script price [default: fetched from the
+    /// provider]
+    #[arg(long, value_name = "FILE", global = true)]
+    protocol_params: Option<PathBuf>,
+
+    /// Evaluate the transaction through Ogmios (or the provider) and compare the outcome of each
+    /// redeemer with the budget it declares
+    #[arg(long)]
+    cross_check: bool,
+
+    /// Token registry (a URL such as https://tokens.cardano.org, or a JSON mapping file) whose
+    /// tickers and decimals show asset amounts in their own unit in the pretty output
+    #[arg(long, value_name = "URL|FILE", global = true)]
+    token_registry: Option<String>,
+
+    /// CIP-57 blueprint (plutus.json) whose schemas label the datum and redeemer of its
+    /// validators in the pretty output
+    #[arg(long, value_name = "FILE", global = true)]
+    blueprint: Option<PathBuf>,
+
+    /// Reference script context (CBOR, hex or JSON), e.g. dumped by the node's Plutus debugging
+    /// tools, to diff the built context against
+    #[arg(long, value_name = "FILE")]
+    compare: Option<PathBuf>,
+
+    /// Saved script contexts (the CBOR hex of each, one per line, as written by --cbor-out) the
+    /// built ones must match, failing with their differences otherwise
+    #[arg(long, value_name = "FILE")]
+    golden: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Build every script context of each transaction file in a directory
+    Batch(batch::BatchArgs),
+    /// Generate a test fixture from the script contexts of a transaction
+    Export(export::ExportArgs),
+    /// Report where two Plutus data (e.g. two script contexts) differ
+    DiffData(diff::DiffDataArgs),
+    /// Run static checks over a transaction before submitting it
+    Lint(lint::LintArgs),
+    /// Print the JSON Schema of the `--output json` documents
+    Schema,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    color::init(if args.deterministic {
+        ColorChoice::Never
+    } else {
+        args.color
+    });
+    formatter::init(args.deterministic, args.time, args.timezone, args.hints);
+    pager::init(!args.no_pager);
+
+    // Comparing data and printing the schema need no provider.
+    match &args.command {
+        Some(Command::DiffData(diff_args)) => return diff::run(&args, diff_args),
+        Some(Command::Schema) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&schema::json_schema())
+                    .expect("JSON values always serialize")
+            );
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let resolver: Box<dyn UtxoResolver> = match &args.utxo_file {
+        Some(path) => Box::new(UtxoFile::load(path)?),
+        None => {
+            let mut config = config::load()?;
+            if let Some(max_concurrency) = args.max_concurrency {
+                config.max_concurrency = max_concurrency;
+            }
+            resolver::from_config(&config)?
+        }
+    };
+
+    match &args.command {
+        Some(Command::Batch(batch_args)) => {
+            batch::run(&args, batch_args, resolver.as_ref()).await?
+        }
+        Some(Command::Export(export_args)) => {
+            export::run(&args, export_args, resolver.as_ref()).await?
+        }
+        Some(Command::DiffData(diff_args)) => diff::run(&args, diff_args)?,
+        Some(Command::Lint(lint_args)) => lint::run(&args, lint_args, resolver.as_ref()).await?,
+        Some(Command::Schema) => unreachable!("handled before the resolver is built"),
+        None => run(&args, resolver.as_ref()).await?,
+    }
+
+    if args.stats {
+        for stats in resolver.stats().await {
+            eprintln!("{}", stats);
+        }
+    }
+
+    Ok(())
+}
 
-    let config = load_config()?;
-    let blockfrost = Blockfrost::new(&config);
+/// Build the script contexts and the reports asked for, paging what goes to stdout in one go:
+/// whatever was built before a failure is still printed.
+async fn run(args: &Args, resolver: &dyn UtxoResolver) -> Result<()> {
+    let mut stdout = Vec::new();
+    let result = build_and_report(args, resolver, &mut stdout).await;
+    if !stdout.is_empty() {
+        pager::print(&stdout.join("\n"));
+    }
+    result
+}
+
+async fn build_and_report(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    stdout: &mut Vec<String>,
+) -> Result<()> {
+    let script_hash = args
+        .script_hash
+        .as_deref()
+        .map(|hash| {
+            hash.trim()
+                .parse::<Hash<28>>()
+                .map_err(|_| anyhow!("Invalid script hash: {}", hash))
+        })
+        .transpose()?;
 
-    let tx_bytes = load_transaction_bytes(&args)?;
+    let selector = match (args.redeemer, script_hash) {
+        (Some(selector), _) => selector,
+        (None, Some(_)) => RedeemerSelector::All,
+        (None, None) => bail!("Either --redeemer or --script-hash is required"),
+    };
+
+    let tx_bytes = transaction::complete(
+        load_transaction_bytes(args, resolver).await?,
+        &witness_fields(args)?,
+    )?;
+    let tx_bytes = repair_if_lenient(args, tx_bytes)?;
     let transaction = decode_transaction(&tx_bytes)?;
 
-    let all_inputs = collect_all_inputs(&transaction);
-    let utxos = blockfrost.get_utxos(&all_inputs).await?;
+    let (utxos, datums, _) = resolve_transaction(args, resolver, &transaction).await?;
+    warn_about_transaction(args, &transaction, &utxos);
 
     let redeemers = get_redeemers(&transaction)?;
-    let redeemer = redeemers.get(args.redeemer as usize).ok_or_else(|| {
-        anyhow!(
-            "Invalid redeemer index {}. Transaction has {} redeemer(s)",
-            args.redeemer,
-            redeemers.len()
-        )
-    })?;
+    let mut selected = selector.select(&redeemers)?;
 
-    let slot = match args.slot {
-        Some(slot) => slot,
-        None => blockfrost.get_tip().await?,
-    };
+    if let Some(script_hash) = &script_hash {
+        selected.retain(|(_, redeemer)| {
+            script_hash::target_script_hash(&transaction, &utxos, redeemer) == Some(*script_hash)
+        });
 
-    let (pretty_context, plutus_data) = build_script_context(
-        args.plutus_version,
-        &transaction,
-        &utxos,
-        redeemer,
-        args.network,
+        if selected.is_empty() {
+            bail!(
+                "No redeemer of the transaction executes script {}",
+                hex::encode(script_hash)
+            );
+        }
+    }
+
+    let slot = resolve_slot(args, resolver, &transaction).await?;
+    let protocol_version = resolve_protocol_version(args)?;
+
+    if let Some(path) = &args.save_utxos {
+        snapshot::save(path, &utxos, slot)?;
+    }
+
+    let datum = parse_datum(args)?;
+    let inputs = ContextInputs {
+        transaction: &transaction,
+        utxos: &utxos,
+        datums: &datums,
+        datum: datum.as_ref(),
+        network: args.network,
         slot,
-    )?;
+        protocol_version,
+    };
+    if args.summary {
+        let lines = summary_lines(args, &inputs, &selected);
+        let failed = lines.iter().filter(|(_, ok)| !ok).count();
+        let lines: Vec<_> = lines.into_iter().map(|(line, _)| line).collect();
+        write_output(stdout, args.out.as_deref(), &lines.join("\n"))?;
+        if failed > 0 {
+            bail!("{} of {} redeemer(s) failed", failed, selected.len());
+        }
+        return Ok(());
+    }
+
+    let contexts = build_script_contexts(args, &inputs, &selected)?;
+
+    // With --cbor-out, the CBOR goes to its own file rather than after the pretty report.
+    let output = match (args.output, &args.cbor_out) {
+        (OutputFormat::Both, Some(_)) => OutputFormat::Pretty,
+        (output, _) => output,
+    };
+
+    if let Some(path) = &args.cbor_out {
+        let lines: Vec<_> = contexts
+            .iter()
+            .map(|context| hex::encode(to_cbor(&context.plutus_data)))
+            .collect();
+        write_output(stdout, Some(path), &lines.join("\n"))?;
+    }
+
+    let tx_hash = hex::encode(transaction.transaction_body.original_hash());
+    if let Some(path) = &args.report {
+        report::write(path, &tx_hash, &contexts)?;
+    }
+    if let Some(path) = &args.diagram {
+        diagram::write(path, &tx_hash, &contexts)?;
+    }
+
+    if let Some(query) = &args.query {
+        let mut nodes = Vec::new();
+        for context in &contexts {
+            let node =
+                query::select(context.version, &context.plutus_data, query).with_context(|| {
+                    format!(
+                        "Failed to query the context of redeemer {}",
+                        context.redeemer
+                    )
+                })?;
+            nodes.push((context, node));
+        }
+
+        let contents = match nodes.as_slice() {
+            [(_, node)] => query::format(node, output),
+            _ if output == OutputFormat::Json => {
+                let documents: Vec<_> = nodes.iter().map(|(_, node)| node.to_json()).collect();
+                serde_json::to_string_pretty(&documents).expect("JSON values always serialize")
+            }
+            // Script data is printed one document per line.
+            _ if output == OutputFormat::ScriptData => nodes
+                .iter()
+                .map(|(_, node)| query::format(node, output))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => nodes
+                .iter()
+                .map(|(context, node)| {
+                    let label = format!(
+                        "Redeemer {} ({} #{})",
+                        context.redeemer, context.purpose, context.index
+                    );
+                    if output == OutputFormat::Aiken {
+                        format!("// {}\n{}", label, query::format(node, output))
+                    } else {
+                        format!("{}:\n{}", label, query::format(node, output))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        };
+        write_output(
+            stdout,
+            args.out.as_deref(),
+            &painted(args, output, contents),
+        )?;
+    } else if args.quiet {
+        let lines: Vec<_> = contexts
+            .iter()
+            .map(|context| hex::encode(to_cbor(&context.plutus_data)))
+            .collect();
+        write_output(stdout, args.out.as_deref(), &lines.join("\n"))?;
+    } else if args.ndjson {
+        let lines: Vec<_> = contexts
+            .iter()
+            .map(|context| {
+                let mut line = context.to_json();
+                line["transaction"] = json!(tx_hash);
+                line.to_string()
+            })
+            .collect();
+        write_output(stdout, args.out.as_deref(), &lines.join("\n"))?;
+    } else if let Some(out_dir) = &args.out_dir {
+        std::fs::create_dir_all(out_dir)
+            .with_context(|| format!("Failed to create directory: {}", out_dir.display()))?;
+
+        for context in &contexts {
+            if output == OutputFormat::ScriptData {
+                write_script_data(out_dir, context)?;
+                continue;
+            }
+
+            let out_path = out_dir.join(format!("redeemer-{}.context", context.redeemer));
+            std::fs::write(&out_path, context.format_labelled(output))
+                .with_context(|| format!("Failed to write {}", out_path.display()))?;
+            eprintln!("Redeemer {} -> {}", context.redeemer, out_path.display());
+        }
+    } else if selector == RedeemerSelector::All && output == OutputFormat::Json {
+        let documents: Vec<_> = contexts.iter().map(RedeemerContext::to_json).collect();
+        write_output(
+            stdout,
+            args.out.as_deref(),
+            &serde_json::to_string_pretty(&documents).expect("JSON values always serialize"),
+        )?;
+    } else if selector == RedeemerSelector::All {
+        let formatted: Vec<_> = contexts
+            .iter()
+            .map(|context| context.format_labelled(output))
+            .collect();
+        // Script data is printed one document per line.
+        let separator = if output == OutputFormat::ScriptData {
+            "\n"
+        } else {
+            "\n\n"
+        };
+        write_output(
+            stdout,
+            args.out.as_deref(),
+            &painted(args, output, formatted.join(separator)),
+        )?;
+    } else {
+        let formatted: Vec<_> = contexts
+            .iter()
+            .map(|context| context.format(output))
+            .collect();
+        write_output(
+            stdout,
+            args.out.as_deref(),
+            &painted(args, output, formatted.join("\n")),
+        )?;
+    }
+
+    if let Some(path) = &args.compare {
+        stdout.push(compare_with_reference(&contexts, path)?);
+    }
+
+    if let Some(path) = &args.golden {
+        compare_with_golden(&contexts, path)?;
+    }
+
+    if args.cross_check {
+        let (provider, evaluations) = evaluate::evaluate(resolver, &tx_bytes).await?;
+        let redeemers = get_redeemers(&transaction)?;
+        let redeemers: Vec<_> = redeemers.iter().map(Deref::deref).collect();
+
+        let (report, disagreements) = evaluate::cross_check(provider, &redeemers, &evaluations);
+        stdout.push(format!("\n{}", report));
+        if disagreements > 0 {
+            bail!("{} disagreement(s) with {}", disagreements, provider);
+        }
+    }
+
+    if args.language_views {
+        stdout.push(format!(
+            "\n{}",
+            format_language_views(args, resolver, &transaction, &utxos).await?
+        ));
+    }
+
+    if args.budget {
+        let (report, overruns) = format_budget(args, resolver, &transaction).await?;
+        stdout.push(format!("\n{}", report));
+        if overruns > 0 {
+            bail!("The redeemers exceed {} execution unit limit(s)", overruns);
+        }
+    }
+
+    if args.fee {
+        let (report, shortfall) =
+            format_fee(args, resolver, &transaction, &utxos, &tx_bytes).await?;
+        stdout.push(format!("\n{}", report));
+        if shortfall > 0 {
+            bail!(
+                "The declared fee is {} lovelace below the minimum",
+                shortfall
+            );
+        }
+    }
+
+    if args.balance {
+        let parameters = resolve_deposit_parameters(args, resolver, &transaction).await;
+        let deposits = Deposits::of(&transaction, &parameters);
+        let (report, discrepancies) = balance::describe(&transaction, &utxos, &deposits);
+        stdout.push(format!("\n{}", report));
+        if discrepancies > 0 {
+            bail!(
+                "The transaction doesn't preserve value: {} asset(s) out of balance",
+                discrepancies
+            );
+        }
+    }
+
+    if args.collateral {
+        let parameters = resolve_protocol_parameters(args, resolver)
+            .await
+            .and_then(|parameters| CollateralParameters::from_json(&parameters));
+        let (report, problems) = collateral::describe(&transaction, &utxos, parameters);
+        stdout.push(format!("\n{}", report));
+        if problems > 0 {
+            bail!("The ledger rejects the collateral: {} problem(s)", problems);
+        }
+    }
 
-    match args.output {
-        OutputFormat::Pretty => println!("{}", pretty_context),
-        OutputFormat::Cbor => print_script_context(&plutus_data),
-        OutputFormat::Both => {
-            println!("{}", pretty_context);
-            print_script_context(&plutus_data);
+    if args.min_ada {
+        let coins_per_byte = resolve_protocol_parameters(args, resolver)
+            .await
+            .and_then(|parameters| protocol::coins_per_utxo_byte(&parameters))?;
+        let (report, below) = min_ada::describe(&transaction, coins_per_byte);
+        stdout.push(format!("\n{}", report));
+        if below > 0 {
+            bail!("{} output(s) below the minimum lovelace", below);
         }
+    }
+
+    if args.size {
+        let max_size = resolve_protocol_parameters(args, resolver)
+            .await
+            .and_then(|parameters| protocol::max_transaction_size(&parameters));
+        let (report, excess) = size::describe(&tx_bytes, max_size)?;
+        stdout.push(format!("\n{}", report));
+        if excess > 0 {
+            bail!("The transaction is {} bytes over the maximum size", excess);
+        }
+    }
+
+    if args.redeemer_scripts {
+        stdout.push(format!(
+            "\n{}",
+            format_redeemer_scripts(&transaction, &utxos)?
+        ));
+    }
+
+    if args.native_scripts {
+        let (report, failing) = native_script::describe(&transaction, &utxos);
+        stdout.push(format!("\n{}", report));
+        if failing > 0 {
+            bail!("{} native script(s) fail", failing);
+        }
+    }
+
+    if args.reference_scripts {
+        stdout.push(format!(
+            "\n{}",
+            format_reference_scripts(args, resolver, &transaction, &utxos).await
+        ));
+    }
+
+    Ok(())
+}
+
+/// Color the pretty report when it goes to the terminal.
+fn painted(args: &Args, output: OutputFormat, contents: String) -> String {
+    match (output, &args.out) {
+        (OutputFormat::Pretty | OutputFormat::Both, None) => color::paint(&contents),
+        _ => contents,
+    }
+}
+
+/// Add to what is printed to stdout, or write to a file when one is given.
+fn write_output(stdout: &mut Vec<String>, path: Option<&Path>, contents: &str) -> Result<()> {
+    let Some(path) = path else {
+        stdout.push(contents.to_string());
+        return Ok(());
     };
 
+    std::fs::write(path, format!("{}\n", contents))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    eprintln!("-> {}", path.display());
+
     Ok(())
 }
 
-fn load_config() -> Result<BlockfrostConfig> {
-    Figment::new()
-        .merge(Toml::file("nawi.toml"))
-        .merge(Env::prefixed("BLOCKFROST_"))
-        .extract()
-        .context("Failed to load configuration. Ensure BLOCKFROST_KEY is set or nawi.toml exists")
+/// Write the context, redeemer and datum of a redeemer as detailed-schema script data files, for
+/// `cardano-cli --script-data-file` and the like.
+fn write_script_data(out_dir: &Path, context: &RedeemerContext) -> Result<()> {
+    let files = [
+        ("context", Some(&context.plutus_data)),
+        ("redeemer", Some(&context.redeemer_data)),
+        ("datum", context.datum.as_ref()),
+    ];
+
+    for (name, data) in files {
+        let Some(data) = data else {
+            continue;
+        };
+        let out_path = out_dir.join(format!("redeemer-{}.{}.json", context.redeemer, name));
+        std::fs::write(&out_path, script_data::detailed_schema(data))
+            .with_context(|| format!("Failed to write {}", out_path.display()))?;
+        eprintln!("Redeemer {} -> {}", context.redeemer, out_path.display());
+    }
+
+    Ok(())
 }
 
-fn load_transaction_bytes(args: &Args) -> Result<Vec<u8>> {
-    match (&args.tx_file, &args.bytes) {
-        (Some(path), _) => std::fs::read(path)
-            .with_context(|| format!("Failed to read transaction file: {}", path.display())),
-        (None, Some(hex_str)) => hex::decode(hex_str.trim()).context(
-            "Failed to decode hex string. Ensure it contains valid hexadecimal characters",
+/// Diff the single context built against a reference one, failing at the first divergent field.
+fn compare_with_reference(contexts: &[RedeemerContext], path: &Path) -> Result<String> {
+    let [context] = contexts else {
+        bail!(
+            "--compare needs a single script context, but {} were built",
+            contexts.len()
+        );
+    };
+
+    let reference = compare::decode_reference(&read_cbor_file(path, "reference context")?)?;
+
+    match compare::first_difference(context.version, &context.plutus_data, &reference) {
+        None => Ok(format!(
+            "\nThe script context matches the reference {}",
+            path.display()
+        )),
+        Some(difference) => bail!(
+            "The script context differs from the reference {} at {}",
+            path.display(),
+            difference
         ),
-        (None, None) => Err(anyhow!(
-            "No input provided. Use either --tx-file or --bytes"
+    }
+}
+
+/// Diff the contexts built against saved ones, failing with every difference found.
+fn compare_with_golden(contexts: &[RedeemerContext], path: &Path) -> Result<()> {
+    let contents = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failed to read golden file: {}. Write it with --cbor-out",
+            path.display()
+        )
+    })?;
+    let golden = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let bytes = hex::decode(line.trim())
+                .with_context(|| format!("Invalid hex on line {} of {}", i + 1, path.display()))?;
+            cbor::decode(&bytes).with_context(|| {
+                format!(
+                    "Failed to decode line {} of {} as PlutusData",
+                    i + 1,
+                    path.display()
+                )
+            })
+        })
+        .collect::<Result<Vec<PlutusData>>>()?;
+
+    if golden.len() != contexts.len() {
+        bail!(
+            "{} script context(s) were built, but the golden file {} holds {}",
+            contexts.len(),
+            path.display(),
+            golden.len()
+        );
+    }
+
+    let mut mismatches = Vec::new();
+    for (context, golden) in contexts.iter().zip(&golden) {
+        let differences = compare::all_differences(
+            context.version,
+            &context.plutus_data,
+            golden,
+            ("built", "golden"),
+        );
+        if !differences.is_empty() {
+            mismatches.push(format!(
+                "Redeemer {} ({} #{}), {} difference(s):\n{}",
+                context.redeemer,
+                context.purpose,
+                context.index,
+                differences.len(),
+                differences.join("\n")
+            ));
+        }
+    }
+
+    if mismatches.is_empty() {
+        eprintln!(
+            "The script contexts match the golden file {}",
+            path.display()
+        );
+        return Ok(());
+    }
+
+    bail!(
+        "The script contexts differ from the golden file {}:\n\n{}",
+        path.display(),
+        mismatches.join("\n\n")
+    )
+}
+
+/// The cost models given with --cost-models, or else the provider's current ones.
+async fn resolve_cost_models(args: &Args, resolver: &dyn UtxoResolver) -> Result<CostModels> {
+    match &args.cost_models {
+        Some(path) => CostModels::load(path),
+        None => resolver
+            .cost_models()
+            .await
+            .context("Failed to fetch cost models. Use --cost-models"),
+    }
+}
+
+/// The languages of the scripts the transaction runs, and their encoded views as they enter the
+/// script integrity hash.
+async fn format_language_views(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+) -> Result<String> {
+    let redeemers = get_redeemers(transaction)?;
+    let redeemers: Vec<_> = redeemers.iter().map(Deref::deref).collect();
+    let languages = script_hash::languages(transaction, utxos, &redeemers);
+
+    let cost_models = resolve_cost_models(args, resolver).await?;
+    let encoded = protocol::encode_language_views(&cost_models, &languages)?;
+
+    let mut section = String::from("Language views (script integrity hash):");
+    if languages.is_empty() {
+        section.push_str("\n  (none, no Plutus script found for the redeemers)");
+    }
+    for language in &languages {
+        section.push_str(&format!(
+            "\n  {:?}: {} cost model parameters",
+            language,
+            cost_models.get(*language).map_or(0, <[i64]>::len)
+        ));
+    }
+    section.push_str(&format!("\n\nEncoded:\n{}", hex::encode(encoded)));
+
+    Ok(section)
+}
+
+/// The protocol parameters given with --protocol-params, or else the provider's current ones.
+async fn resolve_protocol_parameters(args: &Args, resolver: &dyn UtxoResolver) -> Result<Value> {
+    match &args.protocol_params {
+        Some(path) => protocol::load_parameters(path),
+        None => resolver
+            .protocol_parameters()
+            .await
+            .context("Failed to fetch the protocol parameters. Use --protocol-params"),
+    }
+}
+
+/// The execution units each redeemer declares, as a share of the limits per transaction, then
+/// their total against the limits per transaction and per block. Returns the report and the
+/// number of limits exceeded.
+async fn format_budget(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    transaction: &MintedTx<'_>,
+) -> Result<(String, usize)> {
+    let limits = ExecutionLimits::from_json(&resolve_protocol_parameters(args, resolver).await?)?;
+    let redeemers = get_redeemers(transaction)?;
+
+    let share = |used: u64, limit: u64| {
+        if limit == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}%", used as f64 * 100.0 / limit as f64)
+        }
+    };
+    let exceeded = |used: ExUnits, limit: ExUnits| {
+        [
+            (used.mem > limit.mem).then_some("mem"),
+            (used.steps > limit.steps).then_some("steps"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+    };
+    let flag = |exceeded: Vec<&str>| {
+        if exceeded.is_empty() {
+            String::new()
+        } else {
+            format!("  OVER THE LIMIT ({})", exceeded.join(", "))
+        }
+    };
+
+    let mut section = format!(
+        "Execution budget (per transaction: {} mem, {} steps):\n  {:<4} {:<12} {:>12} {:>7} {:>16} {:>7}",
+        limits.transaction.mem, limits.transaction.steps, "#", "Pointer", "Mem", "", "Steps", ""
+    );
+    let mut total = ExUnits::default();
+    for (position, redeemer) in redeemers.iter().enumerate() {
+        let used = ExUnits {
+            mem: redeemer.ex_units.mem,
+            steps: redeemer.ex_units.steps,
+        };
+        total.mem += used.mem;
+        total.steps += used.steps;
+
+        section.push_str(&format!(
+            "\n  {:<4} {:<12} {:>12} {:>7} {:>16} {:>7}{}",
+            position,
+            format!(
+                "{}:{}",
+                evaluate::redeemer_purpose(redeemer),
+                redeemer.index
+            ),
+            used.mem,
+            share(used.mem, limits.transaction.mem),
+            used.steps,
+            share(used.steps, limits.transaction.steps),
+            flag(exceeded(used, limits.transaction))
+        ));
+    }
+
+    section.push_str(&format!(
+        "\n  {:<17} {:>12} {:>7} {:>16} {:>7}{}",
+        "Total",
+        total.mem,
+        share(total.mem, limits.transaction.mem),
+        total.steps,
+        share(total.steps, limits.transaction.steps),
+        flag(exceeded(total, limits.transaction))
+    ));
+    section.push_str(&format!(
+        "\n  {:<17} {:>12} {:>7} {:>16} {:>7}{}",
+        "Block limit",
+        limits.block.mem,
+        share(total.mem, limits.block.mem),
+        limits.block.steps,
+        share(total.steps, limits.block.steps),
+        flag(exceeded(total, limits.block))
+    ));
+
+    // A redeemer over the limits puts the total over them too.
+    let overruns = exceeded(total, limits.transaction).len() + exceeded(total, limits.block).len();
+
+    Ok((section, overruns))
+}
+
+/// A table of every redeemer of the transaction: its pointer, the hash of the script it runs and
+/// where that script is provided.
+fn format_redeemer_scripts(transaction: &MintedTx<'_>, utxos: &ResolvedUtxos) -> Result<String> {
+    let redeemers = get_redeemers(transaction)?;
+
+    let mut section = format!(
+        "Redeemer scripts:\n  {:<4} {:<12} {:<56}  {}",
+        "#", "Pointer", "Script", "Source"
+    );
+    for (position, redeemer) in redeemers.iter().enumerate() {
+        let pointer = format!(
+            "{}:{}",
+            evaluate::redeemer_purpose(redeemer),
+            redeemer.index
+        );
+        let (hash, source) = match script_hash::target_script_hash(transaction, utxos, redeemer) {
+            Some(hash) => (
+                hex::encode(hash),
+                script_hash::script_source(transaction, utxos, &hash)
+                    .map(|source| source.to_string())
+                    .unwrap_or_else(|| "missing, neither witnessed nor referenced".to_string()),
+            ),
+            None => (
+                "-".to_string(),
+                "no script at this pointer (out of range, key credential or unresolved input)"
+                    .to_string(),
+            ),
+        };
+        section.push_str(&format!(
+            "\n  {:<4} {:<12} {:<56}  {}",
+            position, pointer, hash, source
+        ));
+    }
+
+    Ok(section)
+}
+
+/// The deposits set by the protocol parameters, fetched only when the transaction has
+/// certificates or proposals. Without them, the deposits the transaction doesn't state are left
+/// out of the balance.
+async fn resolve_deposit_parameters(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    transaction: &MintedTx<'_>,
+) -> DepositParameters {
+    let body = &transaction.transaction_body;
+    if body.certificates.is_none() && body.proposal_procedures.is_none() {
+        return DepositParameters::default();
+    }
+
+    match resolve_protocol_parameters(args, resolver).await {
+        Ok(parameters) => DepositParameters::from_json(&parameters),
+        Err(e) => {
+            color::warning(format!("Failed to find the deposit amounts: {:#}", e));
+            DepositParameters::default()
+        }
+    }
+}
+
+/// The minimum fee of the transaction part by part, as the ledger computes it: the size of the
+/// transaction, the execution units its redeemers declare and the reference scripts it uses.
+/// Returns the report and how far the declared fee falls short of the minimum.
+async fn format_fee(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    tx_bytes: &[u8],
+) -> Result<(String, u64)> {
+    let parameters = resolve_protocol_parameters(args, resolver).await?;
+    let fees = FeeParameters::from_json(&parameters)?;
+
+    let size = tx_bytes.len() as u64;
+    let size_fee = fees.size_fee(size);
+
+    let mut units = ExUnits::default();
+    for redeemer in get_redeemers(transaction)?.iter() {
+        units.mem += redeemer.ex_units.mem;
+        units.steps += redeemer.ex_units.steps;
+    }
+    let script_fee = fees.script_fee(units);
+
+    // Parameters from before Conway have no reference script price, which only matters when the
+    // transaction uses reference scripts.
+    let reference_size = reference_scripts_size(transaction, utxos);
+    let (reference_fee, reference_price) = if reference_size == 0 {
+        (0, String::new())
+    } else {
+        let cost = match args.ref_script_cost_per_byte {
+            Some(cost) => cost,
+            None => protocol::reference_script_cost_per_byte(&parameters)?,
+        };
+        (
+            protocol::reference_script_fee(reference_size, cost),
+            format!(" at {} lovelace per byte and up", cost),
+        )
+    };
+
+    let minimum = size_fee + script_fee + reference_fee;
+    let declared = transaction.transaction_body.fee;
+
+    let mut section = String::from("Fee breakdown:");
+    section.push_str(&format!(
+        "\n  {:<19} {} bytes x {} + {} = {} lovelace",
+        "Size:", size, fees.per_byte, fees.fixed, size_fee
+    ));
+    section.push_str(&format!(
+        "\n  {:<19} {} mem x {} + {} steps x {} = {} lovelace",
+        "Execution units:", units.mem, fees.price_mem, units.steps, fees.price_steps, script_fee
+    ));
+    section.push_str(&format!(
+        "\n  {:<19} {} bytes{} = {} lovelace",
+        "Reference scripts:", reference_size, reference_price, reference_fee
+    ));
+    section.push_str(&format!("\n  {:<19} {} lovelace", "Minimum fee:", minimum));
+
+    let shortfall = minimum.saturating_sub(declared);
+    let comparison = if shortfall > 0 {
+        format!("{} lovelace BELOW the minimum", shortfall)
+    } else {
+        format!("{} lovelace above the minimum", declared - minimum)
+    };
+    section.push_str(&format!(
+        "\n  {:<19} {} lovelace ({})",
+        "Declared fee:", declared, comparison
+    ));
+
+    // A fee estimated without a part falls short by about that part: tell what the shortfall
+    // amounts to in bytes, and which of the other parts could be missing from the estimate.
+    if shortfall > 0 {
+        let bytes = shortfall.div_ceil(fees.per_byte.max(1));
+        section.push_str(&format!(
+            "\n\n  The shortfall is the price of {} byte(s) of transaction size (about {} signature(s))",
+            bytes,
+            bytes.div_ceil(101)
+        ));
+        for (part, fee) in [
+            ("execution units", script_fee),
+            ("reference scripts", reference_fee),
+        ] {
+            if fee >= shortfall {
+                section.push_str(&format!(
+                    "\n  or of less than the {} part ({} lovelace), if the fee estimate leaves it out",
+                    part, fee
+                ));
+            }
+        }
+    }
+
+    let signed = RawTransaction::parse(tx_bytes)
+        .is_ok_and(|raw| raw.witness_set.contains_key(&transaction::VKEY_WITNESSES));
+    if !signed {
+        section.push_str(&format!(
+            "\n\n  The transaction carries no signature yet: each one adds about 101 bytes, so {} lovelace, to the minimum",
+            101 * fees.per_byte
+        ));
+    }
+
+    Ok((section, shortfall))
+}
+
+/// Total size of the reference scripts carried by the inputs and reference inputs, native ones
+/// included, as Conway prices and caps them.
+fn reference_scripts_size(transaction: &MintedTx<'_>, utxos: &ResolvedUtxos) -> u64 {
+    collect_all_inputs(transaction)
+        .iter()
+        .filter_map(|input| utxos.get(input)?.script.as_ref())
+        .map(reference_script_size)
+        .sum()
+}
+
+/// Size of a reference script as the ledger counts it: the bytes of a Plutus script, or the
+/// original CBOR of a native script, which its memoized form encodes back as is.
+fn reference_script_size(script: &MemoizedScript) -> u64 {
+    match script {
+        MemoizedScript::PlutusV1Script(script) => script.0.len() as u64,
+        MemoizedScript::PlutusV2Script(script) => script.0.len() as u64,
+        MemoizedScript::PlutusV3Script(script) => script.0.len() as u64,
+        MemoizedScript::NativeScript(script) => to_cbor(script).len() as u64,
+    }
+}
+
+/// The reference scripts carried by the inputs and reference inputs, and the fee Conway charges
+/// for their total size. Scripts are listed even when the price per byte can't be found.
+async fn format_reference_scripts(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+) -> String {
+    let reference_inputs: Vec<_> = transaction
+        .transaction_body
+        .reference_inputs
+        .as_deref()
+        .map(|set| set.to_vec())
+        .unwrap_or_default();
+
+    let mut section = String::from("Reference scripts:");
+    let mut total = 0;
+    let mut scripts = 0;
+    for input in collect_all_inputs(transaction) {
+        let Some(script) = utxos.get(&input).and_then(|output| output.script.as_ref()) else {
+            continue;
+        };
+        let kind = if reference_inputs.contains(&input) {
+            "reference input"
+        } else {
+            "input"
+        };
+
+        let (language, hash) = match script {
+            MemoizedScript::PlutusV1Script(script) => ("PlutusV1", script.compute_hash()),
+            MemoizedScript::PlutusV2Script(script) => ("PlutusV2", script.compute_hash()),
+            MemoizedScript::PlutusV3Script(script) => ("PlutusV3", script.compute_hash()),
+            MemoizedScript::NativeScript(script) => ("Native", script.compute_hash()),
+        };
+        let size = reference_script_size(script);
+
+        scripts += 1;
+        total += size;
+        section.push_str(&format!(
+            "\n  {} ({}): {} {}, {} bytes",
+            input.format_readable(),
+            kind,
+            language,
+            hash,
+            size
+        ));
+    }
+
+    if scripts == 0 {
+        section.push_str("\n  (none)");
+        return section;
+    }
+
+    section.push_str(&format!(
+        "\n\n  Total size: {} bytes (at most {} per transaction)",
+        total,
+        protocol::MAX_REFERENCE_SCRIPTS_SIZE
+    ));
+    if total > protocol::MAX_REFERENCE_SCRIPTS_SIZE {
+        section.push_str(
+            "\n  The ledger rejects transactions over this size (ConwayTxRefScriptsSizeTooBig)",
+        );
+    }
+
+    let cost_per_byte = match args.ref_script_cost_per_byte {
+        Some(cost) => Ok(cost),
+        None => resolve_protocol_parameters(args, resolver)
+            .await
+            .and_then(|parameters| protocol::reference_script_cost_per_byte(&parameters)),
+    };
+    match cost_per_byte {
+        Ok(cost) => section.push_str(&format!(
+            "\n  Fee: {} lovelace ({} lovelace per byte, 1.2 times more for each 25600 bytes), part of the minimum fee of the transaction",
+            protocol::reference_script_fee(total, cost),
+            cost
         )),
+        Err(e) => section.push_str(&format!("\n  Fee: unknown, {:#}", e)),
+    }
+    section
+}
+
+/// The datum given with --datum, as a file or hex-encoded CBOR.
+fn parse_datum(args: &Args) -> Result<Option<PlutusData>> {
+    let Some(datum) = &args.datum else {
+        return Ok(None);
+    };
+
+    let path = Path::new(datum);
+    let bytes = if path.is_file() {
+        read_cbor_file(path, "datum")?
+    } else {
+        hex::decode(datum.trim()).context("--datum is neither a file nor valid hex")?
+    };
+
+    cbor::decode(&bytes)
+        .map(Some)
+        .context("Failed to decode --datum as PlutusData")
+}
+
+/// Witness-set fields supplied in their own files, replacing those of the transaction. Fields
+/// given individually take precedence over those of --witness-file.
+fn witness_fields(args: &Args) -> Result<WitnessFields> {
+    let mut fields = WitnessFields::new();
+
+    if let Some(path) = &args.witness_file {
+        let witness_set = read_cbor_file(path, "witness set")?;
+        fields = transaction::parse_witness_set(&witness_set)
+            .with_context(|| format!("Invalid witness set: {}", path.display()))?;
+    }
+
+    if let Some(path) = &args.redeemers_file {
+        fields.insert(transaction::REDEEMERS, read_cbor_file(path, "redeemers")?);
+    }
+
+    if let Some(path) = &args.datums_file {
+        fields.insert(transaction::PLUTUS_DATA, read_cbor_file(path, "datums")?);
+    }
+
+    Ok(fields)
+}
+
+/// Resolve the outputs spent or referenced by a transaction, applying overrides, and the datums
+/// they point to. With --allow-missing-utxos, also returns the inputs given a placeholder output.
+async fn resolve_transaction(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    transaction: &MintedTx<'_>,
+) -> Result<(ResolvedUtxos, ResolvedDatums, BTreeSet<TransactionInput>)> {
+    let overrides = args
+        .override_utxos
+        .iter()
+        .map(|utxo_override| resolver::parse_utxo_override(utxo_override))
+        .collect::<Result<ResolvedUtxos>>()?;
+
+    let all_inputs: Vec<_> = collect_all_inputs(transaction)
+        .into_iter()
+        .filter(|input| !overrides.contains_key(input))
+        .collect();
+
+    let (mut utxos, placeholders) = if args.allow_missing_utxos {
+        resolver::resolve_with_placeholders(resolver, &all_inputs, *args.network).await?
+    } else {
+        (
+            resolver::resolve_all(resolver, &all_inputs).await?,
+            BTreeSet::new(),
+        )
+    };
+    utxos.extend(overrides);
+
+    // Collateral isn't part of any context, so it's only resolved to be shown alongside it, and
+    // failing to do so isn't fatal.
+    let collateral: Vec<_> = collateral_inputs(transaction)
+        .into_iter()
+        .filter(|input| !utxos.contains_key(input))
+        .collect();
+    if !collateral.is_empty() {
+        match resolver.resolve(&collateral).await {
+            Ok(resolved) => utxos.extend(resolved),
+            Err(e) => color::warning(format!("Failed to resolve the collateral inputs: {:#}", e)),
+        }
+    }
+
+    let datums = if args.resolve_datums {
+        resolver::resolve_datums(resolver, &utxos).await?
+    } else {
+        ResolvedDatums::new()
+    };
+
+    if let Some(source) = &args.token_registry {
+        token_registry::load(source, &token_registry::subjects(transaction, &utxos)).await?;
+    }
+
+    Ok((utxos, datums, placeholders))
+}
+
+/// Warn about what makes the ledger reject the transaction although its script contexts look
+/// fine: addresses from another network, and needed scripts it doesn't provide.
+fn warn_about_transaction(args: &Args, transaction: &MintedTx<'_>, utxos: &ResolvedUtxos) {
+    for mismatch in network::mismatches(*args.network, transaction, utxos) {
+        color::warning(format!("Network mismatch: {}", mismatch.description));
+    }
+
+    for (what, hash) in missing_scripts(transaction, utxos) {
+        color::warning(format!(
+            "Missing script: {} needs script {}, which is neither witnessed nor referenced (MissingScriptWitnessesUTXOW)",
+            what,
+            hex::encode(hash)
+        ));
+    }
+}
+
+/// The scripts the transaction needs but neither witnesses nor references, with what needs them.
+fn missing_scripts(transaction: &MintedTx<'_>, utxos: &ResolvedUtxos) -> Vec<(String, Hash<28>)> {
+    script_hash::required_scripts(transaction, utxos)
+        .into_iter()
+        .filter(|(_, hash)| script_hash::script_source(transaction, utxos, hash).is_none())
+        .collect()
+}
+
+/// Everything a script context is built from, besides the redeemer and the Plutus version.
+struct ContextInputs<'a> {
+    transaction: &'a MintedTx<'a>,
+    utxos: &'a ResolvedUtxos,
+    datums: &'a ResolvedDatums,
+    /// Replaces the datum of the spent output for spending redeemers, given with --datum.
+    datum: Option<&'a PlutusData>,
+    network: NetworkNameAdapter,
+    slot: u64,
+    /// Unknown when neither --protocol-version nor the provider tell it, in which case
+    /// era-conditional rules aren't checked.
+    protocol_version: Option<ProtocolVersion>,
+}
+
+/// Build the script context of each selected redeemer, reusing a single resolution pass.
+/// Redeemers of PlutusV2 scripts are skipped with a warning, as their contexts aren't built yet.
+fn build_script_contexts(
+    args: &Args,
+    inputs: &ContextInputs<'_>,
+    redeemers: &[(usize, &Redeemer)],
+) -> Result<Vec<RedeemerContext>> {
+    let blueprint = args
+        .blueprint
+        .as_deref()
+        .map(blueprint::Blueprint::load)
+        .transpose()?;
+
+    redeemers
+        .iter()
+        .filter_map(|&(position, redeemer)| {
+            let version = plutus_version(args, inputs.transaction, inputs.utxos, redeemer);
+            if version == PlutusVersion::PlutusV2 {
+                color::warning(format!(
+                    "Redeemer {}: nawi can't build PlutusV2 contexts yet, so it is skipped",
+                    position
+                ));
+                return None;
+            }
+            Some((position, redeemer, version))
+        })
+        .map(|(position, redeemer, version)| {
+            let (mut pretty_context, mut json_context, plutus_data) =
+                with_sections(&args.only, &args.skip, || build_script_context(version, inputs, redeemer))
+                .with_context(|| {
+                    format!(
+                        "Failed to build the script context of redeemer {}",
+                        position
+                    )
+                })?;
+
+            roundtrip::verify(&plutus_data).with_context(|| {
+                format!(
+                    "The script context of redeemer {} doesn't survive a CBOR round trip",
+                    position
+                )
+            })?;
+
+            if !args.explain_translation {
+                for warning in translation::byron_warnings(inputs) {
+                    color::warning(format!("Redeemer {}: {}", position, warning));
+                }
+            }
+
+            let divergences = canonical::check(version, &plutus_data);
+            if args.canonical && !divergences.is_empty() {
+                bail!(
+                    "The script context of redeemer {} isn't ordered as the ledger orders it:\n  {}",
+                    position,
+                    divergences.join("\n  ")
+                );
+            }
+            for divergence in divergences {
+                color::warning(format!("Redeemer {} is not ordered as the ledger orders it, {}",
+                    position, divergence));
+            }
+
+            let datum = extract_datum(inputs, redeemer)?;
+            if let Some(blueprint) = &blueprint
+                && let Some(hash) =
+                    script_hash::target_script_hash(inputs.transaction, inputs.utxos, redeemer)
+            {
+                match blueprint.describe(&hash, redeemer.tag, datum.as_ref(), &redeemer.data) {
+                    Some(described) => pretty_context.push_str(&format!("\n\n{}", described)),
+                    None => color::warning(format!(
+                        "Redeemer {}: the blueprint has no validator with hash {}",
+                        position,
+                        hex::encode(hash)
+                    )),
+                }
+            }
+
+            if let Some(script_inputs) = format_script_inputs(inputs) {
+                pretty_context.push_str(&format!("\n\n{}", script_inputs));
+            }
+
+            if let Some(metadata) = token_metadata::describe(inputs) {
+                pretty_context.push_str(&format!("\n\n{}", metadata));
+            }
+
+            if let Some(metadata) = metadata::describe(inputs.transaction) {
+                pretty_context.push_str(&format!("\n\n{}", metadata));
+            }
+
+            if let Some(collateral) = format_collateral(inputs) {
+                pretty_context.push_str(&format!("\n\n{}", collateral));
+            }
+
+            if args.explain_translation {
+                let notes = translation::explain(version, inputs, redeemer);
+                pretty_context.push_str(&format!("\n\nTranslation notes ({:?}):", version));
+                for note in &notes {
+                    pretty_context.push_str(&format!("\n  - {}", note));
+                }
+                json_context["translationNotes"] = json!(notes);
+            }
+
+            Ok(RedeemerContext {
+                redeemer: position,
+                purpose: format!("{:?}", redeemer.tag),
+                index: redeemer.index,
+                version,
+                pretty_context,
+                json_context,
+                plutus_data,
+                datum,
+                redeemer_data: redeemer.data.clone(),
+            })
+        })
+        .collect()
+}
+
+/// One line per redeemer for --summary, each flagged with whether its context could be built. A
+/// redeemer that fails doesn't keep the others from being summarized.
+fn summary_lines(
+    args: &Args,
+    inputs: &ContextInputs<'_>,
+    redeemers: &[(usize, &Redeemer)],
+) -> Vec<(String, bool)> {
+    redeemers
+        .iter()
+        .map(|&(position, redeemer)| {
+            let pointer = format!(
+                "{}:{}",
+                evaluate::redeemer_purpose(redeemer),
+                redeemer.index
+            );
+            let script =
+                script_hash::target_script_hash(inputs.transaction, inputs.utxos, redeemer)
+                    .map(hex::encode)
+                    .unwrap_or_else(|| "-".to_string());
+
+            let (outcome, ok) = match build_script_contexts(args, inputs, &[(position, redeemer)]) {
+                Ok(contexts) if contexts.is_empty() => (
+                    "skipped  PlutusV2 contexts aren't built yet".to_string(),
+                    true,
+                ),
+                Ok(contexts) => (
+                    contexts
+                        .iter()
+                        .map(|context| {
+                            format!(
+                                "ok  {:?}  {} bytes",
+                                context.version,
+                                to_cbor(&context.plutus_data).len()
+                            )
+                        })
+                        .collect::<String>(),
+                    true,
+                ),
+                Err(e) => (
+                    format!("FAILED  {}", format!("{:#}", e).replace('\n', " ")),
+                    false,
+                ),
+            };
+
+            let line = format!(
+                "#{:<3} {:<10} {:<56}  {} mem  {} steps  {}",
+                position, pointer, script, redeemer.ex_units.mem, redeemer.ex_units.steps, outcome
+            );
+            (line, ok)
+        })
+        .collect()
+}
+
+/// The Plutus version to build a redeemer's context for: the one given with --plutus-version, or
+/// else the language of the script the redeemer executes, defaulting to PlutusV3.
+fn plutus_version(
+    args: &Args,
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    redeemer: &Redeemer,
+) -> PlutusVersion {
+    let inferred = script_hash::target_script_hash(transaction, utxos, redeemer)
+        .and_then(|hash| script_hash::script_version(transaction, utxos, &hash));
+
+    match (args.plutus_version, inferred) {
+        (Some(requested), Some(inferred)) if requested != inferred => {
+            color::warning(format!(
+                "Building a {:?} context for a {:?} redeemer whose script is {:?}",
+                requested, redeemer.tag, inferred
+            ));
+            requested
+        }
+        (Some(requested), _) => requested,
+        (None, Some(inferred)) => inferred,
+        (None, None) => {
+            color::warning(format!(
+                "Could not find the script executed by the {:?} redeemer at index {}. Assuming PlutusV3; use --plutus-version to override",
+                redeemer.tag, redeemer.index
+            ));
+            PlutusVersion::default()
+        }
+    }
+}
+
+async fn resolve_slot(
+    args: &Args,
+    resolver: &dyn UtxoResolver,
+    transaction: &MintedTx<'_>,
+) -> Result<u64> {
+    match args.slot {
+        Some(SlotSelector::Number(slot)) => Ok(slot),
+        Some(SlotSelector::Tip) => resolver.tip().await,
+        Some(SlotSelector::Validity) => validity_slot(transaction).ok_or_else(|| {
+            anyhow!("Transaction has no validity interval. Use --slot <SLOT> or --slot tip")
+        }),
+        None => match validity_slot(transaction) {
+            Some(slot) => Ok(slot),
+            None => resolver.tip().await,
+        },
+    }
+}
+
+/// The protocol version given with --protocol-version, or else the one of the protocol
+/// parameters given with --protocol-params. The provider's current version isn't assumed, as it
+/// may not be the one in force at the transaction's slot.
+fn resolve_protocol_version(args: &Args) -> Result<Option<ProtocolVersion>> {
+    if let Some(major) = args.protocol_version {
+        return Ok(Some(ProtocolVersion(major)));
+    }
+
+    match &args.protocol_params {
+        Some(path) => {
+            Ok(protocol::major_version(&protocol::load_parameters(path)?).map(ProtocolVersion))
+        }
+        None => Ok(None),
+    }
+}
+
+/// A slot the transaction is valid at: the start of its validity interval, or else the last
+/// slot before it expires.
+fn validity_slot(transaction: &MintedTx<'_>) -> Option<u64> {
+    let body = &transaction.transaction_body;
+
+    body.validity_interval_start
+        .or_else(|| body.ttl.map(|ttl| ttl.saturating_sub(1)))
+}
+
+/// Keys under which JSON payloads embed CBOR as hex, in order of preference: cardano-cli text
+/// envelopes (`cborHex`), Blockfrost and Ogmios v6 (`cbor`, under `transaction` for the latter)
+/// and Ogmios v5 (`submit`).
+const EMBEDDED_CBOR_KEYS: [&str; 4] = ["cborHex", "cbor", "transaction", "submit"];
+
+/// Find the hex-encoded CBOR inside a JSON payload pasted verbatim.
+fn find_embedded_cbor(value: &Value) -> Option<&str> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+
+    EMBEDDED_CBOR_KEYS
+        .iter()
+        .filter_map(|key| object.get(*key))
+        .find_map(|value| match value {
+            Value::String(hex) if is_hex_text(hex.as_bytes()) => Some(hex.as_str()),
+            _ => None,
+        })
+        .or_else(|| object.values().find_map(find_embedded_cbor))
+}
+
+/// Whether a file holds hex text rather than raw CBOR. Transactions, blocks and witness sets
+/// always start with an array or map header (0x80-0xbf), which is never an ASCII hex digit.
+fn is_hex_text(contents: &[u8]) -> bool {
+    let text = contents.trim_ascii();
+    !text.is_empty() && text.iter().all(u8::is_ascii_hexdigit)
+}
+
+async fn load_transaction_bytes(args: &Args, resolver: &dyn UtxoResolver) -> Result<Vec<u8>> {
+    if let Some(path) = &args.tx_file {
+        return read_cbor_file(path, "transaction");
+    }
+
+    if let Some(hex_str) = &args.bytes {
+        return decode_cbor_text(hex_str.as_bytes())?.map_or_else(
+            || {
+                hex::decode(hex_str.trim()).context(
+                    "Failed to decode hex string. Ensure it contains valid hexadecimal characters",
+                )
+            },
+            Ok,
+        );
+    }
+
+    if let Some(tx_hash) = &args.tx_hash {
+        let hash = parse_hash(tx_hash, "transaction")?;
+
+        return if args.mempool {
+            resolver
+                .fetch_mempool_transaction(&hash)
+                .await?
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Transaction {} is not in the mempool of {}. It may have been included in a block or expired",
+                        tx_hash,
+                        resolver.name()
+                    )
+                })
+        } else {
+            fetch_transaction(resolver, &hash).await
+        };
+    }
+
+    let tx_index = args
+        .tx_index
+        .ok_or_else(|| anyhow!("--tx-index is required to pick a transaction from a block"))?;
+
+    if let Some(path) = &args.block_file {
+        let block = read_cbor_file(path, "block")?;
+        return block::extract_transaction(&block, tx_index)
+            .with_context(|| format!("Failed to read block file: {}", path.display()));
+    }
+
+    if let Some(block_hash) = &args.block_hash {
+        let hash = parse_hash(block_hash, "block")?;
+        let transactions = resolver.block_transactions(&hash).await?;
+        let tx_hash = transactions.get(tx_index).ok_or_else(|| {
+            anyhow!(
+                "Invalid transaction index {}. Block {} has {} transaction(s)",
+                tx_index,
+                block_hash,
+                transactions.len()
+            )
+        })?;
+        return fetch_transaction(resolver, tx_hash).await;
+    }
+
+    Err(anyhow!(
+        "No input provided. Use either --tx-file, --bytes, --tx-hash, --block-file or --block-hash"
+    ))
+}
+
+async fn fetch_transaction(resolver: &dyn UtxoResolver, hash: &Hash<32>) -> Result<Vec<u8>> {
+    resolver.fetch_transaction(hash).await?.ok_or_else(|| {
+        anyhow!(
+            "Transaction {} was not found by {}. If it is still pending, try --mempool",
+            hex::encode(hash),
+            resolver.name()
+        )
+    })
+}
+
+fn parse_hash(hash: &str, what: &str) -> Result<Hash<32>> {
+    hash.trim()
+        .parse()
+        .map_err(|_| anyhow!("Invalid {} hash: {}", what, hash))
+}
+
+/// Read CBOR from a file holding raw bytes, hex text, or a JSON payload embedding it (cardano-cli
+/// text envelopes, Ogmios and Blockfrost requests).
+fn read_cbor_file(path: &Path, what: &str) -> Result<Vec<u8>> {
+    let contents = std::fs::read(path)
+        .with_context(|| format!("Failed to read {} file: {}", what, path.display()))?;
+
+    decode_cbor_text(&contents)
+        .with_context(|| format!("Invalid {} file: {}", what, path.display()))
+        .map(|decoded| decoded.unwrap_or(contents))
+}
+
+/// Decode CBOR written out as hex text or embedded in JSON. Returns `None` for anything else,
+/// which is assumed to be raw CBOR.
+fn decode_cbor_text(contents: &[u8]) -> Result<Option<Vec<u8>>> {
+    if let Ok(json @ Value::Object(_)) = serde_json::from_slice::<Value>(contents) {
+        let hex = find_embedded_cbor(&json).ok_or_else(|| {
+            anyhow!("No CBOR found in JSON. Expected a cborHex, cbor or submit field")
+        })?;
+        return hex::decode(hex.trim())
+            .map(Some)
+            .context("Failed to decode CBOR hex embedded in JSON");
     }
+
+    if is_hex_text(contents) {
+        return hex::decode(contents.trim_ascii())
+            .map(Some)
+            .context("Failed to decode hex text");
+    }
+
+    Ok(None)
 }
 
 fn decode_transaction(tx_bytes: &[u8]) -> Result<MintedTx<'_>> {
     cbor::decode(tx_bytes).context(
-        "Failed to decode transaction. Ensure the CBOR data is a valid Cardano transaction",
+        "Failed to decode transaction. Ensure the CBOR data is a valid Cardano transaction, or use --lenient to locate the failing field",
     )
 }
 
+/// With --lenient, drop the fields that fail to decode, warning about each of them.
+fn repair_if_lenient(args: &Args, tx_bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if !args.lenient || decode_transaction(&tx_bytes).is_ok() {
+        return Ok(tx_bytes);
+    }
+
+    let (repaired, problems) = lenient::repair(&tx_bytes)?;
+    for problem in problems {
+        color::warning(format!("Failed to decode {}", problem));
+    }
+
+    Ok(repaired)
+}
+
 fn collect_all_inputs(transaction: &MintedTx) -> Vec<TransactionInput> {
     let regular_inputs = transaction.transaction_body.inputs.deref().as_slice();
     let ref_inputs = transaction
@@ -205,38 +2136,165 @@ fn collect_all_inputs(transaction: &MintedTx) -> Vec<TransactionInput> {
     [regular_inputs, ref_inputs].concat()
 }
 
+fn collateral_inputs(transaction: &MintedTx) -> Vec<TransactionInput> {
+    transaction
+        .transaction_body
+        .collateral
+        .as_deref()
+        .map(|set| set.to_vec())
+        .unwrap_or_default()
+}
+
+/// Every input locked by a script, in the ledger's order (the one spending redeemers point
+/// into): the hash of the script from its address, and where the transaction provides it.
+fn format_script_inputs(inputs: &ContextInputs<'_>) -> Option<String> {
+    let mut spent: Vec<_> = inputs.transaction.transaction_body.inputs.iter().collect();
+    spent.sort();
+
+    let mut lines = Vec::new();
+    for (index, input) in spent.into_iter().enumerate() {
+        let Some(output) = inputs.utxos.get(input) else {
+            continue;
+        };
+        let Address::Shelley(address) = &output.address else {
+            continue;
+        };
+        let ShelleyPaymentPart::Script(hash) = address.payment() else {
+            continue;
+        };
+
+        let source = script_hash::script_source(inputs.transaction, inputs.utxos, hash)
+            .map(|source| source.to_string())
+            .unwrap_or_else(|| "missing, neither witnessed nor referenced".to_string());
+        lines.push(format!(
+            "  [{}] {}\n      Script: {}\n      Source: {}",
+            index,
+            input.format_readable(),
+            hex::encode(hash),
+            source
+        ));
+    }
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    Some(format!("Script inputs:\n{}", lines.join("\n")))
+}
+
+/// The collateral fields of the transaction body, which no Plutus version puts in the TxInfo:
+/// the collateral inputs with the outputs they resolve to, the collateral return and the total
+/// collateral.
+fn format_collateral(inputs: &ContextInputs<'_>) -> Option<String> {
+    let body = &inputs.transaction.transaction_body;
+    let collateral = collateral_inputs(inputs.transaction);
+    if collateral.is_empty() && body.collateral_return.is_none() && body.total_collateral.is_none()
+    {
+        return None;
+    }
+
+    let mut result = String::from("Collateral (not part of the TxInfo):");
+
+    result.push_str(&format!("\n  Inputs: {} input(s)", collateral.len()));
+    for (index, input) in collateral.iter().enumerate() {
+        match inputs.utxos.get(input) {
+            Some(output) => result.push_str(&format!(
+                "\n    [{}] {}\n{}",
+                index,
+                input.format_readable(),
+                indent_lines(&TransactionOutput::from(output).format_readable(), 8)
+            )),
+            None => result.push_str(&format!(
+                "\n    [{}] {} (unresolved)",
+                index,
+                input.format_readable()
+            )),
+        }
+    }
+
+    if let Some(output) = &body.collateral_return {
+        match MemoizedTransactionOutput::try_from(output.clone()) {
+            Ok(output) => result.push_str(&format!(
+                "\n  Return:\n{}",
+                indent_lines(&TransactionOutput::from(&output).format_readable(), 4)
+            )),
+            Err(_) => result.push_str("\n  Return: (undecodable output)"),
+        }
+    }
+
+    if let Some(total) = body.total_collateral {
+        result.push_str(&format!("\n  Total: {} lovelace", total));
+    }
+
+    Some(result)
+}
+
 fn get_redeemers<'a>(transaction: &'a MintedTx<'_>) -> Result<Vec<Cow<'a, Redeemer>>> {
     let redeemers = transaction
         .transaction_witness_set
         .redeemer
         .as_ref()
-        .ok_or_else(|| anyhow!("Transaction contains no redeemers"))?;
+        .ok_or_else(|| {
+            anyhow!("Transaction contains no redeemers. Pass --redeemers-file to supply them")
+        })?;
 
     Ok(normalize_redeemers(redeemers.deref()))
 }
 
-fn extract_datum(
-    transaction: &MintedTx,
-    utxos: &BTreeMap<TransactionInput, MemoizedTransactionOutput>,
+/// The output spent by a spending redeemer, which points into the sorted inputs.
+fn spent_output<'a>(
+    inputs: &ContextInputs<'a>,
     redeemer: &Redeemer,
-) -> Result<Option<PlutusData>> {
+) -> Result<Option<&'a MemoizedTransactionOutput>> {
     if !matches!(redeemer.tag, ScriptPurpose::Spend) {
         return Ok(None);
     }
 
-    let input = transaction
-        .transaction_body
-        .inputs
+    // Spending redeemers point into the inputs sorted by transaction id then index, whatever
+    // their order in the body.
+    let mut spent: Vec<_> = inputs.transaction.transaction_body.inputs.iter().collect();
+    spent.sort();
+    let input = spent
         .get(redeemer.index as usize)
+        .copied()
         .context("Invalid redeemer index for spending input")?;
 
-    let utxo = utxos
+    inputs
+        .utxos
         .get(input)
-        .context("Missing UTxO for spending input")?;
+        .map(Some)
+        .context("Missing UTxO for spending input")
+}
+
+/// The datum a hash commits to, taken from the transaction's own witnesses as the ledger does, or
+/// else from the datums resolved through the provider.
+fn datum_preimage(inputs: &ContextInputs<'_>, hash: &Hash<32>) -> Option<PlutusData> {
+    inputs
+        .transaction
+        .transaction_witness_set
+        .plutus_data
+        .iter()
+        .flat_map(|datums| datums.iter())
+        .find(|datum| datum.original_hash() == *hash)
+        .map(|datum| datum.deref().clone())
+        .or_else(|| inputs.datums.get(hash).cloned())
+}
+
+fn extract_datum(inputs: &ContextInputs<'_>, redeemer: &Redeemer) -> Result<Option<PlutusData>> {
+    let Some(utxo) = spent_output(inputs, redeemer)? else {
+        return Ok(None);
+    };
+
+    if let Some(datum) = inputs.datum {
+        return Ok(Some(datum.clone()));
+    }
 
     let datum = match &utxo.datum {
         MemoizedDatum::None => None,
-        MemoizedDatum::Hash(hash) => Some(PlutusData::BoundedBytes(hash.to_vec().into())),
+        MemoizedDatum::Hash(hash) => Some(
+            datum_preimage(inputs, hash)
+                .unwrap_or_else(|| PlutusData::BoundedBytes(hash.to_vec().into())),
+        ),
         amaru_kernel::MemoizedDatum::Inline(plutus_data) => Some(plutus_data.as_ref().clone()),
     };
 
@@ -245,14 +2303,23 @@ fn extract_datum(
 
 fn build_script_context(
     version: PlutusVersion,
-    transaction: &MintedTx,
-    utxos: &BTreeMap<TransactionInput, MemoizedTransactionOutput>,
+    inputs: &ContextInputs<'_>,
     redeemer: &Redeemer,
-    network: NetworkNameAdapter,
-    slot: u64,
-) -> Result<(String, PlutusData)> {
+) -> Result<(String, Value, PlutusData)> {
+    let ContextInputs {
+        transaction,
+        utxos,
+        slot,
+        ..
+    } = *inputs;
     let tx_hash = transaction.transaction_body.original_hash();
-    let network_name = NetworkName::from(network);
+    let network_name = NetworkName::from(inputs.network);
+    let validity_slots = (
+        transaction.transaction_body.validity_interval_start,
+        transaction.transaction_body.ttl,
+    );
+
+    translation::check(version, inputs, redeemer)?;
 
     match version {
         PlutusVersion::PlutusV1 => {
@@ -266,11 +2333,27 @@ fn build_script_context(
                 network_name.into(),
             )?;
 
+            // The ledger hands the datum to PlutusV1 scripts as a separate argument rather than
+            // in the context.
+            let datum = extract_datum(inputs, redeemer)?;
+
             let script_context: ScriptContextV1<'_> = ScriptContextV1::new(tx_info, redeemer)
                 .context("Failed to construct PlutusV1 script context")?;
 
+            let mut pretty_context =
+                with_validity_slots(validity_slots, || script_context.format_readable());
+            let mut json_context = script_context.to_json();
+            if let Some(datum) = &datum {
+                pretty_context.push_str(&format!(
+                    "\n\nDatum (passed to the script as a separate argument):\n{}",
+                    indent_lines(&datum.format_readable(), 2)
+                ));
+                json_context["datum"] = datum.to_json();
+            }
+
             Ok((
-                script_context.format_readable(),
+                pretty_context,
+                json_context,
                 <ScriptContextV1 as ToPlutusData<1>>::to_plutus_data(&script_context),
             ))
         }
@@ -278,7 +2361,7 @@ fn build_script_context(
             bail!("PlutusV2 is not yet implemented")
         }
         PlutusVersion::PlutusV3 => {
-            let datum = extract_datum(transaction, utxos, redeemer)?;
+            let datum = extract_datum(inputs, redeemer)?;
 
             let tx_info = TxInfoV3::new(
                 &transaction.transaction_body,
@@ -291,17 +2374,24 @@ fn build_script_context(
             )?;
 
             v3::ScriptContext::new(tx_info, redeemer, datum)
-                .map(|context| (context.format_readable(), context.to_plutus_data()))
+                .map(|context| {
+                    (
+                        with_validity_slots(validity_slots, || context.format_readable()),
+                        context.to_json(),
+                        context.to_plutus_data(),
+                    )
+                })
                 .context("Failed to construct PlutusV3 script context")
         }
     }
 }
 
-fn print_script_context(script_context: &PlutusData) {
+fn format_script_context(script_context: &PlutusData) -> String {
     let cbor_bytes = to_cbor(script_context);
-    let hex_string = hex::encode(&cbor_bytes);
 
-    println!("CBOR-encoded script context:");
-    println!("{}", hex_string);
-    println!("\nLength: {} bytes", cbor_bytes.len());
+    format!(
+        "CBOR-encoded script context:\n{}\n\nLength: {} bytes",
+        hex::encode(&cbor_bytes),
+        cbor_bytes.len()
+    )
 }
@@ -0,0 +1,191 @@
+use amaru_kernel::{
+    MintedTx, Nullable,
+    cbor::{Decoder, data::Type},
+};
+use anyhow::{Result, bail};
+
+use crate::{formatter::indent_lines, transaction::more};
+
+/// Metadata label of CIP-20 transaction messages.
+pub const CIP20_LABEL: u64 = 674;
+
+/// Metadata label of CIP-25 NFT metadata.
+pub const CIP25_LABEL: u64 = 721;
+
+/// Tag of post-Alonzo auxiliary data.
+const AUXILIARY_DATA_TAG: u64 = 259;
+
+/// The metadata of the transaction, by label, as the pretty output shows it after the context.
+/// No Plutus version hands it to scripts; they only see its hash through the body.
+pub fn describe(transaction: &MintedTx<'_>) -> Option<String> {
+    let Metadatum::Map(labels) = transaction_metadata(transaction)? else {
+        return None;
+    };
+    if labels.is_empty() {
+        return None;
+    }
+
+    let mut result = String::from("Metadata (not part of the TxInfo):");
+    for (label, value) in &labels {
+        let (name, body) = if is_label(label, CIP20_LABEL) {
+            (format!("{} (CIP-20 message)", CIP20_LABEL), message(value))
+        } else if is_label(label, CIP25_LABEL) {
+            (
+                format!("{} (CIP-25 NFT metadata)", CIP25_LABEL),
+                value.format(),
+            )
+        } else {
+            (label.format(), value.format())
+        };
+        result.push_str(&format!("\n  {}:\n{}", name, indent_lines(&body, 4)));
+    }
+
+    Some(result)
+}
+
+/// The lines of a CIP-20 message, `{ "msg": [text, ...] }`.
+fn message(value: &Metadatum) -> String {
+    match value.get(|key| matches!(key, Metadatum::Text(text) if text == "msg")) {
+        Some(Metadatum::List(lines)) => lines
+            .iter()
+            .map(Metadatum::format)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => value.format(),
+    }
+}
+
+/// The metadata map of the auxiliary data, in any of its Shelley, Mary or post-Alonzo forms.
+pub fn transaction_metadata(transaction: &MintedTx<'_>) -> Option<Metadatum> {
+    let Nullable::Some(auxiliary_data) = &transaction.auxiliary_data else {
+        return None;
+    };
+    let mut decoder = Decoder::new(auxiliary_data.raw_cbor());
+
+    match decoder.datatype().ok()? {
+        Type::Tag => {
+            if decoder.tag().ok()?.as_u64() != AUXILIARY_DATA_TAG {
+                return None;
+            }
+            Metadatum::decode(&mut decoder)
+                .ok()?
+                .take(|key| matches!(key, Metadatum::Int(0)))
+        }
+        Type::Array | Type::ArrayIndef => {
+            decoder.array().ok()?;
+            Metadatum::decode(&mut decoder).ok()
+        }
+        _ => Metadatum::decode(&mut decoder).ok(),
+    }
+}
+
+/// Whether a key of the metadata map is this label.
+pub fn is_label(key: &Metadatum, label: u64) -> bool {
+    matches!(key, Metadatum::Int(key) if *key == i128::from(label))
+}
+
+/// A transaction metadatum, decoded from the auxiliary data.
+pub enum Metadatum {
+    Int(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    List(Vec<Metadatum>),
+    Map(Vec<(Metadatum, Metadatum)>),
+}
+
+impl Metadatum {
+    pub fn decode(decoder: &mut Decoder<'_>) -> Result<Self> {
+        Ok(match decoder.datatype()? {
+            Type::Bytes | Type::BytesIndef => {
+                let mut bytes = Vec::new();
+                for chunk in decoder.bytes_iter()? {
+                    bytes.extend_from_slice(chunk?);
+                }
+                Metadatum::Bytes(bytes)
+            }
+            Type::String | Type::StringIndef => {
+                let mut text = String::new();
+                for chunk in decoder.str_iter()? {
+                    text.push_str(chunk?);
+                }
+                Metadatum::Text(text)
+            }
+            Type::Array | Type::ArrayIndef => {
+                let len = decoder.array()?;
+                let mut items = Vec::new();
+                while more(decoder, len, items.len() as u64)? {
+                    items.push(Metadatum::decode(decoder)?);
+                }
+                Metadatum::List(items)
+            }
+            Type::Map | Type::MapIndef => {
+                let len = decoder.map()?;
+                let mut entries = Vec::new();
+                while more(decoder, len, entries.len() as u64)? {
+                    entries.push((Metadatum::decode(decoder)?, Metadatum::decode(decoder)?));
+                }
+                Metadatum::Map(entries)
+            }
+            Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::I8
+            | Type::I16
+            | Type::I32
+            | Type::I64
+            | Type::Int => Metadatum::Int(i128::from(decoder.int()?)),
+            other => bail!("Unexpected {} in transaction metadata", other),
+        })
+    }
+
+    /// The value of the first entry of a map whose key matches.
+    pub fn take(self, key: impl Fn(&Metadatum) -> bool) -> Option<Metadatum> {
+        match self {
+            Metadatum::Map(entries) => entries
+                .into_iter()
+                .find(|(k, _)| key(k))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: impl Fn(&Metadatum) -> bool) -> Option<&Metadatum> {
+        match self {
+            Metadatum::Map(entries) => entries.iter().find(|(k, _)| key(k)).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Text keys and values as they read, lists of text joined (CIP-25 splits long URLs into
+    /// chunks of 64 bytes), and bytes in hex.
+    pub fn format(&self) -> String {
+        match self {
+            Metadatum::Int(int) => int.to_string(),
+            Metadatum::Bytes(bytes) => format!("0x{}", hex::encode(bytes)),
+            Metadatum::Text(text) => text.clone(),
+            Metadatum::List(items) if self.is_text_list() => {
+                items.iter().map(Metadatum::format).collect()
+            }
+            Metadatum::List(items) => items
+                .iter()
+                .map(|item| format!("- {}", indent_lines(&item.format(), 2).trim_start()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Metadatum::Map(entries) => entries
+                .iter()
+                .map(|(key, value)| match value {
+                    Metadatum::List(_) | Metadatum::Map(_) if !value.is_text_list() => {
+                        format!("{}:\n{}", key.format(), indent_lines(&value.format(), 2))
+                    }
+                    _ => format!("{}: {}", key.format(), value.format()),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    fn is_text_list(&self) -> bool {
+        matches!(self, Metadatum::List(items) if items.iter().all(|i| matches!(i, Metadatum::Text(_))))
+    }
+}
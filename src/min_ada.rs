@@ -0,0 +1,96 @@
+use amaru_kernel::{MemoizedTransactionOutput, MintedTx, to_cbor};
+use amaru_plutus::script_context::TransactionOutput;
+
+/// The bytes the ledger adds to the size of an output for the UTxO entry holding it.
+const ENTRY_OVERHEAD: u64 = 160;
+
+/// An output of the transaction against the lovelace its size requires.
+pub struct Output {
+    /// `output <index>`, or `the collateral return`.
+    pub name: String,
+    pub size: u64,
+    /// None when the output can't be decoded.
+    pub lovelace: Option<u64>,
+    pub minimum: u64,
+}
+
+impl Output {
+    /// How much lovelace the output lacks, zero when it holds enough.
+    pub fn shortfall(&self) -> u64 {
+        self.lovelace
+            .map_or(0, |lovelace| self.minimum.saturating_sub(lovelace))
+    }
+}
+
+/// The outputs of the transaction, then its collateral return, each with the minimum lovelace
+/// the ledger requires of it: (160 + its serialized size) × coinsPerUTxOByte.
+pub fn outputs(transaction: &MintedTx<'_>, coins_per_byte: u64) -> Vec<Output> {
+    let body = &transaction.transaction_body;
+    let outputs = body
+        .outputs
+        .iter()
+        .enumerate()
+        .map(|(index, output)| (format!("output {}", index), output));
+    let collateral_return = body
+        .collateral_return
+        .iter()
+        .map(|output| ("the collateral return".to_string(), output));
+
+    outputs
+        .chain(collateral_return)
+        .map(|(name, output)| {
+            let size = to_cbor(output).len() as u64;
+            let lovelace = MemoizedTransactionOutput::try_from(output.clone())
+                .ok()
+                .map(|output| {
+                    TransactionOutput::from(&output)
+                        .value
+                        .ada()
+                        .unwrap_or_default()
+                });
+            Output {
+                name,
+                size,
+                lovelace,
+                minimum: (ENTRY_OVERHEAD + size) * coins_per_byte,
+            }
+        })
+        .collect()
+}
+
+/// A table of every output with its size, its lovelace and the minimum the ledger requires of
+/// it, flagging those below. Returns the report and the number of outputs below their minimum.
+pub fn describe(transaction: &MintedTx<'_>, coins_per_byte: u64) -> (String, usize) {
+    let outputs = outputs(transaction, coins_per_byte);
+
+    let mut section = format!(
+        "Minimum lovelace per output (coinsPerUTxOByte {}):\n  {:<22} {:>10} {:>14} {:>14}",
+        coins_per_byte, "Output", "Size", "Lovelace", "Minimum"
+    );
+    let mut below = 0;
+    for output in &outputs {
+        let lovelace = output.lovelace.map_or_else(
+            || "undecodable".to_string(),
+            |lovelace| lovelace.to_string(),
+        );
+        let flag = match output.shortfall() {
+            0 => String::new(),
+            shortfall => {
+                below += 1;
+                format!("  BELOW THE MINIMUM by {} lovelace", shortfall)
+            }
+        };
+        section.push_str(&format!(
+            "\n  {:<22} {:>4} bytes {:>14} {:>14}{}",
+            output.name, output.size, lovelace, output.minimum, flag
+        ));
+    }
+    if below > 0 {
+        section.push_str(&format!(
+            "\n\n  {} output(s) below the minimum: the ledger rejects them (BabbageOutputTooSmallUTxO)",
+            below
+        ));
+    }
+
+    (section, below)
+}
@@ -0,0 +1,378 @@
+use std::collections::BTreeSet;
+
+use amaru_kernel::{
+    ComputeHash, Hash, Hasher, MemoizedScript, MintedTx, NativeScript, OriginalHash,
+};
+
+use crate::{collect_all_inputs, resolver::ResolvedUtxos, script_hash};
+
+/// What native scripts are evaluated against: the keys that signed the transaction and its
+/// validity interval.
+pub struct Conditions {
+    signatories: BTreeSet<Hash<28>>,
+    valid_from: Option<u64>,
+    valid_until: Option<u64>,
+}
+
+impl Conditions {
+    pub fn of(transaction: &MintedTx<'_>) -> Self {
+        let body = &transaction.transaction_body;
+        Self {
+            signatories: transaction
+                .transaction_witness_set
+                .vkeywitness
+                .iter()
+                .flat_map(|witnesses| witnesses.iter())
+                .map(|witness| Hasher::<224>::hash(&witness.vkey))
+                .collect(),
+            valid_from: body.validity_interval_start,
+            valid_until: body.ttl,
+        }
+    }
+}
+
+/// The outcome of a native script: whether it passes, each of its clauses as an indented line,
+/// and the signature and time bound clauses that make it fail.
+pub struct Evaluation {
+    pub passes: bool,
+    pub lines: Vec<String>,
+    pub failures: Vec<String>,
+}
+
+/// Evaluate a native script the way the ledger does: every clause of `all`, one of `any`, at
+/// least n of `n of k`, the signatures among the keys that signed the transaction, and the time
+/// bounds against its validity interval, which must lie within them.
+pub fn evaluate(script: &NativeScript, conditions: &Conditions) -> Evaluation {
+    let mut lines = Vec::new();
+    let (passes, failures) = clause(script, conditions, 1, &mut lines);
+    Evaluation {
+        passes,
+        lines,
+        failures,
+    }
+}
+
+/// Evaluate one clause, adding its line and those of its sub-clauses. Returns whether it passes
+/// and, when it doesn't, the failing signature and time bound clauses below it.
+fn clause(
+    script: &NativeScript,
+    conditions: &Conditions,
+    depth: usize,
+    lines: &mut Vec<String>,
+) -> (bool, Vec<String>) {
+    let line = lines.len();
+    lines.push(String::new());
+
+    // Every sub-clause is evaluated, to show them all: the number passing, and the failures of
+    // those that don't.
+    let children = |scripts: &[NativeScript], lines: &mut Vec<String>| {
+        let mut passing = 0;
+        let mut failures = Vec::new();
+        for script in scripts {
+            match clause(script, conditions, depth + 1, lines) {
+                (true, _) => passing += 1,
+                (false, failed) => failures.extend(failed),
+            }
+        }
+        (passing, failures)
+    };
+
+    let (passes, description, failures) = match script {
+        NativeScript::ScriptPubkey(hash) => {
+            let description = format!("signature of {}", hex::encode(hash));
+            if conditions.signatories.contains(hash) {
+                (true, description, Vec::new())
+            } else {
+                let description = format!("{}, missing", description);
+                (false, description.clone(), vec![description])
+            }
+        }
+        NativeScript::ScriptAll(scripts) => {
+            let (passing, failures) = children(scripts, lines);
+            (
+                passing == scripts.len(),
+                format!("all of {} ({} pass)", scripts.len(), passing),
+                failures,
+            )
+        }
+        NativeScript::ScriptAny(scripts) => {
+            let (passing, failures) = children(scripts, lines);
+            (
+                passing > 0,
+                format!("any of {} ({} pass)", scripts.len(), passing),
+                failures,
+            )
+        }
+        NativeScript::ScriptNOfK(required, scripts) => {
+            let (passing, failures) = children(scripts, lines);
+            (
+                passing >= *required as usize,
+                format!(
+                    "at least {} of {} ({} pass)",
+                    required,
+                    scripts.len(),
+                    passing
+                ),
+                failures,
+            )
+        }
+        NativeScript::InvalidBefore(slot) => {
+            let (passes, description) = match conditions.valid_from {
+                Some(start) if start >= *slot => (
+                    true,
+                    format!("not before slot {} (valid from slot {})", slot, start),
+                ),
+                Some(start) => (
+                    false,
+                    format!(
+                        "not before slot {}, but the transaction is valid from slot {}",
+                        slot, start
+                    ),
+                ),
+                None => (
+                    false,
+                    format!(
+                        "not before slot {}, but the validity interval has no start",
+                        slot
+                    ),
+                ),
+            };
+            let failures = if passes {
+                Vec::new()
+            } else {
+                vec![description.clone()]
+            };
+            (passes, description, failures)
+        }
+        NativeScript::InvalidHereafter(slot) => {
+            let (passes, description) = match conditions.valid_until {
+                Some(ttl) if ttl <= *slot => (
+                    true,
+                    format!("before slot {} (valid until slot {})", slot, ttl),
+                ),
+                Some(ttl) => (
+                    false,
+                    format!(
+                        "before slot {}, but the transaction is valid until slot {}",
+                        slot, ttl
+                    ),
+                ),
+                None => (
+                    false,
+                    format!("before slot {}, but the transaction has no TTL", slot),
+                ),
+            };
+            let failures = if passes {
+                Vec::new()
+            } else {
+                vec![description.clone()]
+            };
+            (passes, description, failures)
+        }
+    };
+
+    lines[line] = format!(
+        "{:indent$}{}: {}",
+        "",
+        description,
+        if passes { "ok" } else { "FAILS" },
+        indent = 2 * depth
+    );
+    (passes, if passes { Vec::new() } else { failures })
+}
+
+/// The native script with the given hash, among the scripts witnessed by the transaction and the
+/// reference scripts of the resolved outputs.
+pub fn find(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    script_hash: &Hash<28>,
+) -> Option<NativeScript> {
+    let witnessed = transaction
+        .transaction_witness_set
+        .native_script
+        .iter()
+        .flat_map(|scripts| scripts.iter())
+        .find(|script| script.original_hash() == *script_hash)
+        .map(|script| NativeScript::clone(script));
+    if witnessed.is_some() {
+        return witnessed;
+    }
+
+    collect_all_inputs(transaction)
+        .into_iter()
+        .find_map(|input| match utxos.get(&input)?.script.as_ref()? {
+            MemoizedScript::NativeScript(script) if script.compute_hash() == *script_hash => {
+                Some(NativeScript::clone(script))
+            }
+            _ => None,
+        })
+}
+
+/// Every native script the transaction needs, with what needs it and how it evaluates.
+pub fn evaluations(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+) -> Vec<(String, Hash<28>, Evaluation)> {
+    let conditions = Conditions::of(transaction);
+    script_hash::required_scripts(transaction, utxos)
+        .into_iter()
+        .filter_map(|(what, hash)| {
+            let script = find(transaction, utxos, &hash)?;
+            Some((what, hash, evaluate(&script, &conditions)))
+        })
+        .collect()
+}
+
+/// Each native script the transaction needs, clause by clause, against its signatures and
+/// validity interval. Returns the report and the number of scripts that fail.
+pub fn describe(transaction: &MintedTx<'_>, utxos: &ResolvedUtxos) -> (String, usize) {
+    let evaluations = evaluations(transaction, utxos);
+
+    let mut section = String::from("Native scripts:");
+    if evaluations.is_empty() {
+        section.push_str("\n  (none, the transaction needs no native script it provides)");
+    }
+
+    let mut failing = 0;
+    for (what, hash, evaluation) in &evaluations {
+        if !evaluation.passes {
+            failing += 1;
+        }
+        section.push_str(&format!(
+            "\n  {} needs {}: {}",
+            what,
+            hex::encode(hash),
+            if evaluation.passes { "passes" } else { "FAILS" }
+        ));
+        for line in &evaluation.lines {
+            section.push_str(&format!("\n  {}", line));
+        }
+    }
+
+    if failing > 0 {
+        section.push_str(&format!(
+            "\n\n  {} native script(s) fail: the ledger rejects the transaction (ScriptWitnessNotValidatingUTXOW)",
+            failing
+        ));
+        if transaction.transaction_witness_set.vkeywitness.is_none() {
+            section.push_str("\n  The transaction carries no signature yet: signature clauses fail until it is signed");
+        }
+    }
+
+    (section, failing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> Hash<28> {
+        Hash::new([byte; 28])
+    }
+
+    fn conditions(
+        signatories: &[u8],
+        valid_from: Option<u64>,
+        valid_until: Option<u64>,
+    ) -> Conditions {
+        Conditions {
+            signatories: signatories.iter().map(|byte| key(*byte)).collect(),
+            valid_from,
+            valid_until,
+        }
+    }
+
+    #[test]
+    fn evaluations() {
+        let signed = || NativeScript::ScriptPubkey(key(1));
+        let unsigned = || NativeScript::ScriptPubkey(key(2));
+        let cases = [
+            (signed(), conditions(&[1], None, None), true, 0),
+            (unsigned(), conditions(&[1], None, None), false, 1),
+            (
+                NativeScript::ScriptAll(vec![signed(), unsigned()]),
+                conditions(&[1], None, None),
+                false,
+                1,
+            ),
+            (
+                NativeScript::ScriptAll(vec![signed(), unsigned()]),
+                conditions(&[1, 2], None, None),
+                true,
+                0,
+            ),
+            (
+                NativeScript::ScriptAll(vec![]),
+                conditions(&[], None, None),
+                true,
+                0,
+            ),
+            (
+                NativeScript::ScriptAny(vec![signed(), unsigned()]),
+                conditions(&[1], None, None),
+                true,
+                0,
+            ),
+            (
+                NativeScript::ScriptAny(vec![]),
+                conditions(&[], None, None),
+                false,
+                0,
+            ),
+            (
+                NativeScript::ScriptNOfK(2, vec![signed(), unsigned(), signed()]),
+                conditions(&[1], None, None),
+                true,
+                0,
+            ),
+            (
+                NativeScript::ScriptNOfK(2, vec![signed(), unsigned(), unsigned()]),
+                conditions(&[1], None, None),
+                false,
+                2,
+            ),
+            (
+                NativeScript::InvalidBefore(100),
+                conditions(&[], Some(100), None),
+                true,
+                0,
+            ),
+            (
+                NativeScript::InvalidBefore(100),
+                conditions(&[], Some(99), None),
+                false,
+                1,
+            ),
+            (
+                NativeScript::InvalidBefore(100),
+                conditions(&[], None, None),
+                false,
+                1,
+            ),
+            (
+                NativeScript::InvalidHereafter(200),
+                conditions(&[], None, Some(200)),
+                true,
+                0,
+            ),
+            (
+                NativeScript::InvalidHereafter(200),
+                conditions(&[], None, Some(201)),
+                false,
+                1,
+            ),
+            (
+                NativeScript::InvalidHereafter(200),
+                conditions(&[], None, None),
+                false,
+                1,
+            ),
+        ];
+        for (position, (script, conditions, passes, failures)) in cases.into_iter().enumerate() {
+            let evaluation = evaluate(&script, &conditions);
+            assert_eq!(evaluation.passes, passes, "case {}", position);
+            assert_eq!(evaluation.failures.len(), failures, "case {}", position);
+        }
+    }
+}
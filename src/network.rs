@@ -0,0 +1,118 @@
+use amaru_kernel::{
+    Address, MemoizedTransactionOutput, MintedTx, Network, NetworkId, network::NetworkName,
+};
+
+use crate::{formatter::ReadableFormatter, json, resolver::ResolvedUtxos};
+
+/// An address, or the network id of the body, from another network than --network.
+pub struct Mismatch {
+    pub description: String,
+    /// Whether the ledger rejects the transaction for it (WrongNetwork, WrongNetworkWithdrawal,
+    /// WrongNetworkInTxBody). It doesn't check the addresses of the outputs the transaction
+    /// spends or references, which can only point to a mistaken --network or provider.
+    pub rejected: bool,
+}
+
+/// Every address of the transaction that belongs to another network than the one given with
+/// --network: the resolved inputs, the outputs, the collateral return, the withdrawals and the
+/// return accounts of proposals, then the network id of the body.
+pub fn mismatches(
+    network: NetworkName,
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+) -> Vec<Mismatch> {
+    let body = &transaction.transaction_body;
+    let expected = match network {
+        NetworkName::Mainnet => Network::Mainnet,
+        _ => Network::Testnet,
+    };
+
+    let mut addresses = Vec::new();
+    let mut inputs: Vec<_> = utxos.iter().collect();
+    inputs.sort_by_key(|(input, _)| *input);
+    for (input, output) in inputs {
+        addresses.push((
+            format!("input {}", input.format_readable()),
+            output.address.clone(),
+            false,
+        ));
+    }
+    for (index, output) in body.outputs.iter().enumerate() {
+        if let Ok(output) = MemoizedTransactionOutput::try_from(output.clone()) {
+            addresses.push((format!("output {}", index), output.address, true));
+        }
+    }
+    if let Some(output) = &body.collateral_return
+        && let Ok(output) = MemoizedTransactionOutput::try_from(output.clone())
+    {
+        addresses.push(("the collateral return".to_string(), output.address, true));
+    }
+    for (account, _) in body.withdrawals.iter().flat_map(|w| w.iter()) {
+        if let Ok(address) = Address::from_bytes(account) {
+            addresses.push(("a withdrawal".to_string(), address, true));
+        }
+    }
+    for (index, proposal) in body
+        .proposal_procedures
+        .iter()
+        .flat_map(|p| p.iter())
+        .enumerate()
+    {
+        if let Ok(address) = Address::from_bytes(&proposal.reward_account) {
+            addresses.push((
+                format!("the return account of proposal {}", index),
+                address,
+                true,
+            ));
+        }
+    }
+
+    let mut mismatches: Vec<_> = addresses
+        .into_iter()
+        .filter_map(|(what, address, rejected)| {
+            let network = match &address {
+                Address::Shelley(shelley) => shelley.network(),
+                Address::Stake(stake) => stake.network(),
+                Address::Byron(_) => return None,
+            };
+            (network != expected).then(|| Mismatch {
+                description: format!(
+                    "{} is a {} address, {}, but --network is {}",
+                    what,
+                    name(network),
+                    json::address(&address),
+                    name(expected)
+                ),
+                rejected,
+            })
+        })
+        .collect();
+
+    let network_id = match body.network_id {
+        Some(NetworkId::Mainnet) => Some(Network::Mainnet),
+        Some(NetworkId::Testnet) => Some(Network::Testnet),
+        None => None,
+    };
+    if let Some(network_id) = network_id
+        && network_id != expected
+    {
+        mismatches.push(Mismatch {
+            description: format!(
+                "the network id of the transaction body is {}, but --network is {}",
+                name(network_id),
+                name(expected)
+            ),
+            rejected: true,
+        });
+    }
+
+    mismatches
+}
+
+fn name(network: Network) -> String {
+    match network {
+        Network::Mainnet => "mainnet".to_string(),
+        Network::Testnet => "testnet".to_string(),
+        Network::Other(tag) => format!("network {}", tag),
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use amaru_kernel::{
+    Address, Hash, MemoizedTransactionOutput, MintedTransactionOutput, cbor, cbor::data::Tag,
+    network::NetworkName,
+};
+use anyhow::{Context, Result, anyhow};
+
+const ENCODED_CBOR_TAG: u64 = 24;
+
+/// The datum attached to an output, as reported by an indexer.
+pub enum OutputDatum {
+    None,
+    Hash(Hash<32>),
+    /// CBOR-encoded PlutusData.
+    Inline(Vec<u8>),
+}
+
+/// A reference script attached to an output, as reported by an indexer.
+#[derive(Clone)]
+pub enum ReferenceScript {
+    /// CBOR-encoded native script.
+    Native(Vec<u8>),
+    PlutusV1(Vec<u8>),
+    PlutusV2(Vec<u8>),
+    PlutusV3(Vec<u8>),
+}
+
+/// The pieces of a transaction output for providers that don't serve the raw output CBOR.
+pub struct OutputParts {
+    pub address: Address,
+    pub lovelace: u64,
+    pub assets: BTreeMap<Hash<28>, BTreeMap<Vec<u8>, u64>>,
+    pub datum: OutputDatum,
+    pub script: Option<ReferenceScript>,
+}
+
+impl OutputParts {
+    /// Re-assemble a post-Alonzo output and decode it like one read from a transaction body.
+    pub fn into_memoized(self) -> Result<MemoizedTransactionOutput> {
+        let bytes = self
+            .encode()
+            .context("Failed to encode transaction output")?;
+
+        decode_output(&bytes)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, cbor::encode::Error<std::convert::Infallible>> {
+        let mut encoder = cbor::Encoder::new(Vec::new());
+
+        let has_datum = !matches!(self.datum, OutputDatum::None);
+        let fields = 2 + has_datum as u64 + self.script.is_some() as u64;
+        encoder.map(fields)?;
+
+        encoder.u8(0)?.bytes(&self.address.to_vec())?;
+
+        encoder.u8(1)?;
+        if self.assets.is_empty() {
+            encoder.u64(self.lovelace)?;
+        } else {
+            encoder.array(2)?.u64(self.lovelace)?;
+            encoder.map(self.assets.len() as u64)?;
+            for (policy, names) in &self.assets {
+                encoder.bytes(policy.as_ref())?.map(names.len() as u64)?;
+                for (name, quantity) in names {
+                    encoder.bytes(name)?.u64(*quantity)?;
+                }
+            }
+        }
+
+        match &self.datum {
+            OutputDatum::None => {}
+            OutputDatum::Hash(hash) => {
+                encoder.u8(2)?.array(2)?.u8(0)?.bytes(hash.as_ref())?;
+            }
+            OutputDatum::Inline(data) => {
+                encoder
+                    .u8(2)?
+                    .array(2)?
+                    .u8(1)?
+                    .tag(Tag::new(ENCODED_CBOR_TAG))?
+                    .bytes(data)?;
+            }
+        }
+
+        if let Some(script) = &self.script {
+            let mut inner = cbor::Encoder::new(Vec::new());
+            inner.array(2)?;
+            match script {
+                ReferenceScript::Native(native) => {
+                    inner.u8(0)?;
+                    inner.writer_mut().extend_from_slice(native);
+                }
+                ReferenceScript::PlutusV1(bytes) => {
+                    inner.u8(1)?.bytes(bytes)?;
+                }
+                ReferenceScript::PlutusV2(bytes) => {
+                    inner.u8(2)?.bytes(bytes)?;
+                }
+                ReferenceScript::PlutusV3(bytes) => {
+                    inner.u8(3)?.bytes(bytes)?;
+                }
+            }
+
+            encoder
+                .u8(3)?
+                .tag(Tag::new(ENCODED_CBOR_TAG))?
+                .bytes(&inner.into_writer())?;
+        }
+
+        Ok(encoder.into_writer())
+    }
+}
+
+/// Decode a standalone CBOR-encoded transaction output, in any era's format.
+pub fn decode_output(bytes: &[u8]) -> Result<MemoizedTransactionOutput> {
+    let output: MintedTransactionOutput<'_> =
+        cbor::decode(bytes).context("Failed to decode transaction output CBOR")?;
+
+    MemoizedTransactionOutput::try_from(output)
+        .map_err(|e| anyhow!("Failed to convert output to memoized format: {}", e))
+}
+
+/// A stand-in for an output that couldn't be resolved: no value and no datum, locked by an
+/// all-zero key hash on the given network.
+pub fn placeholder_output(network: NetworkName) -> Result<MemoizedTransactionOutput> {
+    // Header of an enterprise address with a key payment credential, tagged with the network id.
+    let header = match network {
+        NetworkName::Mainnet => 0x61,
+        _ => 0x60,
+    };
+
+    let mut bytes = vec![header];
+    bytes.extend_from_slice(&[0; 28]);
+
+    OutputParts {
+        address: Address::from_bytes(&bytes)
+            .map_err(|e| anyhow!("Failed to build placeholder address: {}", e))?,
+        lovelace: 0,
+        assets: BTreeMap::new(),
+        datum: OutputDatum::None,
+        script: None,
+    }
+    .into_memoized()
+}
+
+pub fn parse_address(address: &str) -> Result<Address> {
+    Address::from_bech32(address)
+        .or_else(|_| Address::from_base58(address))
+        .map_err(|e| anyhow!("Invalid address {}: {}", address, e))
+}
@@ -0,0 +1,55 @@
+use std::{
+    io::{IsTerminal, Write},
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Pager used when $PAGER isn't set. As git does, `LESS=FRX` makes it quit when the output fits
+/// on one screen, keep colors, and leave the output on the terminal.
+const DEFAULT_PAGER: &str = "less";
+
+pub fn init(enabled: bool) {
+    let _ = ENABLED.set(enabled);
+}
+
+/// Print to stdout, through $PAGER when stdout is a terminal. The pager, not nawi, tells whether
+/// the text fits on one screen.
+pub fn print(contents: &str) {
+    if !ENABLED.get().copied().unwrap_or_default()
+        || !std::io::stdout().is_terminal()
+        || !page(contents)
+    {
+        println!("{}", contents);
+    }
+}
+
+/// Pipe the text through the pager, returning false when there is none to run.
+fn page(contents: &str) -> bool {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let mut words = pager.split_whitespace();
+    let Some(program) = words.next() else {
+        return false;
+    };
+    if program == "cat" {
+        return false;
+    }
+
+    let mut command = Command::new(program);
+    command.args(words).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+
+    let Ok(mut child) = command.spawn() else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closes its input when quit early, which isn't an error.
+        let _ = writeln!(stdin, "{}", contents);
+    }
+    let _ = child.wait();
+
+    true
+}
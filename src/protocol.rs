@@ -0,0 +1,682 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::Path,
+};
+
+use amaru_kernel::cbor;
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::Value;
+
+use crate::PlutusVersion;
+
+/// Major protocol version of the first hard fork of each Plutus-capable era.
+pub const ALONZO: u64 = 5;
+pub const BABBAGE: u64 = 7;
+pub const CONWAY: u64 = 9;
+
+/// Major version of the protocol a transaction executes under. It only decides which Plutus
+/// versions and Conway features exist: the contexts nawi builds are the same under every
+/// version that accepts them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProtocolVersion(pub u64);
+
+impl ProtocolVersion {
+    pub fn era(&self) -> &'static str {
+        match self.0 {
+            0..=1 => "Byron",
+            2 => "Shelley",
+            3 => "Allegra",
+            4 => "Mary",
+            5..=6 => "Alonzo",
+            7..=8 => "Babbage",
+            _ => "Conway",
+        }
+    }
+
+    /// Whether scripts of the given Plutus version can run at all under this protocol version.
+    pub fn supports(&self, version: PlutusVersion) -> bool {
+        self.0 >= introduced_in(version)
+    }
+
+    /// Whether Conway certificates and governance fields exist under this protocol version.
+    pub fn has_governance(&self) -> bool {
+        self.0 >= CONWAY
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.0, self.era())
+    }
+}
+
+/// The major protocol version of a protocol parameters dump of cardano-cli
+/// (`protocolVersion.major`), Ogmios (`version.major`) or Blockfrost (`protocol_major_ver`).
+pub fn major_version(json: &Value) -> Option<u64> {
+    [
+        "/protocolVersion/major",
+        "/version/major",
+        "/protocol_major_ver",
+    ]
+    .iter()
+    .find_map(|pointer| json.pointer(pointer).and_then(number))
+}
+
+/// The first major protocol version under which scripts of a Plutus version can run.
+pub fn introduced_in(version: PlutusVersion) -> u64 {
+    match version {
+        PlutusVersion::PlutusV1 => ALONZO,
+        PlutusVersion::PlutusV2 => BABBAGE,
+        PlutusVersion::PlutusV3 => CONWAY,
+    }
+}
+
+/// Conway prices reference scripts by tiers of this many bytes, each costing this many times more
+/// per byte than the one before.
+const REFERENCE_SCRIPT_TIER: u64 = 25_600;
+const REFERENCE_SCRIPT_TIER_MULTIPLIER: f64 = 1.2;
+
+/// Total size of reference scripts a transaction may use since Conway.
+pub const MAX_REFERENCE_SCRIPTS_SIZE: u64 = 200 * 1024;
+
+/// Keys under which protocol parameter dumps hold the base price of reference scripts per byte:
+/// cardano-cli (`minFeeRefScriptCostPerByte`) and Blockfrost (`min_fee_ref_script_cost_per_byte`).
+const REFERENCE_SCRIPT_COST_KEYS: [&str; 2] = [
+    "minFeeRefScriptCostPerByte",
+    "min_fee_ref_script_cost_per_byte",
+];
+
+/// The base price of reference scripts per byte, from a protocol parameters dump. Ogmios nests
+/// it as `minFeeReferenceScripts.base`.
+pub fn reference_script_cost_per_byte(json: &Value) -> Result<f64> {
+    REFERENCE_SCRIPT_COST_KEYS
+        .iter()
+        .find_map(|key| json.get(key).and_then(Value::as_f64))
+        .or_else(|| {
+            json.pointer("/minFeeReferenceScripts/base")
+                .and_then(Value::as_f64)
+        })
+        .ok_or_else(|| anyhow!("No reference script cost per byte in the protocol parameters"))
+}
+
+/// The fee Conway adds for the reference scripts of a transaction, given their total size: each
+/// tier of 25,600 bytes costs 1.2 times more per byte than the previous one, rounded down once
+/// at the end as the ledger does.
+pub fn reference_script_fee(size: u64, cost_per_byte: f64) -> u64 {
+    let mut fee = 0.0;
+    let mut price = cost_per_byte;
+    let mut remaining = size;
+
+    while remaining >= REFERENCE_SCRIPT_TIER {
+        fee += REFERENCE_SCRIPT_TIER as f64 * price;
+        price *= REFERENCE_SCRIPT_TIER_MULTIPLIER;
+        remaining -= REFERENCE_SCRIPT_TIER;
+    }
+    fee += remaining as f64 * price;
+
+    fee.floor() as u64
+}
+
+/// Memory and CPU steps, as execution budgets and limits are counted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExUnits {
+    pub mem: u64,
+    pub steps: u64,
+}
+
+/// The execution units the redeemers of one transaction, and of one block, may use altogether.
+#[derive(Clone, Copy, Debug)]
+pub struct ExecutionLimits {
+    pub transaction: ExUnits,
+    pub block: ExUnits,
+}
+
+impl ExecutionLimits {
+    /// Read the limits from a protocol parameters dump of cardano-cli (`maxTxExecutionUnits`),
+    /// Ogmios (`maxExecutionUnitsPerTransaction`) or Blockfrost (`max_tx_ex_mem`, ...).
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let nested = |keys: [&str; 2]| {
+            keys.iter().find_map(|key| {
+                let units = json.get(key)?;
+                Some(ExUnits {
+                    mem: number(units.get("memory")?)?,
+                    steps: number(units.get("steps").or_else(|| units.get("cpu"))?)?,
+                })
+            })
+        };
+        let flat = |mem: &str, steps: &str| {
+            Some(ExUnits {
+                mem: number(json.get(mem)?)?,
+                steps: number(json.get(steps)?)?,
+            })
+        };
+
+        let transaction = nested(["maxTxExecutionUnits", "maxExecutionUnitsPerTransaction"])
+            .or_else(|| flat("max_tx_ex_mem", "max_tx_ex_steps"))
+            .ok_or_else(|| {
+                anyhow!("No execution unit limits per transaction in the protocol parameters")
+            })?;
+        let block = nested(["maxBlockExecutionUnits", "maxExecutionUnitsPerBlock"])
+            .or_else(|| flat("max_block_ex_mem", "max_block_ex_steps"))
+            .ok_or_else(|| {
+                anyhow!("No execution unit limits per block in the protocol parameters")
+            })?;
+
+        Ok(Self { transaction, block })
+    }
+}
+
+/// The largest transaction the protocol accepts in bytes, from a protocol parameters dump of
+/// cardano-cli (`maxTxSize`), Ogmios (`maxTransactionSize.bytes`) or Blockfrost (`max_tx_size`).
+pub fn max_transaction_size(json: &Value) -> Result<u64> {
+    ["/maxTxSize", "/maxTransactionSize/bytes", "/max_tx_size"]
+        .iter()
+        .find_map(|pointer| json.pointer(pointer).and_then(number))
+        .ok_or_else(|| anyhow!("No maximum transaction size in the protocol parameters"))
+}
+
+/// The lovelace each byte of an output must hold, from a protocol parameters dump of cardano-cli
+/// (`utxoCostPerByte`), Ogmios (`minUtxoDepositCoefficient`) or Blockfrost
+/// (`coins_per_utxo_size`).
+pub fn coins_per_utxo_byte(json: &Value) -> Result<u64> {
+    [
+        "/utxoCostPerByte",
+        "/coinsPerUTxOByte",
+        "/minUtxoDepositCoefficient",
+        "/coins_per_utxo_size",
+    ]
+    .iter()
+    .find_map(|pointer| json.pointer(pointer).and_then(number))
+    .ok_or_else(|| anyhow!("No cost per UTxO byte in the protocol parameters"))
+}
+
+/// An exact non-negative fraction, as the ledger holds the prices of execution units.
+#[derive(Clone, Copy, Debug)]
+pub struct Ratio {
+    pub numerator: u128,
+    pub denominator: u128,
+}
+
+impl Ratio {
+    /// Read a fraction written as `577/10000` (Ogmios) or as a decimal number (cardano-cli,
+    /// Blockfrost), without going through floating point.
+    fn from_json(value: &Value) -> Option<Self> {
+        let ratio = match value {
+            Value::String(text) => match text.split_once('/') {
+                Some((numerator, denominator)) => Self {
+                    numerator: numerator.trim().parse().ok()?,
+                    denominator: denominator.trim().parse().ok()?,
+                },
+                None => Self::decimal(text.trim())?,
+            },
+            Value::Number(number) => Self::decimal(&number.to_string())?,
+            _ => return None,
+        };
+        (ratio.denominator > 0).then(|| ratio.reduced())
+    }
+
+    /// A decimal such as `0.0577` or `7.21e-5`.
+    fn decimal(text: &str) -> Option<Self> {
+        let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+            None => (text, 0),
+        };
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let numerator: u128 = format!("{}{}", whole, fraction).parse().ok()?;
+        let exponent = exponent - fraction.len() as i32;
+
+        if exponent >= 0 {
+            Some(Self {
+                numerator: numerator.checked_mul(10u128.checked_pow(exponent as u32)?)?,
+                denominator: 1,
+            })
+        } else {
+            Some(Self {
+                numerator,
+                denominator: 10u128.checked_pow(exponent.unsigned_abs())?,
+            })
+        }
+    }
+
+    fn reduced(self) -> Self {
+        let (mut a, mut b) = (self.numerator, self.denominator);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        Self {
+            numerator: self.numerator / a,
+            denominator: self.denominator / a,
+        }
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+/// The parameters of the minimum fee: a price per byte of transaction plus a fixed part, and the
+/// prices of memory and CPU steps.
+#[derive(Clone, Copy, Debug)]
+pub struct FeeParameters {
+    pub per_byte: u64,
+    pub fixed: u64,
+    pub price_mem: Ratio,
+    pub price_steps: Ratio,
+}
+
+impl FeeParameters {
+    /// Read the fee parameters from a protocol parameters dump of cardano-cli (`txFeePerByte`,
+    /// `executionUnitPrices`), Ogmios (`minFeeCoefficient`, `scriptExecutionPrices`) or
+    /// Blockfrost (`min_fee_a`, `price_mem`, ...).
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let first = |pointers: &[&str]| pointers.iter().find_map(|pointer| json.pointer(pointer));
+
+        let per_byte = first(&[
+            "/txFeePerByte",
+            "/minFeeA",
+            "/minFeeCoefficient",
+            "/min_fee_a",
+        ])
+        .and_then(number)
+        .ok_or_else(|| anyhow!("No fee per byte in the protocol parameters"))?;
+        let fixed = first(&[
+            "/txFeeFixed",
+            "/minFeeB",
+            "/minFeeConstant/ada/lovelace",
+            "/min_fee_b",
+        ])
+        .and_then(number)
+        .ok_or_else(|| anyhow!("No fixed fee in the protocol parameters"))?;
+        let price_mem = first(&[
+            "/executionUnitPrices/priceMemory",
+            "/scriptExecutionPrices/memory",
+            "/price_mem",
+        ])
+        .and_then(Ratio::from_json)
+        .ok_or_else(|| anyhow!("No price of memory in the protocol parameters"))?;
+        let price_steps = first(&[
+            "/executionUnitPrices/priceSteps",
+            "/scriptExecutionPrices/cpu",
+            "/price_step",
+        ])
+        .and_then(Ratio::from_json)
+        .ok_or_else(|| anyhow!("No price of CPU steps in the protocol parameters"))?;
+
+        Ok(Self {
+            per_byte,
+            fixed,
+            price_mem,
+            price_steps,
+        })
+    }
+
+    /// The part of the minimum fee paid for the size of the transaction.
+    pub fn size_fee(&self, size: u64) -> u64 {
+        self.per_byte * size + self.fixed
+    }
+
+    /// The part of the minimum fee paid for the execution units of all redeemers, rounded up once
+    /// at the end as the ledger does.
+    pub fn script_fee(&self, units: ExUnits) -> u64 {
+        let (mem, steps) = (self.price_mem, self.price_steps);
+        let numerator = units.mem as u128 * mem.numerator * steps.denominator
+            + units.steps as u128 * steps.numerator * mem.denominator;
+        let denominator = mem.denominator * steps.denominator;
+
+        numerator.div_ceil(denominator) as u64
+    }
+}
+
+/// The deposits the protocol parameters set: for stake credentials, stake pools, DReps and
+/// governance actions. Each is None when the dump doesn't have it, as before Conway for the last
+/// two.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DepositParameters {
+    pub stake_credential: Option<u64>,
+    pub stake_pool: Option<u64>,
+    pub drep: Option<u64>,
+    pub gov_action: Option<u64>,
+}
+
+impl DepositParameters {
+    /// Read the deposits from a protocol parameters dump of cardano-cli (`stakeAddressDeposit`,
+    /// ...), Ogmios (`stakeCredentialDeposit.ada.lovelace`, ...) or Blockfrost (`key_deposit`,
+    /// ...).
+    pub fn from_json(json: &Value) -> Self {
+        let first = |pointers: &[&str]| {
+            pointers
+                .iter()
+                .find_map(|pointer| json.pointer(pointer).and_then(number))
+        };
+
+        Self {
+            stake_credential: first(&[
+                "/stakeAddressDeposit",
+                "/keyDeposit",
+                "/stakeCredentialDeposit/ada/lovelace",
+                "/key_deposit",
+            ]),
+            stake_pool: first(&[
+                "/stakePoolDeposit",
+                "/poolDeposit",
+                "/stakePoolDeposit/ada/lovelace",
+                "/pool_deposit",
+            ]),
+            drep: first(&[
+                "/dRepDeposit",
+                "/delegateRepresentativeDeposit/ada/lovelace",
+                "/drep_deposit",
+            ]),
+            gov_action: first(&[
+                "/govActionDeposit",
+                "/governanceActionDeposit/ada/lovelace",
+                "/gov_action_deposit",
+            ]),
+        }
+    }
+}
+
+/// What the protocol parameters require of the collateral of a transaction running scripts.
+#[derive(Clone, Copy, Debug)]
+pub struct CollateralParameters {
+    /// The collateral the transaction must put up, as a percentage of its fee.
+    pub percentage: u64,
+    /// None when the dump doesn't have it.
+    pub max_inputs: Option<u64>,
+}
+
+impl CollateralParameters {
+    /// Read the collateral parameters from a protocol parameters dump of cardano-cli
+    /// (`collateralPercentage`, `maxCollateralInputs`), Ogmios (the same keys) or Blockfrost
+    /// (`collateral_percent`, `max_collateral_inputs`).
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let first = |pointers: &[&str]| {
+            pointers
+                .iter()
+                .find_map(|pointer| json.pointer(pointer).and_then(number))
+        };
+
+        Ok(Self {
+            percentage: first(&["/collateralPercentage", "/collateral_percent"])
+                .ok_or_else(|| anyhow!("No collateral percentage in the protocol parameters"))?,
+            max_inputs: first(&["/maxCollateralInputs", "/max_collateral_inputs"]),
+        })
+    }
+
+    /// The least collateral the ledger accepts for a fee: the percentage of it, rounded up.
+    pub fn required(&self, fee: u64) -> u64 {
+        (u128::from(fee) * u128::from(self.percentage)).div_ceil(100) as u64
+    }
+}
+
+/// A count in protocol parameters, which Blockfrost serves as a string.
+fn number(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|text| text.parse().ok()))
+}
+
+/// Read a protocol parameters dump, as written by `cardano-cli query protocol-parameters`.
+pub fn load_parameters(path: &Path) -> Result<Value> {
+    let contents = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failed to read protocol parameters file: {}",
+            path.display()
+        )
+    })?;
+    serde_json::from_str(&contents).with_context(|| {
+        format!(
+            "Invalid JSON in protocol parameters file: {}",
+            path.display()
+        )
+    })
+}
+
+/// Keys under which protocol parameter dumps hold the cost models: cardano-cli and Ogmios
+/// (`costModels`), Blockfrost (`cost_models_raw`, `cost_models`).
+const COST_MODELS_KEYS: [&str; 3] = ["costModels", "cost_models_raw", "cost_models"];
+
+/// Cost model parameters of each Plutus language, in the order the ledger serialises them.
+#[derive(Clone, Debug, Default)]
+pub struct CostModels(BTreeMap<PlutusVersion, Vec<i64>>);
+
+impl CostModels {
+    /// Read cost models from a JSON file: either a bare `{"PlutusV1": [...], ...}` object or a
+    /// full protocol parameters dump holding one.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cost models file: {}", path.display()))?;
+        let json: Value = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid JSON in cost models file: {}", path.display()))?;
+
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let models = COST_MODELS_KEYS
+            .iter()
+            .find_map(|key| json.get(key).filter(|value| value.is_object()))
+            .unwrap_or(json)
+            .as_object()
+            .ok_or_else(|| anyhow!("Cost models must be a JSON object keyed by language"))?;
+
+        let mut cost_models = BTreeMap::new();
+        for (language, parameters) in models {
+            let version = match language.as_str() {
+                "PlutusV1" | "PlutusScriptV1" => PlutusVersion::PlutusV1,
+                "PlutusV2" | "PlutusScriptV2" => PlutusVersion::PlutusV2,
+                "PlutusV3" | "PlutusScriptV3" => PlutusVersion::PlutusV3,
+                _ => bail!("Unknown language in cost models: {}", language),
+            };
+
+            // Named parameters (older cardano-cli dumps) don't carry the ledger's order.
+            let parameters = parameters
+                .as_array()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Cost model of {} must be an array of integers, in ledger order",
+                        language
+                    )
+                })?
+                .iter()
+                .map(|parameter| {
+                    parameter.as_i64().ok_or_else(|| {
+                        anyhow!(
+                            "Invalid cost model parameter for {}: {}",
+                            language,
+                            parameter
+                        )
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+            cost_models.insert(version, parameters);
+        }
+
+        Ok(Self(cost_models))
+    }
+
+    pub fn get(&self, version: PlutusVersion) -> Option<&[i64]> {
+        self.0.get(&version).map(Vec::as_slice)
+    }
+}
+
+/// The language views that enter the script integrity hash of a transaction running scripts of
+/// the given languages, encoded as the ledger does.
+pub fn encode_language_views(
+    cost_models: &CostModels,
+    languages: &BTreeSet<PlutusVersion>,
+) -> Result<Vec<u8>> {
+    let mut views = languages
+        .iter()
+        .map(|language| {
+            let parameters = cost_models.get(*language).ok_or_else(|| {
+                anyhow!(
+                    "No cost model for {:?}. Pass --cost-models with one",
+                    language
+                )
+            })?;
+            language_view(*language, parameters).map_err(|e| {
+                anyhow!(
+                    "Failed to encode the language view of {:?}: {}",
+                    language,
+                    e
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Canonical CBOR: keys ordered by length, then bytewise.
+    views.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    let mut encoder = cbor::Encoder::new(Vec::new());
+    encoder
+        .map(views.len() as u64)
+        .map_err(|e| anyhow!("Failed to encode language views: {}", e))?;
+    for (key, value) in views {
+        encoder.writer_mut().extend_from_slice(&key);
+        encoder.writer_mut().extend_from_slice(&value);
+    }
+
+    Ok(encoder.into_writer())
+}
+
+type EncodeError = cbor::encode::Error<std::convert::Infallible>;
+
+/// Encoded key and value of one language view. PlutusV1 keeps the quirks of its Alonzo
+/// encoding: both the language id and the indefinite-length parameter list are wrapped in
+/// byte strings.
+fn language_view(
+    language: PlutusVersion,
+    parameters: &[i64],
+) -> Result<(Vec<u8>, Vec<u8>), EncodeError> {
+    let mut key = cbor::Encoder::new(Vec::new());
+    let mut value = cbor::Encoder::new(Vec::new());
+
+    match language {
+        PlutusVersion::PlutusV1 => {
+            key.bytes(&[0])?;
+
+            let mut list = cbor::Encoder::new(Vec::new());
+            list.begin_array()?;
+            for parameter in parameters {
+                list.i64(*parameter)?;
+            }
+            list.end()?;
+            value.bytes(&list.into_writer())?;
+        }
+        PlutusVersion::PlutusV2 | PlutusVersion::PlutusV3 => {
+            key.u8(language_id(language))?;
+
+            value.array(parameters.len() as u64)?;
+            for parameter in parameters {
+                value.i64(*parameter)?;
+            }
+        }
+    }
+
+    Ok((key.into_writer(), value.into_writer()))
+}
+
+/// Identifier of a Plutus language in the ledger's CDDL.
+pub fn language_id(language: PlutusVersion) -> u8 {
+    match language {
+        PlutusVersion::PlutusV1 => 0,
+        PlutusVersion::PlutusV2 => 1,
+        PlutusVersion::PlutusV3 => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_ratios() {
+        let cases = [
+            ("0.0577", Some((577, 10_000))),
+            ("7.21e-5", Some((721, 10_000_000))),
+            ("7.21E-5", Some((721, 10_000_000))),
+            ("1", Some((1, 1))),
+            ("1.5e2", Some((150, 1))),
+            ("0.5", Some((5, 10))),
+            ("abc", None),
+            ("1e-40", None),
+        ];
+        for (text, expected) in cases {
+            let ratio = Ratio::decimal(text).map(|ratio| (ratio.numerator, ratio.denominator));
+            assert_eq!(ratio, expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn script_fees_at_mainnet_prices() {
+        let parameters = FeeParameters {
+            per_byte: 44,
+            fixed: 155_381,
+            price_mem: Ratio {
+                numerator: 577,
+                denominator: 10_000,
+            },
+            price_steps: Ratio {
+                numerator: 721,
+                denominator: 10_000_000,
+            },
+        };
+        let cases = [
+            (0, 0, 0),
+            (1, 0, 1),
+            (0, 1, 1),
+            (10_000, 0, 577),
+            (0, 10_000_000, 721),
+            (1_000_000, 500_000_000, 93_750),
+            (14_000_000, 10_000_000_000, 1_528_800),
+        ];
+        for (mem, steps, expected) in cases {
+            assert_eq!(
+                parameters.script_fee(ExUnits { mem, steps }),
+                expected,
+                "{} mem, {} steps",
+                mem,
+                steps
+            );
+        }
+    }
+
+    #[test]
+    fn reference_script_fee_tiers() {
+        let cases = [
+            (0, 0),
+            (1, 15),
+            (25_599, 383_985),
+            (25_600, 384_000),
+            (25_601, 384_018),
+            (51_200, 844_800),
+            (51_201, 844_821),
+        ];
+        for (size, expected) in cases {
+            assert_eq!(reference_script_fee(size, 15.0), expected, "{} bytes", size);
+        }
+    }
+
+    #[test]
+    fn required_collateral() {
+        let parameters = CollateralParameters {
+            percentage: 150,
+            max_inputs: Some(3),
+        };
+        let cases = [
+            (0, 0),
+            (1, 2),
+            (2, 3),
+            (170_000, 255_000),
+            (170_001, 255_002),
+        ];
+        for (fee, expected) in cases {
+            assert_eq!(parameters.required(fee), expected, "fee {}", fee);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use amaru_kernel::{PlutusData, to_cbor};
+use anyhow::{Result, anyhow, bail};
+
+use crate::{
+    OutputFormat, PlutusVersion, aiken, compare::field_names, formatter::ReadableFormatter,
+    json::JsonFormatter, script_data,
+};
+
+/// The records of a context whose fields can be named in a query. Past them, only positions can.
+#[derive(Clone, Copy)]
+enum Record {
+    Context,
+    TxInfo,
+    TxInInfo,
+    TxOut,
+    Address,
+    Other,
+}
+
+impl Record {
+    fn fields(self, version: PlutusVersion) -> &'static [&'static str] {
+        match self {
+            Record::Context => field_names(version).0,
+            Record::TxInfo => field_names(version).1,
+            Record::TxInInfo => &["outRef", "resolved"],
+            Record::TxOut if version == PlutusVersion::PlutusV1 => {
+                &["address", "value", "datumHash"]
+            }
+            Record::TxOut => &["address", "value", "datum", "referenceScript"],
+            Record::Address => &["credential", "stakingCredential"],
+            Record::Other => &[],
+        }
+    }
+
+    /// What a named field holds: a record, or a list of them.
+    fn field(self, name: &str) -> Record {
+        match (self, name) {
+            (Record::Context, "txInfo") => Record::TxInfo,
+            (Record::TxInfo, "inputs" | "referenceInputs") => Record::TxInInfo,
+            (Record::TxInfo, "outputs") | (Record::TxInInfo, "resolved") => Record::TxOut,
+            (Record::TxOut, "address") => Record::Address,
+            _ => Record::Other,
+        }
+    }
+}
+
+/// Follow a path such as `tx_info.outputs[2].value` into a script context. Fields are named in
+/// snake_case or camelCase down to outputs and addresses, and reached by position (`[n]`) past
+/// them; `[n]` on a list is its element, on a map the value of its entry.
+pub fn select<'a>(
+    version: PlutusVersion,
+    context: &'a PlutusData,
+    query: &str,
+) -> Result<&'a PlutusData> {
+    let mut data = context;
+    let mut record = Record::Context;
+    // Lists of records hand their kind down to their elements.
+    let mut elements = Record::Other;
+
+    for segment in segments(query)? {
+        match segment {
+            Segment::Field(name) => {
+                let fields = record.fields(version);
+                let Some(index) = fields
+                    .iter()
+                    .position(|field| normalize(field) == normalize(name))
+                else {
+                    bail!(
+                        "Unknown field {} in query {}{}",
+                        name,
+                        query,
+                        if fields.is_empty() {
+                            String::new()
+                        } else {
+                            format!(", expected one of: {}", fields.join(", "))
+                        }
+                    );
+                };
+                data = field(data, index)
+                    .ok_or_else(|| anyhow!("No field {} at this point of the context", name))?;
+                let next = record.field(fields[index]);
+                (record, elements) = if data_is_list(data) {
+                    (Record::Other, next)
+                } else {
+                    (next, Record::Other)
+                };
+            }
+            Segment::Index(index) => {
+                data = match data {
+                    PlutusData::Constr(constr) => constr.fields.get(index),
+                    PlutusData::Array(array) => array.get(index),
+                    PlutusData::Map(entries) => entries.get(index).map(|(_, value)| value),
+                    _ => None,
+                }
+                .ok_or_else(|| anyhow!("No element {} at this point of the context", index))?;
+                (record, elements) = (elements, Record::Other);
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+/// Print a node of a script context in the format of `--output`.
+pub fn format(data: &PlutusData, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Pretty => data.format_readable(),
+        OutputFormat::Cbor => hex::encode(to_cbor(data)),
+        OutputFormat::Both => format!(
+            "{}\nCBOR: {}",
+            data.format_readable(),
+            hex::encode(to_cbor(data))
+        ),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(&data.to_json()).expect("JSON values always serialize")
+        }
+        OutputFormat::ScriptData => script_data::detailed_schema(data),
+        OutputFormat::Aiken => aiken::data_literal(data),
+    }
+}
+
+enum Segment<'a> {
+    Field(&'a str),
+    Index(usize),
+}
+
+fn segments(query: &str) -> Result<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+
+    for part in query.trim().split('.').filter(|part| !part.is_empty()) {
+        let (name, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !name.is_empty() && !(segments.is_empty() && normalize(name) == "context") {
+            segments.push(Segment::Field(name));
+        }
+
+        while let Some(open) = rest.strip_prefix('[') {
+            let (index, after) = open
+                .split_once(']')
+                .ok_or_else(|| anyhow!("Unclosed [ in query {}", query))?;
+            let index = index
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("Invalid position {} in query {}", index, query))?;
+            segments.push(Segment::Index(index));
+            rest = after;
+        }
+
+        if !rest.is_empty() {
+            bail!("Invalid query {}, unexpected {}", query, rest);
+        }
+    }
+
+    Ok(segments)
+}
+
+fn normalize(name: &str) -> String {
+    name.replace('_', "").to_lowercase()
+}
+
+fn field(data: &PlutusData, index: usize) -> Option<&PlutusData> {
+    match data {
+        PlutusData::Constr(constr) => constr.fields.get(index),
+        _ => None,
+    }
+}
+
+fn data_is_list(data: &PlutusData) -> bool {
+    matches!(data, PlutusData::Array(_))
+}
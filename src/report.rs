@@ -0,0 +1,386 @@
+use std::path::Path;
+
+use amaru_kernel::to_cbor;
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use crate::RedeemerContext;
+
+/// A part of a report, rendered the same way in Markdown and HTML.
+enum Block {
+    Table {
+        headers: &'static [&'static str],
+        rows: Vec<Vec<String>>,
+    },
+    Code(String),
+}
+
+/// A collapsible section of a report.
+struct Section {
+    title: String,
+    blocks: Vec<Block>,
+    open: bool,
+}
+
+/// Render the script contexts as a shareable document, in Markdown or HTML depending on the
+/// extension of `path`.
+pub fn write(path: &Path, transaction: &str, contexts: &[RedeemerContext]) -> Result<()> {
+    let html = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("md" | "markdown") => false,
+        Some("html" | "htm") => true,
+        _ => bail!(
+            "Unsupported report file {}, expected a .md or .html file",
+            path.display()
+        ),
+    };
+
+    let title = format!("Script contexts of transaction {}", transaction);
+    let contexts: Vec<_> = contexts
+        .iter()
+        .map(|context| (heading(context), sections(context)))
+        .collect();
+
+    let document = if html {
+        render_html(&title, &contexts)
+    } else {
+        render_markdown(&title, &contexts)
+    };
+
+    std::fs::write(path, document)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    eprintln!("Report -> {}", path.display());
+
+    Ok(())
+}
+
+fn heading(context: &RedeemerContext) -> String {
+    format!(
+        "Redeemer {} ({} #{}), {:?}",
+        context.redeemer, context.purpose, context.index, context.version
+    )
+}
+
+fn sections(context: &RedeemerContext) -> Vec<Section> {
+    let tx_info = &context.json_context["txInfo"];
+    let mut sections = vec![Section {
+        title: "Summary".to_string(),
+        blocks: vec![Block::Table {
+            headers: &["Field", "Value"],
+            rows: vec![
+                vec!["Transaction ID".to_string(), text(&tx_info["id"])],
+                vec!["Fee".to_string(), lovelace(&tx_info["fee"])],
+                vec![
+                    "Validity range".to_string(),
+                    format!(
+                        "{} – {}",
+                        bound(&tx_info["validRange"]["lower"]),
+                        bound(&tx_info["validRange"]["upper"])
+                    ),
+                ],
+                vec!["Signatories".to_string(), list(&tx_info["signatories"])],
+            ],
+        }],
+        open: true,
+    }];
+
+    for (key, title) in [
+        ("inputs", "Inputs"),
+        ("referenceInputs", "Reference inputs"),
+    ] {
+        // PlutusV1 has no reference inputs.
+        if tx_info.get(key).is_none() {
+            continue;
+        }
+        let rows = entries(&tx_info[key])
+            .map(|(i, input)| {
+                let mut row = vec![i.to_string(), text(&input["outputReference"])];
+                row.extend(output_cells(&input["output"]));
+                row
+            })
+            .collect();
+        sections.push(table(
+            title,
+            &[
+                "#",
+                "Output reference",
+                "Address",
+                "Lovelace",
+                "Assets",
+                "Datum",
+                "Reference script",
+            ],
+            rows,
+        ));
+    }
+
+    let rows = entries(&tx_info["outputs"])
+        .map(|(i, output)| {
+            let mut row = vec![i.to_string()];
+            row.extend(output_cells(output));
+            row
+        })
+        .collect();
+    sections.push(table(
+        "Outputs",
+        &[
+            "#",
+            "Address",
+            "Lovelace",
+            "Assets",
+            "Datum",
+            "Reference script",
+        ],
+        rows,
+    ));
+
+    let rows = tx_info["mint"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .flat_map(|(policy, assets)| {
+            assets
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(move |(name, quantity)| vec![policy.clone(), name.clone(), text(quantity)])
+        })
+        .collect();
+    sections.push(table(
+        "Minted assets",
+        &["Policy", "Asset name", "Quantity"],
+        rows,
+    ));
+
+    let rows = entries(&tx_info["certificates"])
+        .map(|(i, certificate)| vec![i.to_string(), text(certificate).replace('\n', "; ")])
+        .collect();
+    sections.push(table("Certificates", &["#", "Certificate"], rows));
+
+    let rows = entries(&tx_info["withdrawals"])
+        .map(|(_, withdrawal)| {
+            vec![
+                text(&withdrawal["stakeAddress"]),
+                text(&withdrawal["lovelace"]),
+            ]
+        })
+        .collect();
+    sections.push(table("Withdrawals", &["Stake address", "Lovelace"], rows));
+
+    let rows = entries(&tx_info["redeemers"])
+        .map(|(_, redeemer)| {
+            vec![
+                text(&redeemer["purpose"]),
+                text(&redeemer["index"]),
+                text(&redeemer["exUnits"]["mem"]),
+                text(&redeemer["exUnits"]["steps"]),
+                truncate(&redeemer["data"].to_string(), 80),
+            ]
+        })
+        .collect();
+    sections.push(table(
+        "Redeemers",
+        &["Purpose", "Index", "Memory", "Steps", "Data"],
+        rows,
+    ));
+
+    sections.push(Section {
+        title: "Script context".to_string(),
+        blocks: vec![Block::Code(context.pretty_context.trim().to_string())],
+        open: false,
+    });
+
+    let cbor = to_cbor(&context.plutus_data);
+    sections.push(Section {
+        title: format!("CBOR ({} bytes)", cbor.len()),
+        blocks: vec![Block::Code(hex::encode(&cbor))],
+        open: false,
+    });
+
+    sections
+}
+
+fn table(title: &str, headers: &'static [&'static str], rows: Vec<Vec<String>>) -> Section {
+    Section {
+        title: format!("{} ({})", title, rows.len()),
+        open: !rows.is_empty(),
+        blocks: vec![Block::Table { headers, rows }],
+    }
+}
+
+/// Address, lovelace, assets, datum and reference script of an output.
+fn output_cells(output: &Value) -> Vec<String> {
+    let assets: Vec<_> = output["value"]["assets"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .flat_map(|(policy, assets)| {
+            assets
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(move |(name, quantity)| format!("{}.{}: {}", policy, name, text(quantity)))
+        })
+        .collect();
+
+    let datum = match &output["datum"] {
+        Value::Null => "—".to_string(),
+        datum if datum.get("hash").is_some() => format!("hash {}", text(&datum["hash"])),
+        datum => format!("inline {}", truncate(&datum["inline"].to_string(), 60)),
+    };
+
+    let script = match &output["referenceScript"] {
+        Value::Null => "—".to_string(),
+        script => format!("{} {}", text(&script["language"]), text(&script["hash"])),
+    };
+
+    vec![
+        text(&output["address"]),
+        text(&output["value"]["lovelace"]),
+        if assets.is_empty() {
+            "—".to_string()
+        } else {
+            assets.join(", ")
+        },
+        datum,
+        script,
+    ]
+}
+
+fn entries(value: &Value) -> impl Iterator<Item = (usize, &Value)> {
+    value.as_array().into_iter().flatten().enumerate()
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => "—".to_string(),
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn list(value: &Value) -> String {
+    let items: Vec<_> = value.as_array().into_iter().flatten().map(text).collect();
+    if items.is_empty() {
+        "—".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// PlutusV3 fees are lovelace, PlutusV1 fees a value.
+fn lovelace(fee: &Value) -> String {
+    match fee.get("lovelace") {
+        Some(lovelace) => format!("{} lovelace", text(lovelace)),
+        None => format!("{} lovelace", text(fee)),
+    }
+}
+
+fn bound(bound: &Value) -> String {
+    match bound.as_u64() {
+        Some(ms) => format!("{} ms", ms),
+        None => "∞".to_string(),
+    }
+}
+
+fn truncate(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+
+    format!("{}…", text.chars().take(length).collect::<String>())
+}
+
+fn render_markdown(title: &str, contexts: &[(String, Vec<Section>)]) -> String {
+    let mut document = format!("# {}\n", title);
+
+    for (heading, sections) in contexts {
+        document.push_str(&format!("\n## {}\n", heading));
+
+        for section in sections {
+            document.push_str(&format!(
+                "\n<details{}>\n<summary>{}</summary>\n\n",
+                if section.open { " open" } else { "" },
+                section.title
+            ));
+            for block in &section.blocks {
+                match block {
+                    Block::Table { rows, .. } if rows.is_empty() => document.push_str("None\n"),
+                    Block::Table { headers, rows } => {
+                        document.push_str(&format!("| {} |\n", headers.join(" | ")));
+                        document.push_str(&format!("|{}\n", "---|".repeat(headers.len())));
+                        for row in rows {
+                            let cells: Vec<_> =
+                                row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                            document.push_str(&format!("| {} |\n", cells.join(" | ")));
+                        }
+                    }
+                    Block::Code(code) => document.push_str(&format!("```\n{}\n```\n", code)),
+                }
+            }
+            document.push_str("\n</details>\n");
+        }
+    }
+
+    document
+}
+
+fn render_html(title: &str, contexts: &[(String, Vec<Section>)]) -> String {
+    let mut document = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; margin: 0.5em 0; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; font-family: monospace; }}\n\
+         pre {{ background: #f6f8fa; padding: 1em; overflow-x: auto; white-space: pre-wrap; word-break: break-all; }}\n\
+         summary {{ cursor: pointer; font-weight: bold; margin: 0.5em 0; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+        title = escape(title)
+    );
+
+    for (heading, sections) in contexts {
+        document.push_str(&format!("<h2>{}</h2>\n", escape(heading)));
+
+        for section in sections {
+            document.push_str(&format!(
+                "<details{}>\n<summary>{}</summary>\n",
+                if section.open { " open" } else { "" },
+                escape(&section.title)
+            ));
+            for block in &section.blocks {
+                match block {
+                    Block::Table { rows, .. } if rows.is_empty() => {
+                        document.push_str("<p>None</p>\n")
+                    }
+                    Block::Table { headers, rows } => {
+                        document.push_str("<table>\n<tr>");
+                        for header in *headers {
+                            document.push_str(&format!("<th>{}</th>", escape(header)));
+                        }
+                        document.push_str("</tr>\n");
+                        for row in rows {
+                            document.push_str("<tr>");
+                            for cell in row {
+                                document.push_str(&format!("<td>{}</td>", escape(cell)));
+                            }
+                            document.push_str("</tr>\n");
+                        }
+                        document.push_str("</table>\n");
+                    }
+                    Block::Code(code) => {
+                        document.push_str(&format!("<pre>{}</pre>\n", escape(code)))
+                    }
+                }
+            }
+            document.push_str("</details>\n");
+        }
+    }
+
+    document.push_str("</body>\n</html>\n");
+    document
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,521 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use amaru_kernel::{
+    Hash, MemoizedDatum, MemoizedTransactionOutput, PlutusData, TransactionInput,
+    network::NetworkName,
+};
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::{
+    blockfrost::Blockfrost,
+    color,
+    config::{Config, Provider},
+    evaluate::Evaluation,
+    formatter::ReadableFormatter,
+    kupo::Kupo,
+    output::{decode_output, placeholder_output},
+    protocol::CostModels,
+    snapshot::UtxoFile,
+    stats::ProviderStats,
+    utxo_rpc::UtxoRpc,
+};
+
+pub type ResolvedUtxos = BTreeMap<TransactionInput, MemoizedTransactionOutput>;
+
+pub type ResolvedDatums = BTreeMap<Hash<32>, PlutusData>;
+
+/// A source of chain data able to resolve the outputs spent or referenced by a transaction.
+#[async_trait]
+pub trait UtxoResolver: Send + Sync {
+    /// Resolve the inputs this provider knows about. Inputs it has never seen are left out of
+    /// the result rather than failing, so another provider can be asked for them.
+    async fn resolve(&self, inputs: &[TransactionInput]) -> Result<ResolvedUtxos>;
+
+    /// Slot of the most recent block known to the provider.
+    async fn tip(&self) -> Result<u64> {
+        bail!("This provider does not support querying the chain tip. Use --slot instead")
+    }
+
+    /// Cost models currently in effect on the provider's chain.
+    async fn cost_models(&self) -> Result<CostModels> {
+        bail!(
+            "{} does not serve protocol parameters. Use --cost-models",
+            self.name()
+        )
+    }
+
+    /// Protocol parameters currently in effect on the provider's chain, as JSON.
+    async fn protocol_parameters(&self) -> Result<Value> {
+        bail!(
+            "{} does not serve protocol parameters. Use --protocol-params",
+            self.name()
+        )
+    }
+
+    /// Look up the preimage of a datum hash, if the provider has seen it.
+    async fn resolve_datum(&self, _hash: &Hash<32>) -> Result<Option<PlutusData>> {
+        Ok(None)
+    }
+
+    /// CBOR of a transaction already included in a block, if the provider knows it.
+    async fn fetch_transaction(&self, _hash: &Hash<32>) -> Result<Option<Vec<u8>>> {
+        bail!(
+            "{} does not serve transaction CBOR. Use Blockfrost or pass --tx-file",
+            self.name()
+        )
+    }
+
+    /// Hashes of the transactions of a block, in block order.
+    async fn block_transactions(&self, _hash: &Hash<32>) -> Result<Vec<Hash<32>>> {
+        bail!(
+            "{} does not serve block contents. Use Blockfrost or pass --block-file",
+            self.name()
+        )
+    }
+
+    /// CBOR of a transaction waiting in the provider's mempool, if it is still pending there.
+    async fn fetch_mempool_transaction(&self, _hash: &Hash<32>) -> Result<Option<Vec<u8>>> {
+        bail!(
+            "{} does not expose the CBOR of mempool transactions. Use a UTxO-RPC provider",
+            self.name()
+        )
+    }
+
+    /// Evaluate the scripts of a transaction against the provider's ledger state.
+    async fn evaluate_transaction(&self, _transaction: &[u8]) -> Result<Vec<Evaluation>> {
+        bail!(
+            "{} can't evaluate transactions. Set OGMIOS_URL or `ogmios.url` in nawi.toml",
+            self.name()
+        )
+    }
+
+    /// Request counters for this run, one entry per provider that talks to the network.
+    async fn stats(&self) -> Vec<ProviderStats> {
+        Vec::new()
+    }
+
+    fn name(&self) -> &'static str;
+}
+
+/// Asks each resolver in turn for the inputs that the previous ones couldn't resolve.
+pub struct Fallback {
+    resolvers: Vec<Box<dyn UtxoResolver>>,
+}
+
+#[async_trait]
+impl UtxoResolver for Fallback {
+    async fn resolve(&self, inputs: &[TransactionInput]) -> Result<ResolvedUtxos> {
+        let mut resolved = ResolvedUtxos::new();
+
+        for resolver in &self.resolvers {
+            let missing: Vec<_> = inputs
+                .iter()
+                .filter(|input| !resolved.contains_key(input))
+                .cloned()
+                .collect();
+
+            if missing.is_empty() {
+                break;
+            }
+
+            match resolver.resolve(&missing).await {
+                Ok(found) => resolved.extend(found),
+                Err(e) => color::warning(format!(
+                    "{} failed, trying the next provider: {:#}",
+                    resolver.name(),
+                    e
+                )),
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    async fn tip(&self) -> Result<u64> {
+        let mut last_error = anyhow!("No provider configured");
+
+        for resolver in &self.resolvers {
+            match resolver.tip().await {
+                Ok(slot) => return Ok(slot),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    async fn cost_models(&self) -> Result<CostModels> {
+        let mut last_error = anyhow!("No provider configured");
+
+        for resolver in &self.resolvers {
+            match resolver.cost_models().await {
+                Ok(cost_models) => return Ok(cost_models),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    async fn protocol_parameters(&self) -> Result<Value> {
+        let mut last_error = anyhow!("No provider configured");
+
+        for resolver in &self.resolvers {
+            match resolver.protocol_parameters().await {
+                Ok(parameters) => return Ok(parameters),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    async fn resolve_datum(&self, hash: &Hash<32>) -> Result<Option<PlutusData>> {
+        for resolver in &self.resolvers {
+            match resolver.resolve_datum(hash).await {
+                Ok(Some(datum)) => return Ok(Some(datum)),
+                Ok(None) => {}
+                Err(e) => color::warning(format!(
+                    "{} failed, trying the next provider: {:#}",
+                    resolver.name(),
+                    e
+                )),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn fetch_transaction(&self, hash: &Hash<32>) -> Result<Option<Vec<u8>>> {
+        for resolver in &self.resolvers {
+            match resolver.fetch_transaction(hash).await {
+                Ok(Some(transaction)) => return Ok(Some(transaction)),
+                Ok(None) => {}
+                Err(e) => color::warning(format!(
+                    "{} failed, trying the next provider: {:#}",
+                    resolver.name(),
+                    e
+                )),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn block_transactions(&self, hash: &Hash<32>) -> Result<Vec<Hash<32>>> {
+        let mut last_error = anyhow!("No provider configured");
+
+        for resolver in &self.resolvers {
+            match resolver.block_transactions(hash).await {
+                Ok(transactions) => return Ok(transactions),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    async fn fetch_mempool_transaction(&self, hash: &Hash<32>) -> Result<Option<Vec<u8>>> {
+        for resolver in &self.resolvers {
+            match resolver.fetch_mempool_transaction(hash).await {
+                Ok(Some(transaction)) => return Ok(Some(transaction)),
+                Ok(None) => {}
+                Err(e) => color::warning(format!(
+                    "{} failed, trying the next provider: {:#}",
+                    resolver.name(),
+                    e
+                )),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn evaluate_transaction(&self, transaction: &[u8]) -> Result<Vec<Evaluation>> {
+        let mut last_error = anyhow!("No provider configured");
+
+        for resolver in &self.resolvers {
+            match resolver.evaluate_transaction(transaction).await {
+                Ok(evaluations) => return Ok(evaluations),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    async fn stats(&self) -> Vec<ProviderStats> {
+        let mut stats = Vec::new();
+        for resolver in &self.resolvers {
+            stats.extend(resolver.stats().await);
+        }
+        stats
+    }
+
+    fn name(&self) -> &'static str {
+        "fallback chain"
+    }
+}
+
+/// Resolve every input, failing with the full list of those no provider could find.
+pub async fn resolve_all(
+    resolver: &dyn UtxoResolver,
+    inputs: &[TransactionInput],
+) -> Result<ResolvedUtxos> {
+    let resolved = resolver.resolve(inputs).await?;
+
+    let missing: Vec<_> = inputs
+        .iter()
+        .filter(|input| !resolved.contains_key(input))
+        .map(|input| input.format_readable())
+        .collect();
+
+    if !missing.is_empty() {
+        bail!(
+            "Could not resolve {} input(s) from {}: {}",
+            missing.len(),
+            resolver.name(),
+            missing.join(", ")
+        );
+    }
+
+    Ok(resolved)
+}
+
+/// Look up the preimages of the datum hashes carried by the given outputs. Hashes the provider
+/// doesn't know are left out.
+pub async fn resolve_datums(
+    resolver: &dyn UtxoResolver,
+    utxos: &ResolvedUtxos,
+) -> Result<ResolvedDatums> {
+    let hashes: BTreeSet<_> = utxos
+        .values()
+        .filter_map(|output| match &output.datum {
+            MemoizedDatum::Hash(hash) => Some(*hash),
+            _ => None,
+        })
+        .collect();
+
+    let mut datums = ResolvedDatums::new();
+    for hash in hashes {
+        if let Some(datum) = resolver.resolve_datum(&hash).await? {
+            datums.insert(hash, datum);
+        }
+    }
+
+    Ok(datums)
+}
+
+/// Resolve every input, standing in a placeholder output for those no provider could find.
+/// Returns the outputs, and the inputs given a placeholder.
+pub async fn resolve_with_placeholders(
+    resolver: &dyn UtxoResolver,
+    inputs: &[TransactionInput],
+    network: NetworkName,
+) -> Result<(ResolvedUtxos, BTreeSet<TransactionInput>)> {
+    let mut resolved = resolver.resolve(inputs).await?;
+    let mut placeholders = BTreeSet::new();
+
+    for input in inputs {
+        if !resolved.contains_key(input) {
+            color::warning(format!(
+                "Could not resolve {}. Using a placeholder output with no value and no datum; the script context will not match what the ledger builds",
+                input.format_readable()
+            ));
+            resolved.insert(input.clone(), placeholder_output(network)?);
+            placeholders.insert(input.clone());
+        }
+    }
+
+    Ok((resolved, placeholders))
+}
+
+pub fn from_config(config: &Config) -> Result<Box<dyn UtxoResolver>> {
+    let mut resolvers = config
+        .provider
+        .iter()
+        .map(|provider| build(config, *provider))
+        .collect::<Result<Vec<_>>>()?;
+
+    match resolvers.len() {
+        0 => bail!("No provider configured"),
+        1 => Ok(resolvers.remove(0)),
+        _ => Ok(Box::new(Fallback { resolvers })),
+    }
+}
+
+fn build(config: &Config, provider: Provider) -> Result<Box<dyn UtxoResolver>> {
+    match provider {
+        Provider::Blockfrost => {
+            let blockfrost = config.blockfrost.as_ref().ok_or_else(|| {
+                anyhow!("Blockfrost is not configured. Set BLOCKFROST_KEY or `key` in nawi.toml")
+            })?;
+            Ok(Box::new(Blockfrost::new(
+                blockfrost,
+                &config.http,
+                &config.retry,
+                config.max_concurrency,
+            )?))
+        }
+        Provider::Kupo => {
+            let kupo = config.kupo.as_ref().ok_or_else(|| {
+                anyhow!("Kupo is not configured. Set KUPO_URL or `kupo.url` in nawi.toml")
+            })?;
+            Ok(Box::new(Kupo::new(
+                kupo,
+                &config.http,
+                &config.retry,
+                config.max_concurrency,
+            )?))
+        }
+        Provider::UtxoRpc => {
+            let utxorpc = config.utxorpc.as_ref().ok_or_else(|| {
+                anyhow!("UTxO-RPC is not configured. Set UTXORPC_URL or `utxorpc.url` in nawi.toml")
+            })?;
+            Ok(Box::new(UtxoRpc::new(utxorpc)))
+        }
+        Provider::File => {
+            let path = config
+                .utxo_file
+                .as_ref()
+                .ok_or_else(|| anyhow!("No UTxO file configured. Set `utxo_file` in nawi.toml"))?;
+            Ok(Box::new(UtxoFile::load(path)?))
+        }
+    }
+}
+
+/// Parse an output reference written as `txhash#index`.
+pub fn parse_output_reference(reference: &str) -> Result<TransactionInput> {
+    let (tx_hash, index) = reference.split_once('#').ok_or_else(|| {
+        anyhow!(
+            "Invalid output reference {}, expected <txhash>#<index>",
+            reference
+        )
+    })?;
+
+    Ok(TransactionInput {
+        transaction_id: tx_hash
+            .parse()
+            .map_err(|_| anyhow!("Invalid transaction hash in {}", reference))?,
+        index: index
+            .parse()
+            .context(format!("Invalid output index in {}", reference))?,
+    })
+}
+
+/// Parse a `txhash#index=OUTPUT` override, where OUTPUT is either hex-encoded output CBOR or a
+/// file holding it (as hex or raw bytes).
+pub fn parse_utxo_override(
+    utxo_override: &str,
+) -> Result<(TransactionInput, MemoizedTransactionOutput)> {
+    let (reference, output) = utxo_override.split_once('=').ok_or_else(|| {
+        anyhow!(
+            "Invalid UTxO override {}, expected <txhash>#<index>=<output-cbor|file>",
+            utxo_override
+        )
+    })?;
+
+    let input = parse_output_reference(reference)?;
+
+    let path = Path::new(output);
+    let bytes = if path.is_file() {
+        let contents = std::fs::read(path)
+            .with_context(|| format!("Failed to read output file: {}", path.display()))?;
+        match std::str::from_utf8(&contents).map(|text| hex::decode(text.trim())) {
+            Ok(Ok(decoded)) => decoded,
+            _ => contents,
+        }
+    } else {
+        hex::decode(output.trim()).context(format!(
+            "Override for {} is neither a file nor valid hex",
+            reference
+        ))?
+    };
+
+    let output = decode_output(&bytes)
+        .with_context(|| format!("Invalid output override for {}", reference))?;
+
+    Ok((input, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Knows the outputs it is given, and nothing else.
+    struct Known(ResolvedUtxos);
+
+    #[async_trait]
+    impl UtxoResolver for Known {
+        async fn resolve(&self, inputs: &[TransactionInput]) -> Result<ResolvedUtxos> {
+            Ok(inputs
+                .iter()
+                .filter_map(|input| Some((input.clone(), self.0.get(input)?.clone())))
+                .collect())
+        }
+
+        fn name(&self) -> &'static str {
+            "known"
+        }
+    }
+
+    fn input(index: u64) -> TransactionInput {
+        TransactionInput {
+            transaction_id: Hash::new([0; 32]),
+            index,
+        }
+    }
+
+    #[tokio::test]
+    async fn placeholders_stand_in_for_unresolved_inputs() {
+        let output = placeholder_output(NetworkName::Preprod).unwrap();
+        let resolver = Known(ResolvedUtxos::from([(input(0), output)]));
+
+        let (resolved, placeholders) =
+            resolve_with_placeholders(&resolver, &[input(0), input(1)], NetworkName::Preprod)
+                .await
+                .unwrap();
+        assert_eq!(resolved.len(), 2);
+        assert_eq!(placeholders, BTreeSet::from([input(1)]));
+    }
+
+    /// Fails every request.
+    struct Down;
+
+    #[async_trait]
+    impl UtxoResolver for Down {
+        async fn resolve(&self, _inputs: &[TransactionInput]) -> Result<ResolvedUtxos> {
+            bail!("unreachable")
+        }
+
+        fn name(&self) -> &'static str {
+            "down"
+        }
+    }
+
+    #[tokio::test]
+    async fn fallback_asks_the_next_provider_for_what_is_missing() {
+        let output = placeholder_output(NetworkName::Preprod).unwrap();
+        let fallback = Fallback {
+            resolvers: vec![
+                Box::new(Down),
+                Box::new(Known(ResolvedUtxos::from([(input(0), output.clone())]))),
+                Box::new(Known(ResolvedUtxos::from([(input(1), output)]))),
+            ],
+        };
+
+        let resolved = fallback
+            .resolve(&[input(0), input(1), input(2)])
+            .await
+            .unwrap();
+        assert_eq!(
+            resolved.keys().cloned().collect::<Vec<_>>(),
+            vec![input(0), input(1)]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    future::Future,
+    hash::{BuildHasher, RandomState},
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::stats::RequestStats;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Total attempts per request, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each subsequent one.
+    pub backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Randomize each delay between half and all of its value, so concurrent retries spread out.
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff_ms: 500,
+            max_backoff_ms: 10_000,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Delay to wait before the given retry (starting at 1).
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponential = self
+            .backoff_ms
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(self.max_backoff_ms);
+
+        let millis = if self.jitter && exponential > 0 {
+            // A freshly seeded hasher is a good enough source of randomness for jitter.
+            let random = RandomState::new().hash_one(retry);
+            exponential / 2 + random % (exponential / 2 + 1)
+        } else {
+            exponential
+        };
+
+        Duration::from_millis(millis)
+    }
+}
+
+/// Run a request, retrying with backoff for as long as it fails with a transient error.
+pub async fn retry<T, E, F, Fut>(
+    config: &RetryConfig,
+    stats: &RequestStats,
+    is_transient: impl Fn(&E) -> bool,
+    request: F,
+) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut retries = 0;
+
+    loop {
+        stats.record_request();
+        match request().await {
+            Err(e) if is_transient(&e) && retries + 1 < config.max_attempts => {
+                retries += 1;
+                stats.record_retry();
+                tokio::time::sleep(config.delay(retries)).await;
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use amaru_kernel::{KeyValuePairs, MaybeIndefArray, PlutusData, cbor, to_cbor};
+use anyhow::{Context, Result, bail};
+
+/// Encode a script context, decode it back and check that nothing changed on the way, so that
+/// encoder asymmetries surface here rather than in the tools the hex is pasted into.
+pub fn verify(data: &PlutusData) -> Result<()> {
+    let bytes = to_cbor(data);
+    let decoded: PlutusData =
+        cbor::decode(&bytes).context("The encoded script context doesn't decode as PlutusData")?;
+
+    if let Some(difference) = difference(data, &decoded, "context") {
+        bail!(
+            "The encoded script context doesn't decode back to the same data, {}",
+            difference
+        );
+    }
+
+    if to_cbor(&decoded) != bytes {
+        bail!("The encoded script context doesn't re-encode to the same bytes");
+    }
+
+    Ok(())
+}
+
+/// Where two Data values first differ, including in how their arrays and maps are encoded.
+fn difference(original: &PlutusData, decoded: &PlutusData, path: &str) -> Option<String> {
+    match (original, decoded) {
+        (PlutusData::Constr(a), PlutusData::Constr(b)) => {
+            if a.tag != b.tag || a.any_constructor != b.any_constructor {
+                return Some(format!(
+                    "{}: constructor {} decoded as {}",
+                    path, a.tag, b.tag
+                ));
+            }
+            if let Some(difference) = array_encoding(&a.fields, &b.fields, path) {
+                return Some(difference);
+            }
+            elements(&a.fields, &b.fields, path)
+        }
+        (PlutusData::Array(a), PlutusData::Array(b)) => {
+            array_encoding(a, b, path).or_else(|| elements(a, b, path))
+        }
+        (PlutusData::Map(a), PlutusData::Map(b)) => {
+            let (a_definite, b_definite) = (
+                matches!(a, KeyValuePairs::Def(_)),
+                matches!(b, KeyValuePairs::Def(_)),
+            );
+            if a_definite != b_definite {
+                return Some(format!(
+                    "{}: {} map decoded as {}",
+                    path,
+                    length(a_definite),
+                    length(b_definite)
+                ));
+            }
+            if a.len() != b.len() {
+                return Some(format!(
+                    "{}: map of {} entries decoded with {}",
+                    path,
+                    a.len(),
+                    b.len()
+                ));
+            }
+            a.iter()
+                .zip(b.iter())
+                .enumerate()
+                .find_map(|(position, ((ka, va), (kb, vb)))| {
+                    difference(ka, kb, &format!("{}[{}].key", path, position))
+                        .or_else(|| difference(va, vb, &format!("{}[{}]", path, position)))
+                })
+        }
+        (a, b) if a == b => None,
+        (PlutusData::BigInt(_), PlutusData::BigInt(_)) => {
+            Some(format!("{}: integer decoded with a different value", path))
+        }
+        (PlutusData::BoundedBytes(_), PlutusData::BoundedBytes(_)) => {
+            Some(format!("{}: bytes decoded with a different value", path))
+        }
+        _ => Some(format!("{}: decoded as a different kind of Data", path)),
+    }
+}
+
+fn array_encoding(
+    a: &MaybeIndefArray<PlutusData>,
+    b: &MaybeIndefArray<PlutusData>,
+    path: &str,
+) -> Option<String> {
+    let a = matches!(a, MaybeIndefArray::Def(_));
+    let b = matches!(b, MaybeIndefArray::Def(_));
+
+    (a != b).then(|| format!("{}: {} array decoded as {}", path, length(a), length(b)))
+}
+
+fn elements(a: &[PlutusData], b: &[PlutusData], path: &str) -> Option<String> {
+    if a.len() != b.len() {
+        return Some(format!(
+            "{}: {} element(s) decoded as {}",
+            path,
+            a.len(),
+            b.len()
+        ));
+    }
+
+    a.iter()
+        .zip(b)
+        .enumerate()
+        .find_map(|(position, (a, b))| difference(a, b, &format!("{}.{}", path, position)))
+}
+
+fn length(definite: bool) -> &'static str {
+    if definite {
+        "definite-length"
+    } else {
+        "indefinite-length"
+    }
+}
@@ -0,0 +1,304 @@
+use serde_json::{Value, json};
+
+/// The JSON Schema of the documents printed by `--output json` (and, one per line, by
+/// `--ndjson`), for downstream tools to validate and generate code against. It follows
+/// `JsonFormatter` field for field.
+pub fn json_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": "https://github.com/SundaeSwap-finance/nawi/schema/script-context.json",
+        "title": "nawi script context",
+        "description": "The script context of one redeemer, as printed by `nawi --output json`. With `--redeemer all`, an array of them is printed; with `--ndjson`, one per line along with the transaction.",
+        "type": "object",
+        "required": ["redeemer", "purpose", "index", "version", "context", "cbor"],
+        "properties": {
+            "redeemer": {
+                "description": "Position of the redeemer in the normalized redeemer list",
+                "type": "integer",
+                "minimum": 0
+            },
+            "purpose": {
+                "enum": ["Spend", "Mint", "Cert", "Reward", "Vote", "Propose"]
+            },
+            "index": {
+                "description": "Index the redeemer points at, within its purpose",
+                "type": "integer",
+                "minimum": 0
+            },
+            "version": { "$ref": "#/$defs/plutusVersion" },
+            "context": {
+                "oneOf": [
+                    { "$ref": "#/$defs/scriptContextV3" },
+                    { "$ref": "#/$defs/scriptContextV1" }
+                ]
+            },
+            "cbor": {
+                "description": "CBOR of the script context as Plutus data",
+                "$ref": "#/$defs/hex"
+            },
+            "transaction": {
+                "description": "Hash or file name of the transaction, with --ndjson only",
+                "type": "string"
+            }
+        },
+        "$defs": {
+            "hex": { "type": "string", "pattern": "^([0-9a-f]{2})*$" },
+            "hash28": { "type": "string", "pattern": "^[0-9a-f]{56}$" },
+            "hash32": { "type": "string", "pattern": "^[0-9a-f]{64}$" },
+            "lovelace": { "type": "integer", "minimum": 0 },
+            "plutusVersion": { "enum": ["PlutusV1", "PlutusV2", "PlutusV3"] },
+            "translationNotes": {
+                "description": "How each field was translated from the ledger, with --explain only",
+                "type": "array",
+                "items": { "type": "string" }
+            },
+            "scriptContextV3": {
+                "type": "object",
+                "required": ["version", "txInfo", "redeemer"],
+                "properties": {
+                    "version": { "const": "PlutusV3" },
+                    "txInfo": { "$ref": "#/$defs/txInfoV3" },
+                    "redeemer": {
+                        "type": "object",
+                        "required": ["purpose", "index"],
+                        "properties": {
+                            "purpose": { "type": "string" },
+                            "index": { "type": "integer", "minimum": 0 }
+                        }
+                    },
+                    "translationNotes": { "$ref": "#/$defs/translationNotes" }
+                }
+            },
+            "scriptContextV1": {
+                "type": "object",
+                "required": ["version", "txInfo", "purpose"],
+                "properties": {
+                    "version": { "const": "PlutusV1" },
+                    "txInfo": { "$ref": "#/$defs/txInfoV1" },
+                    "purpose": { "type": "string" },
+                    "datum": {
+                        "description": "Datum handed to spending scripts as a separate argument",
+                        "$ref": "#/$defs/data"
+                    },
+                    "translationNotes": { "$ref": "#/$defs/translationNotes" }
+                }
+            },
+            "txInfoV3": {
+                "type": "object",
+                "required": [
+                    "id", "inputs", "referenceInputs", "outputs", "fee", "mint", "certificates",
+                    "withdrawals", "validRange", "signatories", "redeemers"
+                ],
+                "properties": {
+                    "id": { "$ref": "#/$defs/hash32" },
+                    "inputs": { "type": "array", "items": { "$ref": "#/$defs/resolvedInput" } },
+                    "referenceInputs": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/resolvedInput" }
+                    },
+                    "outputs": { "type": "array", "items": { "$ref": "#/$defs/output" } },
+                    "fee": { "$ref": "#/$defs/lovelace" },
+                    "mint": { "$ref": "#/$defs/mint" },
+                    "certificates": { "$ref": "#/$defs/certificates" },
+                    "withdrawals": { "$ref": "#/$defs/withdrawals" },
+                    "validRange": { "$ref": "#/$defs/validRange" },
+                    "signatories": { "type": "array", "items": { "$ref": "#/$defs/hash28" } },
+                    "redeemers": { "type": "array", "items": { "$ref": "#/$defs/redeemer" } }
+                }
+            },
+            "txInfoV1": {
+                "type": "object",
+                "required": [
+                    "id", "inputs", "outputs", "fee", "mint", "certificates", "withdrawals",
+                    "validRange", "signatories", "redeemers"
+                ],
+                "properties": {
+                    "id": { "$ref": "#/$defs/hash32" },
+                    "inputs": { "type": "array", "items": { "$ref": "#/$defs/resolvedInput" } },
+                    "outputs": { "type": "array", "items": { "$ref": "#/$defs/output" } },
+                    "fee": { "$ref": "#/$defs/value" },
+                    "mint": { "$ref": "#/$defs/mint" },
+                    "certificates": { "$ref": "#/$defs/certificates" },
+                    "withdrawals": { "$ref": "#/$defs/withdrawals" },
+                    "validRange": { "$ref": "#/$defs/validRange" },
+                    "signatories": { "type": "array", "items": { "$ref": "#/$defs/hash28" } },
+                    "redeemers": { "type": "array", "items": { "$ref": "#/$defs/redeemer" } }
+                }
+            },
+            "resolvedInput": {
+                "type": "object",
+                "required": ["outputReference", "output"],
+                "properties": {
+                    "outputReference": {
+                        "description": "Transaction id and output index, as <id>#<index>",
+                        "type": "string"
+                    },
+                    "output": { "$ref": "#/$defs/output" }
+                }
+            },
+            "output": {
+                "type": "object",
+                "required": ["address", "value", "datum", "referenceScript"],
+                "properties": {
+                    "address": {
+                        "description": "Bech32 for Shelley addresses, base58 for Byron ones",
+                        "type": "string"
+                    },
+                    "value": { "$ref": "#/$defs/value" },
+                    "datum": {
+                        "oneOf": [
+                            { "type": "null" },
+                            {
+                                "type": "object",
+                                "required": ["hash"],
+                                "properties": { "hash": { "$ref": "#/$defs/hash32" } },
+                                "additionalProperties": false
+                            },
+                            {
+                                "type": "object",
+                                "required": ["inline"],
+                                "properties": { "inline": { "$ref": "#/$defs/data" } },
+                                "additionalProperties": false
+                            }
+                        ]
+                    },
+                    "referenceScript": {
+                        "oneOf": [
+                            { "type": "null" },
+                            {
+                                "type": "object",
+                                "required": ["language", "hash", "size"],
+                                "properties": {
+                                    "language": {
+                                        "enum": ["Native", "PlutusV1", "PlutusV2", "PlutusV3"]
+                                    },
+                                    "hash": { "$ref": "#/$defs/hash28" },
+                                    "size": {
+                                        "description": "Size of Plutus scripts in bytes, null for native ones",
+                                        "type": ["integer", "null"]
+                                    }
+                                }
+                            }
+                        ]
+                    }
+                }
+            },
+            "value": {
+                "type": "object",
+                "required": ["lovelace", "assets"],
+                "properties": {
+                    "lovelace": { "$ref": "#/$defs/lovelace" },
+                    "assets": { "$ref": "#/$defs/assets" }
+                }
+            },
+            "assets": {
+                "description": "Quantities by policy id, then by hex asset name",
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "additionalProperties": { "type": "integer" }
+                }
+            },
+            "mint": {
+                "description": "Minted (positive) and burnt (negative) quantities",
+                "$ref": "#/$defs/assets"
+            },
+            "certificates": {
+                "description": "Certificates in their readable form",
+                "type": "array",
+                "items": { "type": "string" }
+            },
+            "withdrawals": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["stakeAddress", "lovelace"],
+                    "properties": {
+                        "stakeAddress": { "type": "string" },
+                        "lovelace": { "$ref": "#/$defs/lovelace" }
+                    }
+                }
+            },
+            "validRange": {
+                "description": "Bounds in POSIX milliseconds, null when unbounded",
+                "type": "object",
+                "required": ["lower", "upper"],
+                "properties": {
+                    "lower": { "type": ["integer", "null"] },
+                    "upper": { "type": ["integer", "null"] }
+                }
+            },
+            "redeemer": {
+                "type": "object",
+                "required": ["purpose", "index", "data", "exUnits"],
+                "properties": {
+                    "purpose": { "type": "string" },
+                    "index": { "type": "integer", "minimum": 0 },
+                    "data": { "$ref": "#/$defs/data" },
+                    "exUnits": {
+                        "type": "object",
+                        "required": ["mem", "steps"],
+                        "properties": {
+                            "mem": { "type": "integer", "minimum": 0 },
+                            "steps": { "type": "integer", "minimum": 0 }
+                        }
+                    }
+                }
+            },
+            "data": {
+                "description": "Plutus data in the detailed schema of cardano-cli. Integers beyond the range of JSON numbers are strings, bignums hex.",
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "required": ["constructor", "fields"],
+                        "properties": {
+                            "constructor": { "type": "integer", "minimum": 0 },
+                            "fields": { "type": "array", "items": { "$ref": "#/$defs/data" } }
+                        },
+                        "additionalProperties": false
+                    },
+                    {
+                        "type": "object",
+                        "required": ["map"],
+                        "properties": {
+                            "map": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["k", "v"],
+                                    "properties": {
+                                        "k": { "$ref": "#/$defs/data" },
+                                        "v": { "$ref": "#/$defs/data" }
+                                    }
+                                }
+                            }
+                        },
+                        "additionalProperties": false
+                    },
+                    {
+                        "type": "object",
+                        "required": ["list"],
+                        "properties": {
+                            "list": { "type": "array", "items": { "$ref": "#/$defs/data" } }
+                        },
+                        "additionalProperties": false
+                    },
+                    {
+                        "type": "object",
+                        "required": ["int"],
+                        "properties": {
+                            "int": { "type": ["integer", "string"] }
+                        },
+                        "additionalProperties": false
+                    },
+                    {
+                        "type": "object",
+                        "required": ["bytes"],
+                        "properties": { "bytes": { "$ref": "#/$defs/hex" } },
+                        "additionalProperties": false
+                    }
+                ]
+            }
+        }
+    })
+}
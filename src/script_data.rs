@@ -0,0 +1,115 @@
+use amaru_kernel::{BigInt, PlutusData};
+
+/// Render Data in the `ScriptDataJsonDetailedSchema` of `cardano-cli --script-data-file`.
+///
+/// The JSON is written by hand rather than through serde_json so that integers of any size come
+/// out as exact JSON numbers, as cardano-cli expects.
+pub fn detailed_schema(data: &PlutusData) -> String {
+    let mut json = String::new();
+    write_data(data, &mut json);
+    json
+}
+
+fn write_data(data: &PlutusData, json: &mut String) {
+    match data {
+        PlutusData::Constr(constr) => {
+            json.push_str(&format!(
+                "{{\"constructor\":{},\"fields\":[",
+                constructor(constr.tag, constr.any_constructor)
+            ));
+            write_list(constr.fields.iter(), json);
+            json.push_str("]}");
+        }
+        PlutusData::Map(pairs) => {
+            json.push_str("{\"map\":[");
+            for (position, (key, value)) in pairs.iter().enumerate() {
+                if position > 0 {
+                    json.push(',');
+                }
+                json.push_str("{\"k\":");
+                write_data(key, json);
+                json.push_str(",\"v\":");
+                write_data(value, json);
+                json.push('}');
+            }
+            json.push_str("]}");
+        }
+        PlutusData::Array(elements) => {
+            json.push_str("{\"list\":[");
+            write_list(elements.iter(), json);
+            json.push_str("]}");
+        }
+        PlutusData::BigInt(int) => {
+            json.push_str(&format!("{{\"int\":{}}}", integer(int)));
+        }
+        PlutusData::BoundedBytes(bytes) => {
+            json.push_str(&format!(
+                "{{\"bytes\":\"{}\"}}",
+                hex::encode(bytes.to_vec())
+            ));
+        }
+    }
+}
+
+fn write_list<'a>(elements: impl Iterator<Item = &'a PlutusData>, json: &mut String) {
+    for (position, element) in elements.enumerate() {
+        if position > 0 {
+            json.push(',');
+        }
+        write_data(element, json);
+    }
+}
+
+/// Index of a constructor, from its CBOR tag.
+pub fn constructor(tag: u64, any_constructor: Option<u64>) -> u64 {
+    match tag {
+        121..=127 => tag - 121,
+        1280..=1400 => tag - 1280 + 7,
+        _ => any_constructor.unwrap_or_default(),
+    }
+}
+
+/// The decimal digits of an integer. Bignums are big-endian magnitudes; negative ones encode
+/// `-1 - n`.
+pub fn integer(int: &BigInt) -> String {
+    match int {
+        BigInt::Int(int) => i128::from(int.0).to_string(),
+        BigInt::BigUInt(bytes) => decimal(&bytes.to_vec()),
+        BigInt::BigNInt(bytes) => {
+            let mut magnitude = bytes.to_vec();
+            // -1 - n has magnitude n + 1.
+            let mut carry = true;
+            for byte in magnitude.iter_mut().rev() {
+                if !carry {
+                    break;
+                }
+                (*byte, carry) = byte.overflowing_add(1);
+            }
+            if carry {
+                magnitude.insert(0, 1);
+            }
+            format!("-{}", decimal(&magnitude))
+        }
+    }
+}
+
+fn decimal(big_endian: &[u8]) -> String {
+    let mut magnitude = big_endian.to_vec();
+    let mut digits = Vec::new();
+
+    while magnitude.iter().any(|byte| *byte != 0) {
+        let mut remainder = 0u32;
+        for byte in magnitude.iter_mut() {
+            let current = (remainder << 8) | u32::from(*byte);
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(char::from(b'0' + remainder as u8));
+    }
+
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+
+    digits.iter().rev().collect()
+}
@@ -0,0 +1,348 @@
+use std::{collections::BTreeSet, fmt};
+
+use amaru_kernel::{
+    Address, Certificate, ComputeHash, GovAction, Hash, MemoizedScript, MintedTx, Nullable,
+    OriginalHash, Redeemer, ScriptPurpose, ShelleyPaymentPart, StakeCredential, TransactionInput,
+    Voter,
+};
+
+use crate::{
+    PlutusVersion, collect_all_inputs, formatter::ReadableFormatter, json, resolver::ResolvedUtxos,
+};
+
+/// Header bit set in reward accounts whose credential is a script.
+const SCRIPT_CREDENTIAL_BIT: u8 = 0b0001_0000;
+
+/// Hash of the validator a redeemer is executed for: the script locking the spent input, the
+/// minting policy, the script credential of the certificate, withdrawal or voter it points to,
+/// or the guardrail script of the proposal.
+pub fn target_script_hash(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    redeemer: &Redeemer,
+) -> Option<Hash<28>> {
+    pointer_script_hash(transaction, utxos, redeemer.tag, redeemer.index as usize)
+}
+
+/// Hash of the script credential the item at a redeemer pointer (purpose and index) is locked by
+/// or acts on behalf of, whether or not a redeemer points at it.
+pub fn pointer_script_hash(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    purpose: ScriptPurpose,
+    index: usize,
+) -> Option<Hash<28>> {
+    let body = &transaction.transaction_body;
+
+    match purpose {
+        ScriptPurpose::Spend => {
+            let mut inputs: Vec<_> = body.inputs.iter().collect();
+            inputs.sort();
+            match &utxos.get(inputs.get(index)?)?.address {
+                Address::Shelley(address) => match address.payment() {
+                    ShelleyPaymentPart::Script(hash) => Some(*hash),
+                    ShelleyPaymentPart::Key(_) => None,
+                },
+                _ => None,
+            }
+        }
+        ScriptPurpose::Mint => {
+            let mut policies: Vec<_> = body
+                .mint
+                .as_ref()?
+                .iter()
+                .map(|(policy, _)| *policy)
+                .collect();
+            policies.sort();
+            policies.get(index).copied()
+        }
+        ScriptPurpose::Cert => {
+            match certificate_credential(body.certificates.as_ref()?.get(index)?)? {
+                StakeCredential::ScriptHash(hash) => Some(*hash),
+                StakeCredential::AddrKeyhash(_) => None,
+            }
+        }
+        ScriptPurpose::Reward => {
+            // The ledger orders reward accounts by credential, script credentials first.
+            let mut accounts: Vec<_> = body
+                .withdrawals
+                .as_ref()?
+                .iter()
+                .filter(|(account, _)| account.len() == 29)
+                .map(|(account, _)| (account[0] & SCRIPT_CREDENTIAL_BIT == 0, &account[1..]))
+                .collect();
+            accounts.sort();
+            match accounts.get(index)? {
+                (false, hash) => Some(Hash::from(*hash)),
+                (true, _) => None,
+            }
+        }
+        ScriptPurpose::Vote => {
+            // The ledger orders voters by kind (committee, DRep, pool), script credentials
+            // first.
+            let mut voters: Vec<_> = body
+                .voting_procedures
+                .as_ref()?
+                .iter()
+                .map(|(voter, _)| match voter {
+                    Voter::ConstitutionalCommitteeScript(hash) => (0, false, *hash),
+                    Voter::ConstitutionalCommitteeKey(hash) => (0, true, *hash),
+                    Voter::DRepScript(hash) => (1, false, *hash),
+                    Voter::DRepKey(hash) => (1, true, *hash),
+                    Voter::StakePoolKey(hash) => (2, true, *hash),
+                })
+                .collect();
+            voters.sort();
+            match voters.get(index)? {
+                (_, false, hash) => Some(*hash),
+                (_, true, _) => None,
+            }
+        }
+        ScriptPurpose::Propose => {
+            // Proposals are pointed to by position, and run the guardrail script.
+            let proposal = body.proposal_procedures.as_ref()?.get(index)?;
+            match &proposal.gov_action {
+                GovAction::ParameterChange(_, _, Nullable::Some(guardrail))
+                | GovAction::TreasuryWithdrawals(_, Nullable::Some(guardrail)) => Some(*guardrail),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Language of the Plutus script with the given hash, looked up among the scripts witnessed by
+/// the transaction and the reference scripts of the resolved outputs.
+pub fn script_version(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    script_hash: &Hash<28>,
+) -> Option<PlutusVersion> {
+    let witnesses = &transaction.transaction_witness_set;
+
+    let witnessed = [
+        witnesses
+            .plutus_v1_script
+            .iter()
+            .flat_map(|scripts| scripts.iter())
+            .any(|script| script.compute_hash() == *script_hash)
+            .then_some(PlutusVersion::PlutusV1),
+        witnesses
+            .plutus_v2_script
+            .iter()
+            .flat_map(|scripts| scripts.iter())
+            .any(|script| script.compute_hash() == *script_hash)
+            .then_some(PlutusVersion::PlutusV2),
+        witnesses
+            .plutus_v3_script
+            .iter()
+            .flat_map(|scripts| scripts.iter())
+            .any(|script| script.compute_hash() == *script_hash)
+            .then_some(PlutusVersion::PlutusV3),
+    ];
+
+    witnessed.into_iter().flatten().next().or_else(|| {
+        utxos
+            .values()
+            .filter_map(|output| match output.script.as_ref()? {
+                MemoizedScript::PlutusV1Script(script) if script.compute_hash() == *script_hash => {
+                    Some(PlutusVersion::PlutusV1)
+                }
+                MemoizedScript::PlutusV2Script(script) if script.compute_hash() == *script_hash => {
+                    Some(PlutusVersion::PlutusV2)
+                }
+                MemoizedScript::PlutusV3Script(script) if script.compute_hash() == *script_hash => {
+                    Some(PlutusVersion::PlutusV3)
+                }
+                _ => None,
+            })
+            .next()
+    })
+}
+
+/// Where the ledger finds a script: among the witnesses of the transaction, or as the reference
+/// script of one of its inputs or reference inputs.
+pub enum ScriptSource {
+    Witness(&'static str),
+    Reference(TransactionInput, &'static str),
+}
+
+impl fmt::Display for ScriptSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptSource::Witness(language) => write!(f, "witness ({})", language),
+            ScriptSource::Reference(input, language) => write!(
+                f,
+                "reference script of {} ({})",
+                input.format_readable(),
+                language
+            ),
+        }
+    }
+}
+
+/// Where the script with the given hash is provided, if it is.
+pub fn script_source(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    script_hash: &Hash<28>,
+) -> Option<ScriptSource> {
+    let witnesses = &transaction.transaction_witness_set;
+
+    let witnessed = [
+        witnesses
+            .native_script
+            .iter()
+            .flat_map(|scripts| scripts.iter())
+            .any(|script| script.original_hash() == *script_hash)
+            .then_some("Native"),
+        witnesses
+            .plutus_v1_script
+            .iter()
+            .flat_map(|scripts| scripts.iter())
+            .any(|script| script.compute_hash() == *script_hash)
+            .then_some("PlutusV1"),
+        witnesses
+            .plutus_v2_script
+            .iter()
+            .flat_map(|scripts| scripts.iter())
+            .any(|script| script.compute_hash() == *script_hash)
+            .then_some("PlutusV2"),
+        witnesses
+            .plutus_v3_script
+            .iter()
+            .flat_map(|scripts| scripts.iter())
+            .any(|script| script.compute_hash() == *script_hash)
+            .then_some("PlutusV3"),
+    ];
+    if let Some(language) = witnessed.into_iter().flatten().next() {
+        return Some(ScriptSource::Witness(language));
+    }
+
+    collect_all_inputs(transaction)
+        .into_iter()
+        .find_map(|input| {
+            let language = match utxos.get(&input)?.script.as_ref()? {
+                MemoizedScript::NativeScript(script) if script.compute_hash() == *script_hash => {
+                    "Native"
+                }
+                MemoizedScript::PlutusV1Script(script) if script.compute_hash() == *script_hash => {
+                    "PlutusV1"
+                }
+                MemoizedScript::PlutusV2Script(script) if script.compute_hash() == *script_hash => {
+                    "PlutusV2"
+                }
+                MemoizedScript::PlutusV3Script(script) if script.compute_hash() == *script_hash => {
+                    "PlutusV3"
+                }
+                _ => return None,
+            };
+            Some(ScriptSource::Reference(input, language))
+        })
+}
+
+/// Every script the ledger needs the transaction to provide, with what needs it: those locking
+/// the spent inputs, the minting policies, the script credentials of certificates, withdrawals
+/// and voters, and the guardrail script of proposals. Registrations from before Conway need no
+/// witness.
+pub fn required_scripts(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+) -> Vec<(String, Hash<28>)> {
+    let body = &transaction.transaction_body;
+    let mut required = Vec::new();
+
+    for input in body.inputs.iter() {
+        if let Some(output) = utxos.get(input)
+            && let Address::Shelley(address) = &output.address
+            && let ShelleyPaymentPart::Script(hash) = address.payment()
+        {
+            required.push((format!("input {}", input.format_readable()), *hash));
+        }
+    }
+
+    for (policy, _) in body.mint.iter().flat_map(|mint| mint.iter()) {
+        required.push(("a minting policy".to_string(), *policy));
+    }
+
+    for (index, certificate) in body.certificates.iter().flat_map(|c| c.iter()).enumerate() {
+        if matches!(certificate, Certificate::StakeRegistration(_)) {
+            continue;
+        }
+        if let Some(StakeCredential::ScriptHash(hash)) = certificate_credential(certificate) {
+            required.push((format!("certificate {}", index), *hash));
+        }
+    }
+
+    for (account, _) in body.withdrawals.iter().flat_map(|w| w.iter()) {
+        if account.len() == 29 && account[0] & SCRIPT_CREDENTIAL_BIT != 0 {
+            let account_name = Address::from_bytes(account)
+                .map(|address| json::address(&address))
+                .unwrap_or_else(|_| hex::encode(&account[..]));
+            required.push((
+                format!("the withdrawal from {}", account_name),
+                Hash::from(&account[1..]),
+            ));
+        }
+    }
+
+    for (voter, _) in body.voting_procedures.iter().flat_map(|v| v.iter()) {
+        match voter {
+            Voter::ConstitutionalCommitteeScript(hash) => {
+                required.push(("a constitutional committee vote".to_string(), *hash))
+            }
+            Voter::DRepScript(hash) => required.push(("a DRep vote".to_string(), *hash)),
+            _ => {}
+        }
+    }
+
+    for (index, proposal) in body
+        .proposal_procedures
+        .iter()
+        .flat_map(|p| p.iter())
+        .enumerate()
+    {
+        if let GovAction::ParameterChange(_, _, Nullable::Some(guardrail))
+        | GovAction::TreasuryWithdrawals(_, Nullable::Some(guardrail)) = &proposal.gov_action
+        {
+            required.push((format!("proposal {}", index), *guardrail));
+        }
+    }
+
+    required
+}
+
+/// Languages of the scripts the redeemers execute, those whose cost models enter the script
+/// integrity hash.
+pub fn languages(
+    transaction: &MintedTx<'_>,
+    utxos: &ResolvedUtxos,
+    redeemers: &[&Redeemer],
+) -> BTreeSet<PlutusVersion> {
+    redeemers
+        .iter()
+        .filter_map(|redeemer| target_script_hash(transaction, utxos, redeemer))
+        .filter_map(|hash| script_version(transaction, utxos, &hash))
+        .collect()
+}
+
+/// The credential a certificate acts on behalf of, for certificates a script can witness.
+fn certificate_credential(certificate: &Certificate) -> Option<&StakeCredential> {
+    match certificate {
+        Certificate::StakeRegistration(credential)
+        | Certificate::StakeDeregistration(credential)
+        | Certificate::StakeDelegation(credential, _)
+        | Certificate::Reg(credential, _)
+        | Certificate::UnReg(credential, _)
+        | Certificate::VoteDeleg(credential, _)
+        | Certificate::StakeVoteDeleg(credential, _, _)
+        | Certificate::StakeRegDeleg(credential, _, _)
+        | Certificate::VoteRegDeleg(credential, _, _)
+        | Certificate::StakeVoteRegDeleg(credential, _, _, _)
+        | Certificate::AuthCommitteeHot(credential, _)
+        | Certificate::ResignCommitteeCold(credential, _)
+        | Certificate::RegDRepCert(credential, _, _)
+        | Certificate::UnRegDRepCert(credential, _)
+        | Certificate::UpdateDRepCert(credential, _) => Some(credential),
+        Certificate::PoolRegistration { .. } | Certificate::PoolRetirement(_, _) => None,
+    }
+}
@@ -0,0 +1,120 @@
+use amaru_kernel::cbor::{Decoder, data::Type};
+use anyhow::Result;
+
+use crate::transaction::{self, RawTransaction};
+
+/// The serialized size of each part of a transaction: its body, each field of its witness set
+/// and its auxiliary data, against the maximum size the protocol accepts when known. Returns the
+/// report and how many bytes the transaction is over that maximum.
+pub fn describe(tx_bytes: &[u8], max_size: Result<u64>) -> Result<(String, u64)> {
+    let raw = RawTransaction::parse(tx_bytes)?;
+    let total = tx_bytes.len() as u64;
+    let body = raw.body.len() as u64;
+    let auxiliary_data = raw.auxiliary_data.map_or(0, |bytes| bytes.len() as u64);
+    // The array header, the validity flag and the null in place of missing auxiliary data.
+    let framing = 2 + u64::from(raw.auxiliary_data.is_none());
+    let witness_set = total.saturating_sub(body + auxiliary_data + framing);
+
+    let share = |size: u64| format!("{:.1}%", size as f64 * 100.0 / total as f64);
+
+    let mut section = match &max_size {
+        Ok(max) => format!(
+            "Transaction size: {} bytes (at most {}, {:.1}%)",
+            total,
+            max,
+            total as f64 * 100.0 / *max as f64
+        ),
+        Err(e) => format!(
+            "Transaction size: {} bytes (maximum unknown, {:#})",
+            total, e
+        ),
+    };
+    section.push_str(&format!(
+        "\n  {:<22} {:>8} bytes {:>7}",
+        "Body",
+        body,
+        share(body)
+    ));
+    section.push_str(&format!(
+        "\n  {:<22} {:>8} bytes {:>7}",
+        "Witness set",
+        witness_set,
+        share(witness_set)
+    ));
+
+    let mut fields = 0;
+    for (key, value) in &raw.witness_set {
+        let size = value.len() as u64;
+        fields += size;
+        let items = match count_items(value) {
+            Some(1) => " (1 item)".to_string(),
+            Some(count) => format!(" ({} items)", count),
+            None => String::new(),
+        };
+        section.push_str(&format!(
+            "\n    {:<20} {:>8} bytes {:>7}{}",
+            witness_field(*key),
+            size,
+            share(size),
+            items
+        ));
+    }
+    if !raw.witness_set.is_empty() {
+        section.push_str(&format!(
+            "\n    {:<20} {:>8} bytes",
+            "Keys and headers",
+            witness_set.saturating_sub(fields)
+        ));
+    }
+
+    section.push_str(&format!(
+        "\n  {:<22} {:>8} bytes {:>7}",
+        "Auxiliary data",
+        auxiliary_data,
+        share(auxiliary_data)
+    ));
+    section.push_str(&format!("\n  {:<22} {:>8} bytes", "Framing", framing));
+
+    let excess = match max_size {
+        Ok(max) if total > max => {
+            section.push_str(&format!(
+                "\n\n  {} bytes over the maximum: the ledger rejects it (MaxTxSizeUTxO)",
+                total - max
+            ));
+            total - max
+        }
+        _ => 0,
+    };
+
+    Ok((section, excess))
+}
+
+/// Name of a witness-set field, by its key in the witness-set map.
+fn witness_field(key: u64) -> String {
+    match key {
+        transaction::VKEY_WITNESSES => "VKey witnesses".to_string(),
+        1 => "Native scripts".to_string(),
+        2 => "Bootstrap witnesses".to_string(),
+        3 => "PlutusV1 scripts".to_string(),
+        transaction::PLUTUS_DATA => "Datums".to_string(),
+        transaction::REDEEMERS => "Redeemers".to_string(),
+        6 => "PlutusV2 scripts".to_string(),
+        7 => "PlutusV3 scripts".to_string(),
+        key => format!("Field {}", key),
+    }
+}
+
+/// Number of items of a witness-set field: an array or map, possibly tagged as a set. None for
+/// indefinite-length ones.
+fn count_items(bytes: &[u8]) -> Option<u64> {
+    let mut decoder = Decoder::new(bytes);
+    if decoder.datatype().ok()? == Type::Tag {
+        decoder.tag().ok()?;
+    }
+
+    match decoder.datatype().ok()? {
+        Type::Array => decoder.array().ok()?,
+        Type::Map => decoder.map().ok()?,
+        _ => None,
+    }
+}
@@ -0,0 +1,88 @@
+use std::{collections::BTreeMap, path::Path};
+
+use amaru_kernel::{TransactionInput, to_cbor};
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    formatter::ReadableFormatter,
+    output::decode_output,
+    resolver::{ResolvedUtxos, UtxoResolver, parse_output_reference},
+};
+
+/// On-disk form of a resolution pass: CBOR-encoded outputs keyed by `txhash#index`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+    slot: Option<u64>,
+    utxos: BTreeMap<String, String>,
+}
+
+/// Resolves UTxOs from a snapshot written by `--save-utxos`, without any network access.
+pub struct UtxoFile {
+    slot: Option<u64>,
+    utxos: ResolvedUtxos,
+}
+
+impl UtxoFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read UTxO file: {}", path.display()))?;
+
+        let snapshot: Snapshot = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid UTxO file: {}", path.display()))?;
+
+        let mut utxos = ResolvedUtxos::new();
+        for (reference, output_hex) in snapshot.utxos {
+            let input = parse_output_reference(&reference)?;
+            let bytes = hex::decode(&output_hex)
+                .with_context(|| format!("Invalid output hex for {}", reference))?;
+            let output = decode_output(&bytes)
+                .with_context(|| format!("Invalid output for {}", reference))?;
+            utxos.insert(input, output);
+        }
+
+        Ok(Self {
+            slot: snapshot.slot,
+            utxos,
+        })
+    }
+}
+
+#[async_trait]
+impl UtxoResolver for UtxoFile {
+    async fn resolve(&self, inputs: &[TransactionInput]) -> Result<ResolvedUtxos> {
+        Ok(inputs
+            .iter()
+            .filter_map(|input| {
+                self.utxos
+                    .get(input)
+                    .map(|output| (input.clone(), output.clone()))
+            })
+            .collect())
+    }
+
+    fn name(&self) -> &'static str {
+        "UTxO file"
+    }
+
+    async fn tip(&self) -> Result<u64> {
+        self.slot
+            .ok_or_else(|| anyhow!("UTxO file does not record a slot. Use --slot instead"))
+    }
+}
+
+pub fn save(path: &Path, utxos: &ResolvedUtxos, slot: u64) -> Result<()> {
+    let snapshot = Snapshot {
+        slot: Some(slot),
+        utxos: utxos
+            .iter()
+            .map(|(input, output)| (input.format_readable(), hex::encode(to_cbor(output))))
+            .collect(),
+    };
+
+    let json = serde_json::to_string_pretty(&snapshot)?;
+
+    std::fs::write(path, json)
+        .with_context(|| format!("Failed to write UTxO file: {}", path.display()))
+}
@@ -0,0 +1,62 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Request counters kept by a provider over a whole run.
+#[derive(Debug, Default)]
+pub struct RequestStats {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    cache_hits: AtomicU64,
+}
+
+impl RequestStats {
+    pub fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn report(&self, provider: &'static str, quota: Option<String>) -> ProviderStats {
+        ProviderStats {
+            provider,
+            requests: self.requests.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            quota,
+        }
+    }
+}
+
+/// A snapshot of a provider's counters, printed by `--stats`.
+pub struct ProviderStats {
+    pub provider: &'static str,
+    pub requests: u64,
+    pub retries: u64,
+    pub cache_hits: u64,
+    /// Provider-specific usage note, such as how much of the daily quota has been spent.
+    pub quota: Option<String>,
+}
+
+impl fmt::Display for ProviderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} request(s), {} retry(ies), {} cache hit(s)",
+            self.provider, self.requests, self.retries, self.cache_hits
+        )?;
+
+        if let Some(quota) = &self.quota {
+            write!(f, "\n  {}", quota)?;
+        }
+
+        Ok(())
+    }
+}
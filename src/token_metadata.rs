@@ -0,0 +1,187 @@
+use amaru_kernel::{AssetName, BigInt, Hash, MemoizedDatum, MemoizedTransactionOutput, PlutusData};
+use amaru_plutus::script_context::{CurrencySymbol, TransactionOutput};
+
+use crate::{
+    ContextInputs, collect_all_inputs,
+    formatter::{ReadableFormatter, cip67_label, indent_lines},
+    metadata::{self, CIP25_LABEL, Metadatum},
+};
+
+/// CIP-67 label of the reference NFT holding the metadata of a CIP-68 token.
+const REFERENCE_NFT_LABEL: u16 = 100;
+
+/// Prefix of the names of CIP-68 reference NFTs: label 100 with its checksum.
+const REFERENCE_NFT_PREFIX: [u8; 4] = [0x00, 0x06, 0x43, 0xb0];
+
+/// Metadata of the minted assets: CIP-25 metadata under label 721 of the auxiliary data, and the
+/// inline datums of the CIP-68 reference NFTs produced or spent alongside. None when no minted
+/// asset has any.
+pub fn describe(inputs: &ContextInputs<'_>) -> Option<String> {
+    let mint = inputs.transaction.transaction_body.mint.as_ref()?;
+    let cip25 = cip25_metadata(inputs);
+
+    let mut entries = Vec::new();
+    for (policy, assets) in mint.iter() {
+        for (name, _) in assets.iter() {
+            let name = name.to_vec();
+            let asset = format!(
+                "{}.{}",
+                hex::encode(policy),
+                AssetName::from(name.clone()).format_readable()
+            );
+
+            if let Some(metadata) = cip25.as_ref().and_then(|m| cip25_asset(m, policy, &name)) {
+                entries.push(format!(
+                    "{}\n  Source: CIP-25 (metadata label 721)\n{}",
+                    asset,
+                    indent_lines(&metadata.format(), 2)
+                ));
+            }
+
+            if let Some((source, datum)) = reference_datum(inputs, policy, &name) {
+                entries.push(format!(
+                    "{}\n  Source: CIP-68 reference NFT in {}\n{}",
+                    asset,
+                    source,
+                    indent_lines(&cip68_metadata(datum), 2)
+                ));
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "Token metadata:\n{}",
+        entries
+            .iter()
+            .map(|entry| indent_lines(entry, 2))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    ))
+}
+
+/// The metadatum under label 721 of the auxiliary data.
+fn cip25_metadata(inputs: &ContextInputs<'_>) -> Option<Metadatum> {
+    metadata::transaction_metadata(inputs.transaction)?
+        .take(|key| metadata::is_label(key, CIP25_LABEL))
+}
+
+/// The CIP-25 metadata of an asset. Version 1 keys policies and names as text (hex and UTF-8),
+/// version 2 as bytes.
+fn cip25_asset<'a>(
+    metadata: &'a Metadatum,
+    policy: &Hash<28>,
+    name: &[u8],
+) -> Option<&'a Metadatum> {
+    metadata
+        .get(|key| match key {
+            Metadatum::Text(text) => text.eq_ignore_ascii_case(&hex::encode(policy)),
+            Metadatum::Bytes(bytes) => bytes.as_slice() == policy.as_ref(),
+            _ => false,
+        })?
+        .get(|key| match key {
+            Metadatum::Text(text) => text.as_bytes() == name,
+            Metadatum::Bytes(bytes) => bytes.as_slice() == name,
+            _ => false,
+        })
+}
+
+/// The inline datum of the reference NFT of a CIP-68 token, among the outputs produced and then
+/// the inputs and reference inputs, with where it was found.
+fn reference_datum(
+    inputs: &ContextInputs<'_>,
+    policy: &Hash<28>,
+    name: &[u8],
+) -> Option<(String, PlutusData)> {
+    match cip67_label(name) {
+        Some(label) if label != REFERENCE_NFT_LABEL => {}
+        _ => return None,
+    }
+    let reference = [&REFERENCE_NFT_PREFIX[..], &name[4..]].concat();
+
+    let produced = inputs
+        .transaction
+        .transaction_body
+        .outputs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, output)| {
+            MemoizedTransactionOutput::try_from(output.clone())
+                .ok()
+                .map(|output| (format!("output {}", index), output))
+        });
+    let resolved = collect_all_inputs(inputs.transaction)
+        .into_iter()
+        .filter_map(|input| {
+            inputs
+                .utxos
+                .get(&input)
+                .map(|output| (format!("input {}", input.format_readable()), output.clone()))
+        });
+
+    produced.chain(resolved).find_map(|(source, output)| {
+        let MemoizedDatum::Inline(datum) = &output.datum else {
+            return None;
+        };
+        let holds_reference =
+            TransactionOutput::from(&output)
+                .value
+                .0
+                .iter()
+                .any(|(symbol, assets)| {
+                    matches!(symbol, CurrencySymbol::Native(hash) if hash == policy)
+                        && assets.iter().any(|(asset, _)| asset.to_vec() == reference)
+                });
+        holds_reference.then(|| (source, datum.as_ref().clone()))
+    })
+}
+
+/// A CIP-68 datum, `Constr(0, [metadata, version, extra])`: its metadata map with UTF-8 keys and
+/// values shown as text, then its version.
+fn cip68_metadata(datum: PlutusData) -> String {
+    let PlutusData::Constr(constr) = &datum else {
+        return format!("(not a CIP-68 datum) {}", datum.format_readable());
+    };
+    let [metadata, version, ..] = constr.fields.as_slice() else {
+        return format!("(not a CIP-68 datum) {}", datum.format_readable());
+    };
+
+    let mut result = format_data(metadata);
+    if let PlutusData::BigInt(BigInt::Int(version)) = version {
+        result.push_str(&format!("\nversion: {}", version.0));
+    }
+    result
+}
+
+fn format_data(data: &PlutusData) -> String {
+    match data {
+        PlutusData::BoundedBytes(bytes) => match std::str::from_utf8(bytes) {
+            Ok(text) if !text.chars().any(char::is_control) => text.to_string(),
+            _ => format!("0x{}", hex::encode(bytes.to_vec())),
+        },
+        PlutusData::Map(entries) => entries
+            .iter()
+            .map(|(key, value)| match value {
+                PlutusData::Map(_) | PlutusData::Array(_) => {
+                    format!(
+                        "{}:\n{}",
+                        format_data(key),
+                        indent_lines(&format_data(value), 2)
+                    )
+                }
+                _ => format!("{}: {}", format_data(key), format_data(value)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        PlutusData::Array(items) => items
+            .iter()
+            .map(|item| format!("- {}", indent_lines(&format_data(item), 2).trim_start()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        PlutusData::BigInt(BigInt::Int(int)) => int.0.to_string(),
+        _ => data.format_readable(),
+    }
+}
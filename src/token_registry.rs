@@ -0,0 +1,159 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    sync::RwLock,
+};
+
+use amaru_kernel::{Hash, MemoizedTransactionOutput, MintedTx};
+use amaru_plutus::script_context::{CurrencySymbol, TransactionOutput};
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+
+use crate::{color, resolver::ResolvedUtxos};
+
+/// Ticker and decimals of the assets known from the token registry, by subject (the hex of the
+/// policy id followed by the asset name). Extended as transactions are resolved.
+static TOKENS: RwLock<BTreeMap<String, Token>> = RwLock::new(BTreeMap::new());
+
+struct Token {
+    ticker: String,
+    decimals: u32,
+}
+
+/// Look the given subjects up in the token registry: a local mapping file, or the registry's
+/// query endpoint when the source is a URL (e.g. https://tokens.cardano.org). The registry being
+/// unreachable only leaves amounts unadjusted.
+pub async fn load(source: &str, subjects: &BTreeSet<String>) -> Result<()> {
+    let json = if source.starts_with("http://") || source.starts_with("https://") {
+        if subjects.is_empty() {
+            return Ok(());
+        }
+        match query(source, subjects).await {
+            Ok(json) => json,
+            Err(e) => {
+                color::warning(format!("Failed to query the token registry: {:#}", e));
+                return Ok(());
+            }
+        }
+    } else {
+        let path = Path::new(source);
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read token registry file: {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid JSON in token registry file: {}", path.display()))?
+    };
+
+    let tokens = parse(&json)?;
+    TOKENS
+        .write()
+        .expect("the token registry lock is never poisoned")
+        .extend(tokens);
+    Ok(())
+}
+
+async fn query(url: &str, subjects: &BTreeSet<String>) -> Result<Value> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/metadata/query", url.trim_end_matches('/')))
+        .json(&json!({
+            "subjects": subjects,
+            "properties": ["ticker", "name", "decimals"],
+        }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(response.json().await?)
+}
+
+/// Read registry entries, either as the registry serves them (`{"subjects": [...]}` or a bare
+/// array, properties wrapped in `{"value": ...}`) or as a plain mapping from subject to
+/// `{"ticker": ..., "decimals": ...}`.
+fn parse(json: &Value) -> Result<BTreeMap<String, Token>> {
+    let entries: Vec<(String, &Value)> = match json.get("subjects").unwrap_or(json) {
+        Value::Array(entries) => entries
+            .iter()
+            .filter_map(|entry| {
+                let subject = entry.get("subject")?.as_str()?;
+                Some((subject.to_lowercase(), entry))
+            })
+            .collect(),
+        Value::Object(entries) => entries
+            .iter()
+            .map(|(subject, entry)| (subject.to_lowercase(), entry))
+            .collect(),
+        _ => bail!("The token registry is neither a list of entries nor a mapping by subject"),
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|(subject, entry)| {
+            let property = |name: &str| {
+                let value = entry.get(name)?;
+                Some(value.get("value").unwrap_or(value).clone())
+            };
+            let ticker = property("ticker")
+                .or_else(|| property("name"))?
+                .as_str()?
+                .to_string();
+            let decimals = property("decimals")
+                .and_then(|decimals| decimals.as_u64())
+                .unwrap_or_default() as u32;
+
+            Some((subject, Token { ticker, decimals }))
+        })
+        .collect())
+}
+
+/// The subjects of every native asset the transaction spends, references, produces or mints.
+pub fn subjects(transaction: &MintedTx<'_>, utxos: &ResolvedUtxos) -> BTreeSet<String> {
+    let body = &transaction.transaction_body;
+    let mut subjects = BTreeSet::new();
+
+    let produced = body
+        .outputs
+        .iter()
+        .filter_map(|output| MemoizedTransactionOutput::try_from(output.clone()).ok());
+    for output in utxos.values().cloned().chain(produced) {
+        for (policy, assets) in TransactionOutput::from(&output).value.0.iter() {
+            if let CurrencySymbol::Native(policy) = policy {
+                for (name, _) in assets.iter() {
+                    subjects.insert(subject(policy, &name.to_vec()));
+                }
+            }
+        }
+    }
+
+    for (policy, assets) in body.mint.iter().flat_map(|mint| mint.iter()) {
+        for (name, _) in assets.iter() {
+            subjects.insert(subject(policy, &name.to_vec()));
+        }
+    }
+
+    subjects
+}
+
+fn subject(policy: &Hash<28>, name: &[u8]) -> String {
+    format!("{}{}", hex::encode(policy), hex::encode(name))
+}
+
+/// An amount of an asset known to the registry in its own unit, e.g. `12.5 SUNDAE` for
+/// 12500000 with 6 decimals. None for assets the registry doesn't know.
+pub fn format_amount(policy: &Hash<28>, name: &[u8], amount: i128) -> Option<String> {
+    let tokens = TOKENS
+        .read()
+        .expect("the token registry lock is never poisoned");
+    let token = tokens.get(&subject(policy, name))?;
+
+    let sign = if amount < 0 { "-" } else { "" };
+    let unit = 10u128.checked_pow(token.decimals)?;
+    let whole = amount.unsigned_abs() / unit;
+    let fraction = amount.unsigned_abs() % unit;
+
+    let quantity = if fraction == 0 {
+        whole.to_string()
+    } else {
+        let fraction = format!("{:0width$}", fraction, width = token.decimals as usize);
+        format!("{}.{}", whole, fraction.trim_end_matches('0'))
+    };
+    Some(format!("{}{} {}", sign, quantity, token.ticker))
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use amaru_kernel::cbor::{Decoder, data::Type};
+use anyhow::{Context, Result, bail};
+
+const CBOR_TRUE: u8 = 0xf5;
+const CBOR_FALSE: u8 = 0xf4;
+const CBOR_NULL: u8 = 0xf6;
+
+/// Witness-set field holding the signatures of verification keys.
+pub const VKEY_WITNESSES: u64 = 0;
+/// Witness-set field holding the datums of hashed-datum outputs.
+pub const PLUTUS_DATA: u64 = 4;
+/// Witness-set field holding the redeemers.
+pub const REDEEMERS: u64 = 5;
+
+/// Raw CBOR of witness-set fields, keyed by their index in the witness-set map.
+pub type WitnessFields = BTreeMap<u64, Vec<u8>>;
+
+/// A transaction split into the raw bytes of its parts, so that pieces can be swapped without
+/// re-encoding (and so re-hashing) the body.
+pub struct RawTransaction<'b> {
+    pub body: &'b [u8],
+    pub witness_set: WitnessFields,
+    pub is_valid: bool,
+    pub auxiliary_data: Option<&'b [u8]>,
+}
+
+impl<'b> RawTransaction<'b> {
+    /// Split a full transaction, or wrap a bare transaction body with an empty witness set.
+    pub fn parse(bytes: &'b [u8]) -> Result<Self> {
+        let mut decoder = Decoder::new(bytes);
+
+        if matches!(decoder.datatype()?, Type::Map | Type::MapIndef) {
+            return Ok(Self {
+                body: bytes,
+                witness_set: WitnessFields::new(),
+                is_valid: true,
+                auxiliary_data: None,
+            });
+        }
+
+        let len = decoder.array().context("Transaction is not a CBOR array")?;
+        if len != Some(4) {
+            bail!("Unsupported transaction format. Only Alonzo and later transactions can be read");
+        }
+
+        let body = raw_item(&mut decoder, bytes).context("Failed to decode transaction body")?;
+        let witness_set = parse_witness_set(raw_item(&mut decoder, bytes)?)
+            .context("Failed to decode witness set")?;
+        let is_valid = decoder.bool().context("Failed to decode validity flag")?;
+        let auxiliary_data = match decoder.datatype()? {
+            Type::Null => None,
+            _ => Some(raw_item(&mut decoder, bytes).context("Failed to decode auxiliary data")?),
+        };
+
+        Ok(Self {
+            body,
+            witness_set,
+            is_valid,
+            auxiliary_data,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0x84];
+        bytes.extend_from_slice(self.body);
+        bytes.extend(encode_map(&self.witness_set));
+
+        bytes.push(if self.is_valid { CBOR_TRUE } else { CBOR_FALSE });
+        match self.auxiliary_data {
+            Some(auxiliary_data) => bytes.extend_from_slice(auxiliary_data),
+            None => bytes.push(CBOR_NULL),
+        }
+
+        bytes
+    }
+}
+
+/// Turn a transaction or bare body into a full transaction, replacing the given witness-set
+/// fields. A full transaction is returned untouched when there is nothing to replace.
+pub fn complete(bytes: Vec<u8>, fields: &WitnessFields) -> Result<Vec<u8>> {
+    let mut transaction = RawTransaction::parse(&bytes)?;
+    let is_bare_body = transaction.body.len() == bytes.len();
+
+    if fields.is_empty() && !is_bare_body {
+        return Ok(bytes);
+    }
+
+    transaction
+        .witness_set
+        .extend(fields.iter().map(|(key, value)| (*key, value.clone())));
+
+    Ok(transaction.to_bytes())
+}
+
+/// Raw bytes of the item at the decoder's position.
+pub fn raw_item<'b>(decoder: &mut Decoder<'b>, bytes: &'b [u8]) -> Result<&'b [u8]> {
+    let start = decoder.position();
+    decoder.skip()?;
+    Ok(&bytes[start..decoder.position()])
+}
+
+/// Raw bytes of every item of the array at the decoder's position.
+pub fn raw_items<'b>(decoder: &mut Decoder<'b>, bytes: &'b [u8]) -> Result<Vec<&'b [u8]>> {
+    let len = decoder.array()?;
+    let mut items = Vec::new();
+
+    while more(decoder, len, items.len() as u64)? {
+        items.push(raw_item(decoder, bytes)?);
+    }
+
+    Ok(items)
+}
+
+/// Raw bytes of every value of the uint-keyed map at the decoder's position.
+pub fn raw_entries<'b>(
+    decoder: &mut Decoder<'b>,
+    bytes: &'b [u8],
+) -> Result<BTreeMap<u64, &'b [u8]>> {
+    let len = decoder.map()?;
+    let mut entries = BTreeMap::new();
+    let mut seen = 0;
+
+    while more(decoder, len, seen)? {
+        let key = decoder.u64()?;
+        entries.insert(key, raw_item(decoder, bytes)?);
+        seen += 1;
+    }
+
+    Ok(entries)
+}
+
+/// Whether a definite or indefinite container has items left, consuming the break marker of an
+/// indefinite one.
+pub fn more(decoder: &mut Decoder<'_>, len: Option<u64>, seen: u64) -> Result<bool> {
+    match len {
+        Some(len) => Ok(seen < len),
+        None if decoder.datatype()? == Type::Break => {
+            decoder.set_position(decoder.position() + 1);
+            Ok(false)
+        }
+        None => Ok(true),
+    }
+}
+
+/// Split a witness set into the raw bytes of its fields.
+pub fn parse_witness_set(bytes: &[u8]) -> Result<WitnessFields> {
+    Ok(raw_entries(&mut Decoder::new(bytes), bytes)?
+        .into_iter()
+        .map(|(key, value)| (key, value.to_vec()))
+        .collect())
+}
+
+/// Encode a uint-keyed map from the raw bytes of its values.
+pub fn encode_map<V: AsRef<[u8]>>(entries: &BTreeMap<u64, V>) -> Vec<u8> {
+    let mut bytes = map_header(entries.len() as u64);
+    for (key, value) in entries {
+        bytes.extend(uint(*key));
+        bytes.extend_from_slice(value.as_ref());
+    }
+    bytes
+}
+
+fn map_header(len: u64) -> Vec<u8> {
+    let mut header = uint(len);
+    header[0] |= 0xa0;
+    header
+}
+
+/// A CBOR unsigned integer (major type 0).
+fn uint(value: u64) -> Vec<u8> {
+    match value {
+        0..=23 => vec![value as u8],
+        24..=0xff => vec![0x18, value as u8],
+        0x100..=0xffff => [vec![0x19], (value as u16).to_be_bytes().to_vec()].concat(),
+        0x1_0000..=0xffff_ffff => [vec![0x1a], (value as u32).to_be_bytes().to_vec()].concat(),
+        _ => [vec![0x1b], value.to_be_bytes().to_vec()].concat(),
+    }
+}
@@ -0,0 +1,450 @@
+use amaru_kernel::{
+    Address, Certificate, MemoizedDatum, MemoizedTransactionOutput, Redeemer, ScriptPurpose,
+    TransactionInput,
+};
+use anyhow::{Result, bail};
+
+use crate::{
+    ContextInputs, PlutusVersion, collect_all_inputs, datum_preimage, formatter::ReadableFormatter,
+    protocol, spent_output,
+};
+
+/// Fail the way the ledger does when the transaction uses a feature the Plutus version can't
+/// represent, naming the ledger's context error and the offending field.
+pub fn check(
+    version: PlutusVersion,
+    inputs: &ContextInputs<'_>,
+    redeemer: &Redeemer,
+) -> Result<()> {
+    let body = &inputs.transaction.transaction_body;
+
+    if let Some(protocol_version) = inputs.protocol_version {
+        if !protocol_version.supports(version) {
+            bail!(
+                "{:?} scripts can't run under protocol version {}, they were introduced with protocol version {}",
+                version,
+                protocol_version,
+                protocol::introduced_in(version)
+            );
+        }
+
+        if protocol_version.0 >= protocol::BABBAGE
+            && let Some((name, address)) = byron_outputs(inputs).into_iter().next()
+        {
+            bail!(
+                "ByronTxOutInContext: {} has a Byron address, {}, which no Plutus version can represent since Babbage",
+                name,
+                address
+            );
+        }
+
+        if !protocol_version.has_governance() {
+            if let Some(index) = conway_certificate(inputs) {
+                bail!(
+                    "Certificate {} (body field 4) is a Conway certificate, which doesn't exist under protocol version {}",
+                    index,
+                    protocol_version
+                );
+            }
+            if let Some((_, field)) = governance_fields(inputs).first() {
+                bail!(
+                    "The transaction has {}, which doesn't exist under protocol version {}",
+                    field,
+                    protocol_version
+                );
+            }
+        }
+    }
+
+    if matches!(version, PlutusVersion::PlutusV3) {
+        return Ok(());
+    }
+
+    if matches!(redeemer.tag, ScriptPurpose::Vote | ScriptPurpose::Propose) {
+        bail!(
+            "PlutusPurposeNotSupported: {:?} redeemers can only execute PlutusV3 scripts",
+            redeemer.tag
+        );
+    }
+
+    if matches!(version, PlutusVersion::PlutusV1) {
+        for (name, output) in outputs(inputs) {
+            if matches!(output.datum, MemoizedDatum::Inline(_)) {
+                bail!(
+                    "InlineDatumsNotSupported: {} has an inline datum (output field 2), which PlutusV1 can't represent",
+                    name
+                );
+            }
+            if output.script.is_some() {
+                bail!(
+                    "ReferenceScriptsNotSupported: {} carries a reference script (output field 3), which PlutusV1 can't represent",
+                    name
+                );
+            }
+        }
+
+        if body.reference_inputs.is_some() {
+            bail!(
+                "ReferenceInputsNotSupported: the transaction has reference inputs (body field 18), which PlutusV1 can't represent"
+            );
+        }
+    }
+
+    if let Some(index) = conway_certificate(inputs) {
+        bail!(
+            "CertificateNotSupported: certificate {} (body field 4) is a Conway certificate, which {:?} can't represent",
+            index,
+            version
+        );
+    }
+
+    if let Some((error, field)) = governance_fields(inputs).first() {
+        bail!(
+            "{}: the transaction has {}, which {:?} can't represent",
+            error,
+            field,
+            version
+        );
+    }
+
+    Ok(())
+}
+
+/// Describe what the ledger drops, coerces or defaults when translating this transaction into
+/// the TxInfo of the given Plutus version, so users can see why a validator doesn't see a field.
+pub fn explain(
+    version: PlutusVersion,
+    inputs: &ContextInputs<'_>,
+    redeemer: &Redeemer,
+) -> Vec<String> {
+    let body = &inputs.transaction.transaction_body;
+    let is_v1 = matches!(version, PlutusVersion::PlutusV1);
+    let mut notes = Vec::new();
+
+    match inputs.protocol_version {
+        Some(protocol_version) => notes.push(format!(
+            "translated under the rules of protocol version {}",
+            protocol_version
+        )),
+        None => notes.push(
+            "protocol version unknown: era-conditional rules weren't checked. Use --protocol-version"
+                .to_string(),
+        ),
+    }
+
+    notes.extend(byron_warnings(inputs));
+
+    if is_v1 {
+        notes.push("fee: coerced to a Value holding only lovelace".to_string());
+        if body.mint.is_some() {
+            notes.push(
+                "mint: a zero lovelace entry is added, as the ledger does for PlutusV1".to_string(),
+            );
+        }
+        notes.push("redeemers: dropped, PlutusV1 contexts don't list them".to_string());
+    }
+
+    if body.validity_interval_start.is_some() || body.ttl.is_some() {
+        notes.push(
+            "validity interval: coerced from slots to POSIX milliseconds using the network's era history"
+                .to_string(),
+        );
+    } else {
+        notes.push(
+            "validity interval: defaulted to (-∞, +∞) as the transaction sets no bounds"
+                .to_string(),
+        );
+    }
+
+    if inputs.datum.is_none()
+        && let Ok(Some(output)) = spent_output(inputs, redeemer)
+        && let MemoizedDatum::Hash(hash) = &output.datum
+        && datum_preimage(inputs, hash).is_none()
+    {
+        notes.push(format!(
+            "spend datum: defaulted to the bytes of its hash {}, its preimage wasn't found. Try --resolve-datums or --datum",
+            hex::encode(hash)
+        ));
+    }
+
+    notes
+}
+
+/// Explain, for each Byron address among the resolved inputs and produced outputs, how the
+/// ledger treats it. Under Alonzo it silently leaves them out of the context, as nawi does; since
+/// Babbage it fails translation, which `check` reports.
+pub fn byron_warnings(inputs: &ContextInputs<'_>) -> Vec<String> {
+    let treatment = match inputs.protocol_version {
+        Some(protocol_version) if protocol_version.0 >= protocol::BABBAGE => return Vec::new(),
+        Some(_) => "PlutusV1 contexts built under Alonzo silently exclude it, as this one does",
+        None => {
+            "PlutusV1 contexts built under Alonzo silently exclude it, as this one does, but since Babbage the ledger fails translation (ByronTxOutInContext). Use --protocol-version"
+        }
+    };
+
+    byron_outputs(inputs)
+        .into_iter()
+        .map(|(name, address)| format!("{} has a Byron address, {}: {}", name, address, treatment))
+        .collect()
+}
+
+/// The resolved inputs and produced outputs at a Byron address, by name, with the address.
+fn byron_outputs(inputs: &ContextInputs<'_>) -> Vec<(String, String)> {
+    outputs(inputs)
+        .into_iter()
+        .filter(|(_, output)| matches!(output.address, Address::Byron(_)))
+        .map(|(name, output)| (name, output.address.format_readable()))
+        .collect()
+}
+
+/// Position of the first certificate that only exists since Conway.
+fn conway_certificate(inputs: &ContextInputs<'_>) -> Option<usize> {
+    inputs
+        .transaction
+        .transaction_body
+        .certificates
+        .iter()
+        .flat_map(|certificates| certificates.iter())
+        .position(is_conway_certificate)
+}
+
+/// The governance fields the transaction sets, with the ledger's error for contexts that can't
+/// represent them.
+fn governance_fields(inputs: &ContextInputs<'_>) -> Vec<(&'static str, &'static str)> {
+    let body = &inputs.transaction.transaction_body;
+
+    [
+        (
+            body.voting_procedures.is_some(),
+            "VotingProceduresFieldNotSupported",
+            "voting procedures (body field 19)",
+        ),
+        (
+            body.proposal_procedures.is_some(),
+            "ProposalProceduresFieldNotSupported",
+            "proposal procedures (body field 20)",
+        ),
+        (
+            body.treasury_value.is_some(),
+            "CurrentTreasuryFieldNotSupported",
+            "a current treasury value (body field 21)",
+        ),
+        (
+            body.donation.is_some(),
+            "TreasuryDonationFieldNotSupported",
+            "a treasury donation (body field 22)",
+        ),
+    ]
+    .into_iter()
+    .filter(|(present, _, _)| *present)
+    .map(|(_, error, field)| (error, field))
+    .collect()
+}
+
+/// Every output the context refers to: the resolved inputs, then the outputs produced.
+fn outputs(inputs: &ContextInputs<'_>) -> Vec<(String, MemoizedTransactionOutput)> {
+    let resolved = context_utxos(inputs)
+        .into_iter()
+        .map(|(input, output)| (format!("input {}", input.format_readable()), output.clone()));
+    let produced = inputs
+        .transaction
+        .transaction_body
+        .outputs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, output)| {
+            MemoizedTransactionOutput::try_from(output.clone())
+                .ok()
+                .map(|output| (format!("output {}", index), output))
+        });
+
+    resolved.chain(produced).collect()
+}
+
+/// The resolved inputs and reference inputs, leaving out the collateral resolved alongside them.
+fn context_utxos<'a>(
+    inputs: &ContextInputs<'a>,
+) -> Vec<(TransactionInput, &'a MemoizedTransactionOutput)> {
+    collect_all_inputs(inputs.transaction)
+        .into_iter()
+        .filter_map(|input| inputs.utxos.get(&input).map(|output| (input, output)))
+        .collect()
+}
+
+fn is_conway_certificate(certificate: &Certificate) -> bool {
+    !matches!(
+        certificate,
+        Certificate::StakeRegistration(_)
+            | Certificate::StakeDeregistration(_)
+            | Certificate::StakeDelegation(_, _)
+            | Certificate::PoolRegistration { .. }
+            | Certificate::PoolRetirement(_, _)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use amaru_kernel::{MintedTx, network::NetworkName};
+
+    use super::*;
+    use crate::{
+        NetworkNameAdapter, decode_transaction, get_redeemers,
+        protocol::ProtocolVersion,
+        resolver::{ResolvedDatums, ResolvedUtxos},
+    };
+
+    /// One input spent with a redeemer, paying one output at a key address.
+    const SPEND: &str = "84a30081825820000000000000000000000000000000000000000000000000000000000000000000018182581d60111111111111111111111111111111111111111111111111111111111a000f4240021a00030d40a1058184000000821a000f42401a3b9aca00f5f6";
+
+    /// The same spend, paying an output that holds an inline datum.
+    const INLINE_DATUM: &str = "84a300818258200000000000000000000000000000000000000000000000000000000000000000000181a300581d6011111111111111111111111111111111111111111111111111111111011a000f4240028201d8184100021a00030d40a1058184000000821a000f42401a3b9aca00f5f6";
+
+    /// The same spend, paying an output at a Byron address.
+    const BYRON_OUTPUT: &str = "84a30081825820000000000000000000000000000000000000000000000000000000000000000000018182582b82d818582183581c22222222222222222222222222222222222222222222222222222222a0001a25664a0a1a000f4240021a00030d40a1058184000000821a000f42401a3b9aca00f5f6";
+
+    fn context_inputs<'a>(
+        transaction: &'a MintedTx<'a>,
+        utxos: &'a ResolvedUtxos,
+        datums: &'a ResolvedDatums,
+        protocol_version: Option<u64>,
+    ) -> ContextInputs<'a> {
+        ContextInputs {
+            transaction,
+            utxos,
+            datums,
+            datum: None,
+            network: NetworkNameAdapter(NetworkName::Preprod),
+            slot: 0,
+            protocol_version: protocol_version.map(ProtocolVersion),
+        }
+    }
+
+    fn redeemer(transaction: &MintedTx<'_>) -> Redeemer {
+        get_redeemers(transaction).unwrap()[0].clone().into_owned()
+    }
+
+    #[test]
+    fn explanations() {
+        let bytes = hex::decode(SPEND).unwrap();
+        let transaction = decode_transaction(&bytes).unwrap();
+        let redeemer = redeemer(&transaction);
+        let (utxos, datums) = (ResolvedUtxos::new(), ResolvedDatums::new());
+
+        let inputs = context_inputs(&transaction, &utxos, &datums, None);
+        let notes = explain(PlutusVersion::PlutusV1, &inputs, &redeemer);
+        assert!(notes[0].starts_with("protocol version unknown"));
+        assert!(
+            notes.contains(&"redeemers: dropped, PlutusV1 contexts don't list them".to_string())
+        );
+        assert!(
+            notes.contains(
+                &"validity interval: defaulted to (-∞, +∞) as the transaction sets no bounds"
+                    .to_string()
+            )
+        );
+
+        let inputs = context_inputs(&transaction, &utxos, &datums, Some(protocol::CONWAY));
+        let notes = explain(PlutusVersion::PlutusV3, &inputs, &redeemer);
+        assert_eq!(
+            notes[0],
+            "translated under the rules of protocol version 9 (Conway)"
+        );
+        assert!(!notes.iter().any(|note| note.starts_with("redeemers")));
+    }
+
+    #[test]
+    fn features_the_plutus_version_cannot_represent() {
+        let cases = [
+            (
+                SPEND,
+                ScriptPurpose::Vote,
+                PlutusVersion::PlutusV2,
+                Some("PlutusPurposeNotSupported"),
+            ),
+            (SPEND, ScriptPurpose::Vote, PlutusVersion::PlutusV3, None),
+            (
+                INLINE_DATUM,
+                ScriptPurpose::Spend,
+                PlutusVersion::PlutusV1,
+                Some("InlineDatumsNotSupported"),
+            ),
+            (
+                INLINE_DATUM,
+                ScriptPurpose::Spend,
+                PlutusVersion::PlutusV2,
+                None,
+            ),
+        ];
+
+        for (tx, tag, version, error) in cases {
+            let bytes = hex::decode(tx).unwrap();
+            let transaction = decode_transaction(&bytes).unwrap();
+            let redeemer = Redeemer {
+                tag,
+                ..redeemer(&transaction)
+            };
+            let (utxos, datums) = (ResolvedUtxos::new(), ResolvedDatums::new());
+            let inputs = context_inputs(&transaction, &utxos, &datums, None);
+
+            match (check(version, &inputs, &redeemer), error) {
+                (Ok(()), None) => {}
+                (Err(failure), Some(error)) => assert!(
+                    failure.to_string().starts_with(error),
+                    "{:?}: {}",
+                    version,
+                    failure
+                ),
+                (result, _) => panic!("{:?}: unexpected {:?}", version, result),
+            }
+        }
+    }
+
+    #[test]
+    fn plutus_versions_the_protocol_version_predates() {
+        let bytes = hex::decode(SPEND).unwrap();
+        let transaction = decode_transaction(&bytes).unwrap();
+        let redeemer = redeemer(&transaction);
+        let (utxos, datums) = (ResolvedUtxos::new(), ResolvedDatums::new());
+
+        let inputs = context_inputs(&transaction, &utxos, &datums, Some(protocol::BABBAGE));
+        assert_eq!(
+            check(PlutusVersion::PlutusV3, &inputs, &redeemer)
+                .unwrap_err()
+                .to_string(),
+            "PlutusV3 scripts can't run under protocol version 7 (Babbage), they were introduced with protocol version 9"
+        );
+        assert!(check(PlutusVersion::PlutusV2, &inputs, &redeemer).is_ok());
+
+        let inputs = context_inputs(&transaction, &utxos, &datums, Some(protocol::CONWAY));
+        assert!(check(PlutusVersion::PlutusV3, &inputs, &redeemer).is_ok());
+    }
+
+    #[test]
+    fn byron_outputs_by_protocol_version() {
+        let bytes = hex::decode(BYRON_OUTPUT).unwrap();
+        let transaction = decode_transaction(&bytes).unwrap();
+        let redeemer = redeemer(&transaction);
+        let (utxos, datums) = (ResolvedUtxos::new(), ResolvedDatums::new());
+
+        let inputs = context_inputs(&transaction, &utxos, &datums, Some(protocol::BABBAGE));
+        let failure = check(PlutusVersion::PlutusV2, &inputs, &redeemer).unwrap_err();
+        assert!(
+            failure
+                .to_string()
+                .starts_with("ByronTxOutInContext: output 0 has a Byron address")
+        );
+        assert!(byron_warnings(&inputs).is_empty());
+
+        let inputs = context_inputs(&transaction, &utxos, &datums, Some(protocol::ALONZO));
+        assert!(check(PlutusVersion::PlutusV1, &inputs, &redeemer).is_ok());
+        let warnings = byron_warnings(&inputs);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("output 0 has a Byron address"));
+        assert!(warnings[0].ends_with("silently exclude it, as this one does"));
+
+        let inputs = context_inputs(&transaction, &utxos, &datums, None);
+        let warnings = byron_warnings(&inputs);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].ends_with("Use --protocol-version"));
+    }
+}
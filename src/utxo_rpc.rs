@@ -0,0 +1,151 @@
+use amaru_kernel::{Hash, TransactionInput};
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use serde::Deserialize;
+use tonic::metadata::{AsciiMetadataValue, MetadataKey};
+use utxorpc::{
+    CardanoQueryClient, ClientBuilder,
+    spec::{
+        query::TxoRef,
+        submit::{ReadMempoolRequest, submit_service_client::SubmitServiceClient},
+    },
+};
+
+use crate::{
+    output::decode_output,
+    resolver::{ResolvedUtxos, UtxoResolver},
+};
+
+fn default_header() -> String {
+    "dmtr-api-key".to_string()
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UtxoRpcConfig {
+    pub url: String,
+    pub key: Option<String>,
+    /// Metadata header carrying the API key; Demeter expects `dmtr-api-key`.
+    #[serde(default = "default_header")]
+    pub header: String,
+}
+
+/// Resolves UTxOs through a UTxO-RPC query service, such as Dolos or a Demeter endpoint.
+pub struct UtxoRpc {
+    config: UtxoRpcConfig,
+}
+
+impl UtxoRpc {
+    pub fn new(config: &UtxoRpcConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    async fn client(&self) -> Result<CardanoQueryClient> {
+        let mut builder = ClientBuilder::new()
+            .uri(&self.config.url)
+            .context(format!("Invalid UTxO-RPC endpoint {}", self.config.url))?;
+
+        if let Some(key) = &self.config.key {
+            builder = builder
+                .metadata(&self.config.header, key)
+                .context("Invalid UTxO-RPC API key")?;
+        }
+
+        Ok(builder.build::<CardanoQueryClient>().await)
+    }
+
+    /// The SDK has no wrapper for `ReadMempool`, so the generated submit client is used as is.
+    async fn read_mempool(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut client = SubmitServiceClient::connect(self.config.url.clone())
+            .await
+            .context(format!(
+                "Failed to connect to UTxO-RPC at {}",
+                self.config.url
+            ))?;
+
+        let mut request = tonic::Request::new(ReadMempoolRequest::default());
+        if let Some(key) = &self.config.key {
+            let header = MetadataKey::from_bytes(self.config.header.as_bytes())
+                .context("Invalid UTxO-RPC API key header")?;
+            let value: AsciiMetadataValue = key.parse().context("Invalid UTxO-RPC API key")?;
+            request.metadata_mut().insert(header, value);
+        }
+
+        let response = client
+            .read_mempool(request)
+            .await
+            .context("Failed to read the UTxO-RPC mempool")?;
+
+        Ok(response
+            .into_inner()
+            .items
+            .into_iter()
+            .map(|item| (item.r#ref.to_vec(), item.native_bytes.to_vec()))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl UtxoResolver for UtxoRpc {
+    async fn resolve(&self, inputs: &[TransactionInput]) -> Result<ResolvedUtxos> {
+        let refs = inputs
+            .iter()
+            .map(|input| TxoRef {
+                hash: input.transaction_id.to_vec().into(),
+                index: input.index as u32,
+            })
+            .collect();
+
+        let utxos = self
+            .client()
+            .await?
+            .read_utxos(refs)
+            .await
+            .context("Failed to fetch UTxOs from UTxO-RPC")?;
+
+        let mut resolved = ResolvedUtxos::new();
+        for utxo in utxos {
+            let txo_ref = utxo
+                .txo_ref
+                .ok_or_else(|| anyhow!("UTxO-RPC returned an output without its reference"))?;
+
+            let input = inputs
+                .iter()
+                .find(|input| {
+                    input.transaction_id.as_ref() == txo_ref.hash.as_ref()
+                        && input.index == txo_ref.index as u64
+                })
+                .ok_or_else(|| {
+                    anyhow!(
+                        "UTxO-RPC returned an unrequested output {}#{}",
+                        hex::encode(&txo_ref.hash),
+                        txo_ref.index
+                    )
+                })?;
+
+            let output = decode_output(&utxo.native).context(format!(
+                "Invalid output CBOR from UTxO-RPC for {}#{}",
+                hex::encode(&txo_ref.hash),
+                txo_ref.index
+            ))?;
+
+            resolved.insert(input.clone(), output);
+        }
+
+        Ok(resolved)
+    }
+
+    async fn fetch_mempool_transaction(&self, hash: &Hash<32>) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .read_mempool()
+            .await?
+            .into_iter()
+            .find(|(tx_hash, _)| tx_hash.as_slice() == hash.as_ref())
+            .map(|(_, transaction)| transaction))
+    }
+
+    fn name(&self) -> &'static str {
+        "UTxO-RPC"
+    }
+}